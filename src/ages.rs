@@ -0,0 +1,94 @@
+//! Coloring cells by how many generations they have been alive for.
+
+/// The colors of live cells on boards tracking ages.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AgeColors {
+    /// The color of cells born this generation.
+    pub born: [u8; 3],
+    /// The colors cells fade through as they get older, starting from
+    /// cells a generation old.
+    pub gradient: Vec<[u8; 3]>,
+    /// The age at which cells reach the last color of `gradient`.
+    pub span: u32,
+}
+
+impl Default for AgeColors {
+    fn default() -> Self {
+        Self {
+            born: [120, 255, 140],
+            gradient: vec![[255, 255, 255], [255, 200, 80], [200, 70, 40], [90, 40, 60]],
+            span: 100,
+        }
+    }
+}
+
+impl AgeColors {
+    /// The color of a cell which has been alive for `age` generations.
+    pub fn color(&self, age: u32) -> [u8; 3] {
+        let Some(&last) = self.gradient.last() else {
+            return self.born;
+        };
+
+        if age == 0 {
+            return self.born;
+        }
+        if self.span <= 1 {
+            return last;
+        }
+
+        // How far along the gradient the cell is, in steps of
+        // `1 / (span - 1)` of a color.
+        let steps = u64::from(self.span - 1);
+        let position = u64::from((age - 1).min(self.span - 1)) * (self.gradient.len() as u64 - 1);
+        let (index, rest) = ((position / steps) as usize, position % steps);
+
+        let Some(&next) = self.gradient.get(index + 1) else {
+            return last;
+        };
+        let from = self.gradient[index];
+
+        std::array::from_fn(|channel| {
+            let (from, to) = (i64::from(from[channel]), i64::from(next[channel]));
+            (from + (to - from) * rest as i64 / steps as i64) as u8
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_color() {
+        let colors = AgeColors {
+            born: [0, 255, 0],
+            gradient: vec![[255, 255, 255], [255, 0, 0], [0, 0, 0]],
+            span: 11,
+        };
+
+        assert_eq!(colors.color(0), [0, 255, 0]);
+        assert_eq!(colors.color(1), [255, 255, 255]);
+        assert_eq!(colors.color(3), [255, 153, 153]);
+        assert_eq!(colors.color(6), [255, 0, 0]);
+        assert_eq!(colors.color(9), [102, 0, 0]);
+        assert_eq!(colors.color(11), [0, 0, 0]);
+        assert_eq!(colors.color(u32::MAX), [0, 0, 0]);
+    }
+
+    #[test]
+    fn test_short_gradients() {
+        let single = AgeColors {
+            gradient: vec![[1, 2, 3]],
+            ..AgeColors::default()
+        };
+        assert_eq!(single.color(1), [1, 2, 3]);
+        assert_eq!(single.color(1000), [1, 2, 3]);
+
+        let instant = AgeColors {
+            span: 1,
+            ..AgeColors::default()
+        };
+        assert_eq!(instant.color(0), instant.born);
+        assert_eq!(instant.color(1), [90, 40, 60]);
+    }
+}
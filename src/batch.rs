@@ -0,0 +1,227 @@
+//! Running many random soups without a window, to search for interesting
+//! ones.
+
+use std::{
+    fmt::Write,
+    fs,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    thread,
+    time::Instant,
+};
+
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::{
+    census::Census,
+    cli::{BatchOptions, Options},
+    game_context::GameOfLifeBoard,
+    infinite_board::InfiniteBoard,
+    rule::Rule,
+};
+
+/// The longest period the population of the ash is expected to repeat with.
+const MAX_ASH_PERIOD: usize = 30;
+
+/// A soup counts as stabilized once its population has repeated for this
+/// many generations, which leaves time for gliders flying towards other
+/// objects to hit them.
+const STABLE_GENERATIONS: usize = 500;
+
+/// How a single soup ended up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Soup {
+    pub seed: u64,
+    /// The generation the soup stabilized at, or `None` if it was still
+    /// going after the maximum number of generations.
+    pub lifespan: Option<u64>,
+    pub population: usize,
+    pub census: Census,
+}
+
+/// Runs `batch.soups` soups on as many threads as asked for and writes a
+/// summary of them to `batch.output`.
+pub fn run(options: &Options, batch: &BatchOptions, rule: Rule) -> Result<(), String> {
+    let first_seed = options.seed.unwrap_or_else(rand::random);
+    let density = options.density.unwrap_or(0.5);
+    let threads = batch
+        .threads
+        .unwrap_or_else(|| thread::available_parallelism().map_or(1, |n| n.get()))
+        .min(batch.soups.try_into().unwrap_or(usize::MAX));
+
+    println!(
+        "Running {} soups of {}x{} from seed {first_seed} on {threads} threads",
+        batch.soups, options.size.0, options.size.1
+    );
+
+    let started = Instant::now();
+    let next = AtomicU64::new(0);
+    let soups = Mutex::new(Vec::new());
+
+    thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                if index >= batch.soups {
+                    return;
+                }
+
+                let seed = first_seed.wrapping_add(index);
+                let soup = run_soup(seed, options.size, density, &rule, batch.max_generations);
+                soups.lock().unwrap().push(soup);
+            });
+        }
+    });
+
+    let mut soups = soups.into_inner().unwrap();
+    soups.sort_unstable_by_key(|soup| soup.seed.wrapping_sub(first_seed));
+
+    let summary = summary(&soups, options, &rule);
+    fs::write(&batch.output, &summary)
+        .map_err(|e| format!("could not write `{}`: {e}", batch.output.display()))?;
+
+    println!(
+        "Finished in {:?}, wrote the summary to `{}`",
+        started.elapsed(),
+        batch.output.display()
+    );
+    if let Some(longest) = soups.iter().max_by_key(|soup| soup.lifespan) {
+        if let Some(lifespan) = longest.lifespan {
+            println!(
+                "Longest lived soup: seed {} ({lifespan} generations)",
+                longest.seed
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Runs the soup seeded with `seed` until it stabilizes or `max_generations`
+/// have passed.
+pub fn run_soup(
+    seed: u64,
+    size: (usize, usize),
+    density: f64,
+    rule: &Rule,
+    max_generations: u64,
+) -> Soup {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut board =
+        InfiniteBoard::from_alive_cells(crate::initial_cells(None, &mut rng, size, density));
+    let mut populations = vec![board.population()];
+
+    while (populations.len() as u64) <= max_generations {
+        if let Some(lifespan) = stabilized_at(&populations) {
+            return Soup {
+                seed,
+                lifespan: Some(lifespan),
+                population: board.population(),
+                census: Census::take(&board, rule),
+            };
+        }
+
+        board = board.tick(rule);
+        populations.push(board.population());
+    }
+
+    Soup {
+        seed,
+        lifespan: None,
+        population: board.population(),
+        census: Census::default(),
+    }
+}
+
+/// Returns the generation from which `populations` has been repeating, if
+/// it has been repeating with a period of at most [`MAX_ASH_PERIOD`] for the
+/// last [`STABLE_GENERATIONS`] generations.
+fn stabilized_at(populations: &[usize]) -> Option<u64> {
+    let repeats = |generation: usize, period: usize| {
+        populations[generation] == populations[generation + period]
+    };
+
+    let last = populations.len().checked_sub(1)?;
+    let period = (1..=MAX_ASH_PERIOD).find(|&period| {
+        last >= STABLE_GENERATIONS + period
+            && (last - STABLE_GENERATIONS - period..=last - period)
+                .all(|generation| repeats(generation, period))
+    })?;
+
+    let start = (0..last - period)
+        .rev()
+        .find(|&generation| !repeats(generation, period))
+        .map_or(0, |generation| generation + 1);
+
+    Some(start as u64)
+}
+
+/// A table of every soup followed by the census of all of them.
+fn summary(soups: &[Soup], options: &Options, rule: &Rule) -> String {
+    let mut summary = String::new();
+    let mut census = Census::default();
+
+    let _ = writeln!(
+        summary,
+        "# {} soups of {}x{} at density {}, rule {rule}",
+        soups.len(),
+        options.size.0,
+        options.size.1,
+        options.density.unwrap_or(0.5),
+    );
+    let _ = writeln!(summary, "# seed lifespan population objects");
+
+    for soup in soups {
+        let lifespan = soup
+            .lifespan
+            .map_or_else(|| "unstable".to_string(), |lifespan| lifespan.to_string());
+        let _ = writeln!(
+            summary,
+            "{} {lifespan} {} {}",
+            soup.seed,
+            soup.population,
+            soup.census.total()
+        );
+
+        census.add(&soup.census);
+    }
+
+    let _ = write!(summary, "\n# Census of every stabilized soup: {census}");
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stabilized_at() {
+        let mut populations = vec![10, 12, 9, 7];
+        assert_eq!(stabilized_at(&populations), None);
+
+        // A beacon and a pulsar, which repeat every 6 generations.
+        populations.extend((0..600).map(|g| [8, 6][g % 2] + [48, 56, 72][g % 3]));
+        assert_eq!(stabilized_at(&populations), Some(4));
+
+        assert_eq!(stabilized_at(&[4; STABLE_GENERATIONS]), None);
+        assert_eq!(stabilized_at(&[4; STABLE_GENERATIONS + 2]), Some(0));
+    }
+
+    #[test]
+    fn test_run_soup() {
+        let rule = Rule::CONWAY;
+        let soup = run_soup(1, (16, 16), 0.5, &rule, 100_000);
+
+        // Soups are reproducible from their seed.
+        assert_eq!(soup, run_soup(1, (16, 16), 0.5, &rule, 100_000));
+
+        let lifespan = soup.lifespan.expect("soup should stabilize");
+        assert!(soup.census.total() > 0);
+
+        let gave_up = run_soup(1, (16, 16), 0.5, &rule, lifespan / 2);
+        assert_eq!(gave_up.lifespan, None);
+        assert_eq!(gave_up.census, Census::default());
+    }
+}
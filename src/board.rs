@@ -1,28 +1,90 @@
 use std::{
     collections::HashSet,
     fmt::{self, Write},
+    ops::Range,
+    thread,
 };
 
-use rand::{rngs::ThreadRng, Rng};
+use rand::Rng;
 
-use crate::game_context::GameOfLifeBoard;
+use crate::{
+    game_context::{CellEdit, GameOfLifeBoard},
+    pattern::{Pattern, PatternError},
+    rule::Rule,
+};
+
+/// Boards with fewer live cells than this aren't worth splitting up between
+/// threads.
+const PAR_TICK_THRESHOLD: usize = 10_000;
 
+/// A bounded or wrapping grid of cells, sized when it's created.
 #[derive(Clone)]
-pub struct Board<const WIDTH: usize, const HEIGHT: usize> {
-    cells: Box<[[Cell; WIDTH]; HEIGHT]>,
+pub struct Board {
+    width: usize,
+    height: usize,
+    /// The cells row by row from the top left.
+    cells: Box<[Cell]>,
     alive_cells: Vec<(usize, usize)>,
+    topology: Topology,
+    /// The generations every live cell has been alive for, laid out like
+    /// `cells`, on boards tracking ages. Left out when comparing boards.
+    ages: Option<Box<[u32]>>,
 }
 
-impl<const WIDTH: usize, const HEIGHT: usize> Board<WIDTH, HEIGHT> {
-    pub fn new() -> Self {
-        Self {
-            cells: Box::new([[Cell::empty(); WIDTH]; HEIGHT]),
-            alive_cells: Vec::new(),
+/// How cells on the edges of a [`Board`] find their neighbours.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Topology {
+    /// Everything outside of the board is dead.
+    #[default]
+    Bounded,
+    /// The board wraps around on both axes, so a pattern leaving one edge
+    /// comes back in on the opposite edge.
+    Torus,
+}
+
+/// The part of a [`Board`] which stays put when it's resized.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Anchor {
+    #[default]
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl Anchor {
+    /// How far along each axis the anchor is, in halves of the board.
+    fn halves(self) -> (isize, isize) {
+        match self {
+            Anchor::TopLeft => (0, 0),
+            Anchor::Top => (1, 0),
+            Anchor::TopRight => (2, 0),
+            Anchor::Left => (0, 1),
+            Anchor::Center => (1, 1),
+            Anchor::Right => (2, 1),
+            Anchor::BottomLeft => (0, 2),
+            Anchor::Bottom => (1, 2),
+            Anchor::BottomRight => (2, 2),
         }
     }
+}
+
+impl Board {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self::with_alive_capacity(width, height, 0)
+    }
 
+    /// Creates a board just large enough for `cells`, which are given row by
+    /// row with anything but 0 alive.
+    #[cfg(test)]
     pub fn from_cells(cells: &[&[usize]]) -> Self {
-        let mut board = Self::new();
+        let width = cells.iter().map(|row| row.len()).max().unwrap_or(0);
+        let mut board = Self::new(width, cells.len());
 
         for (y, row) in cells.into_iter().enumerate() {
             for (x, cell) in row.into_iter().enumerate() {
@@ -35,28 +97,66 @@ impl<const WIDTH: usize, const HEIGHT: usize> Board<WIDTH, HEIGHT> {
         board
     }
 
-    pub fn with_alive_capacity(capacity: usize) -> Self {
+    /// Creates a `width` by `height` board with `pattern` placed at
+    /// `anchor`.
+    pub fn from_pattern(
+        pattern: &Pattern,
+        width: usize,
+        height: usize,
+        anchor: Anchor,
+    ) -> Result<Self, PatternError> {
+        if pattern.width > width || pattern.height > height {
+            return Err(PatternError::TooLarge {
+                width: pattern.width,
+                height: pattern.height,
+                board_width: width,
+                board_height: height,
+            });
+        }
+
+        let mut board =
+            Self::with_alive_capacity(pattern.width, pattern.height, pattern.cells.len());
+
+        for &(x, y) in &pattern.cells {
+            if board.cell(x, y).is_dead() {
+                board.make_alive(x, y);
+            }
+        }
+
+        Ok(board.resized(width, height, anchor))
+    }
+
+    pub fn with_alive_capacity(width: usize, height: usize, capacity: usize) -> Self {
         Self {
-            cells: Box::new([[Cell::empty(); WIDTH]; HEIGHT]),
+            width,
+            height,
+            cells: vec![Cell::empty(); width * height].into_boxed_slice(),
             alive_cells: Vec::with_capacity(capacity),
+            topology: Topology::default(),
+            ages: None,
         }
     }
 
-    pub fn full() -> Self {
+    #[cfg(test)]
+    pub fn full(width: usize, height: usize) -> Self {
         Self {
-            cells: Box::new([[Cell::new(true, 8); WIDTH]; HEIGHT]),
-            alive_cells: (0..HEIGHT)
-                .map(|y| (0..WIDTH).map(move |x| (x, y)))
+            width,
+            height,
+            cells: vec![Cell::new(true, 8); width * height].into_boxed_slice(),
+            alive_cells: (0..height)
+                .map(|y| (0..width).map(move |x| (x, y)))
                 .flatten()
                 .collect(),
+            topology: Topology::default(),
+            ages: None,
         }
     }
 
-    pub fn rand(rng: &mut ThreadRng, p: f64) -> Self {
-        let mut board = Self::with_alive_capacity((HEIGHT * WIDTH) / 2);
+    pub fn rand(width: usize, height: usize, rng: &mut impl Rng, p: f64) -> Self {
+        let mut board = Self::with_alive_capacity(width, height, (height * width) / 2);
 
-        for y in 0..HEIGHT {
-            for x in 0..WIDTH {
+        for y in 0..height {
+            for x in 0..width {
                 if rng.gen_bool(p) {
                     board.make_alive(x, y);
                 }
@@ -66,73 +166,367 @@ impl<const WIDTH: usize, const HEIGHT: usize> Board<WIDTH, HEIGHT> {
         board
     }
 
-    pub fn cells(&self) -> &[[Cell; WIDTH]; HEIGHT] {
-        &self.cells
+    /// Returns the board resized to `width` by `height`, with `anchor`
+    /// staying in place. Cells which end up outside of the board are
+    /// dropped.
+    pub fn resized(self, width: usize, height: usize, anchor: Anchor) -> Self {
+        let (horizontal, vertical) = anchor.halves();
+        let offset_x = (width as isize - self.width as isize) * horizontal / 2;
+        let offset_y = (height as isize - self.height as isize) * vertical / 2;
+
+        let mut board = Self::with_alive_capacity(width, height, self.alive_cells.len());
+        board.topology = self.topology;
+        if self.ages.is_some() {
+            board = board.with_ages();
+        }
+
+        for (old_x, old_y) in self.alive_cells.iter().cloned() {
+            let (Some(x), Some(y)) = (
+                old_x.checked_add_signed(offset_x),
+                old_y.checked_add_signed(offset_y),
+            ) else {
+                continue;
+            };
+
+            if x < width && y < height {
+                board.make_alive(x, y);
+
+                if let (Some(ages), Some(old_ages)) = (&mut board.ages, &self.ages) {
+                    ages[y * width + x] = old_ages[old_y * self.width + old_x];
+                }
+            }
+        }
+
+        board
+    }
+
+    /// Sets the state of every cell in `edits`, ignoring cells outside of
+    /// the board.
+    pub fn set_cells(&mut self, edits: &[CellEdit]) {
+        // Killed cells are removed from `alive_cells` all at once at the end.
+        let mut killed = HashSet::new();
+
+        for edit in edits {
+            let (Ok(x), Ok(y)) = (usize::try_from(edit.x), usize::try_from(edit.y)) else {
+                continue;
+            };
+
+            if x >= self.width || y >= self.height || self.cell(x, y).is_alive() == edit.alive {
+                continue;
+            }
+
+            if edit.alive {
+                if !killed.remove(&(x, y)) {
+                    self.alive_cells.push((x, y));
+                }
+
+                self.propagate_neighbours(x, y);
+                self.cell_mut(x, y).make_alive();
+
+                if let Some(ages) = &mut self.ages {
+                    ages[y * self.width + x] = 0;
+                }
+            } else {
+                killed.insert((x, y));
+
+                for (other_x, other_y) in
+                    get_adjacents(x, y, self.width, self.height, self.topology)
+                {
+                    self.cell_mut(other_x, other_y).remove_neighbour();
+                }
+                self.cell_mut(x, y).make_dead();
+            }
+        }
+
+        if !killed.is_empty() {
+            self.alive_cells.retain(|cell| !killed.contains(cell));
+        }
+    }
+
+    /// Returns the board with its edges behaving according to `topology`,
+    /// recounting the neighbours of every cell.
+    pub fn with_topology(self, topology: Topology) -> Self {
+        let mut board = Self::with_alive_capacity(self.width, self.height, self.alive_cells.len());
+        board.topology = topology;
+
+        for (x, y) in self.alive_cells {
+            board.make_alive(x, y);
+        }
+
+        board.ages = self.ages;
+        board
+    }
+
+    /// Returns the board keeping track of how many generations every cell
+    /// has been alive for, with the cells alive now starting out at 0.
+    pub fn with_ages(mut self) -> Self {
+        if self.ages.is_none() {
+            self.ages = Some(vec![0; self.cells.len()].into_boxed_slice());
+        }
+
+        self
+    }
+
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
     }
 
     pub fn alive_cells(&self) -> &Vec<(usize, usize)> {
         &self.alive_cells
     }
 
-    pub fn tick(&self) -> Self {
-        let mut board = Self::with_alive_capacity(self.alive_cells.len());
+    pub fn tick(&self, rule: &Rule) -> Self {
+        let mut board = Self::with_alive_capacity(self.width, self.height, self.alive_cells.len());
+        board.topology = self.topology;
 
         for (x, y) in self.alive_cells.iter().cloned() {
-            if self.cells[y][x].next_alive_state() {
+            if self.cell(x, y).next_alive_state(rule) {
                 board.make_alive(x, y);
             }
 
-            for (x, y) in get_adjacents(x, y) {
-                if x >= WIDTH || y >= HEIGHT {
-                    continue;
-                }
-
-                if self.cells[y][x].is_dead()
-                    && board.cells[y][x].is_dead()
-                    && self.cells[y][x].next_dead_state()
+            for (x, y) in get_adjacents(x, y, self.width, self.height, self.topology) {
+                if self.cell(x, y).is_dead()
+                    && board.cell(x, y).is_dead()
+                    && self.cell(x, y).next_dead_state(rule)
                 {
                     board.make_alive(x, y);
                 }
             }
         }
 
+        board.ages = self.next_ages(&board);
         board
     }
 
+    /// The same as [`Board::tick`], but the board is split into `threads`
+    /// horizontal strips which are simulated in parallel.
+    pub fn par_tick(&self, rule: &Rule, threads: usize) -> Self {
+        let (width, height) = (self.width, self.height);
+        let strip_height = height.div_ceil(threads.clamp(1, height.max(1))).max(1);
+        let strips: Vec<Range<usize>> = (0..height)
+            .step_by(strip_height)
+            .map(|start| start..(start + strip_height).min(height))
+            .collect();
+
+        let alive_rows = self.rows_of(&self.alive_cells);
+
+        let next_alive_cells: Vec<Vec<(usize, usize)>> = thread::scope(|scope| {
+            let handles: Vec<_> = strips
+                .iter()
+                .map(|strip| {
+                    let alive_rows = &alive_rows;
+                    scope.spawn(move || self.next_alive_cells_in(rule, alive_rows, strip.clone()))
+                })
+                .collect();
+
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .collect()
+        });
+
+        let next_alive_rows = self.rows_of(next_alive_cells.iter().flatten());
+        let mut board = Self::new(width, height);
+        board.topology = self.topology;
+
+        // Empty boards have no cells to split up.
+        let strip_cells = (strip_height * width).max(1);
+
+        thread::scope(|scope| {
+            for (strip, cells) in strips.iter().zip(board.cells.chunks_mut(strip_cells)) {
+                let next_alive_rows = &next_alive_rows;
+                let topology = self.topology;
+                scope.spawn(move || {
+                    Self::fill_strip(
+                        cells,
+                        (width, height),
+                        strip.clone(),
+                        next_alive_rows,
+                        topology,
+                    )
+                });
+            }
+        });
+
+        board.alive_cells = next_alive_cells.concat();
+        board.ages = self.next_ages(&board);
+        board
+    }
+
+    /// The ages of the cells of `next`, the generation after this one, if
+    /// this board tracks ages.
+    fn next_ages(&self, next: &Self) -> Option<Box<[u32]>> {
+        let ages = self.ages.as_ref()?;
+        let mut next_ages = vec![0; ages.len()].into_boxed_slice();
+
+        for &(x, y) in &next.alive_cells {
+            let index = y * self.width + x;
+
+            if self.cells[index].is_alive() {
+                next_ages[index] = ages[index].saturating_add(1);
+            }
+        }
+
+        Some(next_ages)
+    }
+
+    /// Returns the cells in `strip` which are alive next generation.
+    fn next_alive_cells_in(
+        &self,
+        rule: &Rule,
+        alive_rows: &[Vec<usize>],
+        strip: Range<usize>,
+    ) -> Vec<(usize, usize)> {
+        let mut next_alive_cells = Vec::new();
+        let mut is_born = vec![false; strip.len() * self.width];
+
+        for y in halo_rows(strip.clone(), self.height, self.topology) {
+            for &x in &alive_rows[y] {
+                if strip.contains(&y) && self.cell(x, y).next_alive_state(rule) {
+                    next_alive_cells.push((x, y));
+                }
+
+                for (x, y) in get_adjacents(x, y, self.width, self.height, self.topology) {
+                    if !strip.contains(&y) {
+                        continue;
+                    }
+
+                    let index = (y - strip.start) * self.width + x;
+
+                    if self.cell(x, y).is_dead()
+                        && !is_born[index]
+                        && self.cell(x, y).next_dead_state(rule)
+                    {
+                        is_born[index] = true;
+                        next_alive_cells.push((x, y));
+                    }
+                }
+            }
+        }
+
+        next_alive_cells
+    }
+
+    /// Fills in the rows of `strip`, which are `cells`, from the live cells
+    /// in and around it.
+    fn fill_strip(
+        cells: &mut [Cell],
+        (width, height): (usize, usize),
+        strip: Range<usize>,
+        alive_rows: &[Vec<usize>],
+        topology: Topology,
+    ) {
+        for y in halo_rows(strip.clone(), height, topology) {
+            for &x in &alive_rows[y] {
+                if strip.contains(&y) {
+                    cells[(y - strip.start) * width + x].make_alive();
+                }
+
+                for (x, y) in get_adjacents(x, y, width, height, topology) {
+                    if strip.contains(&y) {
+                        cells[(y - strip.start) * width + x].add_neighbour();
+                    }
+                }
+            }
+        }
+    }
+
+    /// Groups the x positions of `cells` by row.
+    fn rows_of<'a>(&self, cells: impl IntoIterator<Item = &'a (usize, usize)>) -> Vec<Vec<usize>> {
+        let mut rows = vec![Vec::new(); self.height];
+
+        for &(x, y) in cells {
+            rows[y].push(x);
+        }
+
+        rows
+    }
+
+    #[inline]
+    fn cell(&self, x: usize, y: usize) -> Cell {
+        self.cells[y * self.width + x]
+    }
+
+    #[inline]
+    fn cell_mut(&mut self, x: usize, y: usize) -> &mut Cell {
+        &mut self.cells[y * self.width + x]
+    }
+
     fn make_alive(&mut self, x: usize, y: usize) {
+        if let Some(ages) = &mut self.ages {
+            ages[y * self.width + x] = 0;
+        }
+
         self.alive_cells.push((x, y));
         self.propagate_neighbours(x, y);
-        self.cells[y][x].make_alive();
+        self.cell_mut(x, y).make_alive();
     }
 
     fn propagate_neighbours(&mut self, x: usize, y: usize) {
-        for (other_x, other_y) in get_adjacents(x, y) {
-            if other_x >= WIDTH || other_y >= HEIGHT {
-                continue;
-            }
-
-            self.cells[other_y][other_x].0 += 1
+        for (other_x, other_y) in get_adjacents(x, y, self.width, self.height, self.topology) {
+            self.cell_mut(other_x, other_y).add_neighbour();
         }
     }
 }
 
-impl<const WIDTH: usize, const HEIGHT: usize> GameOfLifeBoard for Board<WIDTH, HEIGHT> {
-    fn alive_cells(&self) -> &[(usize, usize)] {
-        &self.alive_cells
+impl GameOfLifeBoard for Board {
+    fn alive_cells(&self) -> impl Iterator<Item = (isize, isize)> + '_ {
+        self.alive_cells
+            .iter()
+            .map(|&(x, y)| (x as isize, y as isize))
     }
 
-    fn tick(&self) -> Self {
-        self.tick()
+    fn tick(&self, rule: &Rule) -> Self {
+        if self.alive_cells.len() < PAR_TICK_THRESHOLD {
+            return self.tick(rule);
+        }
+
+        let threads = thread::available_parallelism().map_or(1, |threads| threads.get());
+        self.par_tick(rule, threads)
     }
 
     fn size(&self) -> Option<(usize, usize)> {
-        Some((WIDTH, HEIGHT))
+        Some((self.width, self.height))
+    }
+
+    fn is_alive(&self, x: isize, y: isize) -> bool {
+        match (usize::try_from(x), usize::try_from(y)) {
+            (Ok(x), Ok(y)) if x < self.width && y < self.height => self.cell(x, y).is_alive(),
+            _ => false,
+        }
+    }
+
+    fn set_cells(&mut self, edits: &[CellEdit]) {
+        self.set_cells(edits)
+    }
+
+    fn age(&self, x: isize, y: isize) -> Option<u32> {
+        let ages = self.ages.as_ref()?;
+
+        match (usize::try_from(x), usize::try_from(y)) {
+            (Ok(x), Ok(y)) if x < self.width && y < self.height && self.cell(x, y).is_alive() => {
+                Some(ages[y * self.width + x])
+            }
+            _ => None,
+        }
     }
 }
 
-impl<const WIDTH: usize, const HEIGHT: usize> PartialEq for Board<WIDTH, HEIGHT> {
+impl PartialEq for Board {
     fn eq(&self, other: &Self) -> bool {
-        if self.alive_cells.len() != other.alive_cells.len() {
+        if self.width != other.width
+            || self.height != other.height
+            || self.topology != other.topology
+            || self.alive_cells.len() != other.alive_cells.len()
+        {
             return false;
         }
 
@@ -144,27 +538,13 @@ impl<const WIDTH: usize, const HEIGHT: usize> PartialEq for Board<WIDTH, HEIGHT>
             }
         }
 
-        if self.cells.len() != other.cells.len() {
-            return false;
-        }
-
-        self.cells
-            .iter()
-            .zip(other.cells.iter())
-            .map(|(a, b)| {
-                if a.len() != b.len() {
-                    return false;
-                }
-
-                a.iter().zip(b.iter()).map(|(a, b)| a == b).all(|v| v)
-            })
-            .all(|v| v)
+        self.cells == other.cells
     }
 }
 
-impl<const WIDTH: usize, const HEIGHT: usize> fmt::Debug for Board<WIDTH, HEIGHT> {
+impl fmt::Debug for Board {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        for row in self.cells.iter() {
+        for row in self.cells.chunks(self.width.max(1)) {
             f.write_char('\n')?;
 
             for cell in row {
@@ -181,18 +561,62 @@ impl<const WIDTH: usize, const HEIGHT: usize> fmt::Debug for Board<WIDTH, HEIGHT
     }
 }
 
-fn get_adjacents(to_x: usize, to_y: usize) -> impl Iterator<Item = (usize, usize)> {
-    (-1isize..=1)
-        .map(move |y| {
-            (-1isize..=1).filter_map(move |x| {
-                if x == 0 && y == 0 {
-                    None
-                } else {
-                    Some((to_x.checked_add_signed(x)?, to_y.checked_add_signed(y)?))
-                }
-            })
+fn get_adjacents(
+    to_x: usize,
+    to_y: usize,
+    width: usize,
+    height: usize,
+    topology: Topology,
+) -> impl Iterator<Item = (usize, usize)> {
+    let columns = adjacent_indices(to_x, width, topology);
+    let rows = adjacent_indices(to_y, height, topology);
+
+    rows.into_iter().enumerate().flat_map(move |(j, y)| {
+        columns.into_iter().enumerate().filter_map(move |(i, x)| {
+            if i == 1 && j == 1 {
+                return None;
+            }
+
+            Some((x?, y?))
         })
-        .flatten()
+    })
+}
+
+/// The indices before, at and after `i` on an axis `len` cells long, with
+/// `None` for those off the edge of the board.
+///
+/// On a torus shorter than 3 cells the wrapped indices coincide with each
+/// other or with `i`, and are only given once.
+#[inline]
+fn adjacent_indices(i: usize, len: usize, topology: Topology) -> [Option<usize>; 3] {
+    match topology {
+        Topology::Bounded => [i.checked_sub(1), Some(i), Some(i + 1).filter(|&i| i < len)],
+        Topology::Torus => {
+            let before = if i == 0 { len - 1 } else { i - 1 };
+            let after = if i + 1 == len { 0 } else { i + 1 };
+
+            [
+                Some(before).filter(|&before| before != i),
+                Some(i),
+                Some(after).filter(|&after| after != i && after != before),
+            ]
+        }
+    }
+}
+
+/// Returns the rows a strip of the board depends on, which are its own rows
+/// plus a row of halo on either side.
+fn halo_rows(strip: Range<usize>, height: usize, topology: Topology) -> Vec<usize> {
+    let mut rows: Vec<usize> = match topology {
+        Topology::Bounded => (strip.start.saturating_sub(1)..(strip.end + 1).min(height)).collect(),
+        Topology::Torus => (strip.start as isize - 1..strip.end as isize + 1)
+            .map(|y| y.rem_euclid(height as isize) as usize)
+            .collect(),
+    };
+
+    rows.sort_unstable();
+    rows.dedup();
+    rows
 }
 
 /// A single game of life cell.
@@ -243,30 +667,48 @@ impl Cell {
         self.0 &= !Self::LIVE;
     }
 
+    #[inline]
+    pub fn add_neighbour(&mut self) {
+        debug_assert!(
+            self.neighbour_count() < 8,
+            "integer overflow: neighbour_count (is {}) < 8",
+            self.neighbour_count()
+        );
+        self.0 += 1;
+    }
+
+    #[inline]
+    pub fn remove_neighbour(&mut self) {
+        debug_assert!(
+            self.neighbour_count() > 0,
+            "integer underflow: neighbour_count (is {}) > 0",
+            self.neighbour_count()
+        );
+        self.0 -= 1;
+    }
+
     #[inline]
     pub fn neighbour_count(self) -> i8 {
         self.0 & Self::NEIGHBOUR
     }
 
     #[inline]
-    pub fn next_state(self) -> bool {
+    pub fn next_state(self, rule: &Rule) -> bool {
         if self.is_alive() {
-            self.next_alive_state()
+            self.next_alive_state(rule)
         } else {
-            self.next_dead_state()
+            self.next_dead_state(rule)
         }
     }
 
     #[inline]
-    pub fn next_alive_state(self) -> bool {
-        let neighbour_count = self.neighbour_count();
-        neighbour_count >= 2 && neighbour_count <= 3
+    pub fn next_alive_state(self, rule: &Rule) -> bool {
+        rule.survives(self.neighbour_count())
     }
 
     #[inline]
-    pub fn next_dead_state(self) -> bool {
-        let neighbour_count = self.neighbour_count();
-        neighbour_count == 3
+    pub fn next_dead_state(self, rule: &Rule) -> bool {
+        rule.is_born(self.neighbour_count())
     }
 }
 
@@ -276,32 +718,32 @@ mod tests {
 
     #[test]
     fn test_neighbours_for_dead() {
-        assert!(!Cell::new(false, 8).next_state());
-        assert!(!Cell::new(false, 7).next_state());
-        assert!(!Cell::new(false, 6).next_state());
-        assert!(!Cell::new(false, 5).next_state());
-        assert!(!Cell::new(false, 4).next_state());
+        assert!(!Cell::new(false, 8).next_state(&Rule::CONWAY));
+        assert!(!Cell::new(false, 7).next_state(&Rule::CONWAY));
+        assert!(!Cell::new(false, 6).next_state(&Rule::CONWAY));
+        assert!(!Cell::new(false, 5).next_state(&Rule::CONWAY));
+        assert!(!Cell::new(false, 4).next_state(&Rule::CONWAY));
 
-        assert!(Cell::new(false, 3).next_state());
+        assert!(Cell::new(false, 3).next_state(&Rule::CONWAY));
 
-        assert!(!Cell::new(false, 2).next_state());
-        assert!(!Cell::new(false, 1).next_state());
-        assert!(!Cell::new(false, 0).next_state());
+        assert!(!Cell::new(false, 2).next_state(&Rule::CONWAY));
+        assert!(!Cell::new(false, 1).next_state(&Rule::CONWAY));
+        assert!(!Cell::new(false, 0).next_state(&Rule::CONWAY));
     }
 
     #[test]
     fn test_neighbours_for_alive() {
-        assert!(!Cell::new(true, 8).next_state());
-        assert!(!Cell::new(true, 7).next_state());
-        assert!(!Cell::new(true, 6).next_state());
-        assert!(!Cell::new(true, 5).next_state());
-        assert!(!Cell::new(true, 4).next_state());
+        assert!(!Cell::new(true, 8).next_state(&Rule::CONWAY));
+        assert!(!Cell::new(true, 7).next_state(&Rule::CONWAY));
+        assert!(!Cell::new(true, 6).next_state(&Rule::CONWAY));
+        assert!(!Cell::new(true, 5).next_state(&Rule::CONWAY));
+        assert!(!Cell::new(true, 4).next_state(&Rule::CONWAY));
 
-        assert!(Cell::new(true, 3).next_state());
-        assert!(Cell::new(true, 2).next_state());
+        assert!(Cell::new(true, 3).next_state(&Rule::CONWAY));
+        assert!(Cell::new(true, 2).next_state(&Rule::CONWAY));
 
-        assert!(!Cell::new(true, 1).next_state());
-        assert!(!Cell::new(true, 0).next_state());
+        assert!(!Cell::new(true, 1).next_state(&Rule::CONWAY));
+        assert!(!Cell::new(true, 0).next_state(&Rule::CONWAY));
     }
 
     #[test]
@@ -334,17 +776,17 @@ mod tests {
     #[test]
     fn test_adjacents() {
         assert_eq!(
-            get_adjacents(0, 0).collect::<Vec<_>>(),
+            get_adjacents(0, 0, 3, 3, Topology::Bounded).collect::<Vec<_>>(),
             &[(1, 0), (0, 1), (1, 1)]
         );
 
         assert_eq!(
-            get_adjacents(1, 0).collect::<Vec<_>>(),
+            get_adjacents(1, 0, 3, 3, Topology::Bounded).collect::<Vec<_>>(),
             &[(0, 0), (2, 0), (0, 1), (1, 1), (2, 1)]
         );
 
         assert_eq!(
-            get_adjacents(1, 1).collect::<Vec<_>>(),
+            get_adjacents(1, 1, 3, 3, Topology::Bounded).collect::<Vec<_>>(),
             &[
                 (0, 0),
                 (1, 0),
@@ -358,9 +800,48 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_torus_adjacents() {
+        assert_eq!(
+            get_adjacents(0, 0, 3, 4, Topology::Torus).collect::<Vec<_>>(),
+            &[
+                (2, 3),
+                (0, 3),
+                (1, 3),
+                (2, 0),
+                (1, 0),
+                (2, 1),
+                (0, 1),
+                (1, 1)
+            ]
+        );
+
+        assert_eq!(
+            get_adjacents(2, 3, 3, 4, Topology::Torus).collect::<Vec<_>>(),
+            &[
+                (1, 2),
+                (2, 2),
+                (0, 2),
+                (1, 3),
+                (0, 3),
+                (1, 0),
+                (2, 0),
+                (0, 0)
+            ]
+        );
+
+        // Wrapping around tiny tori reaches the same cells more than once,
+        // which are only counted once.
+        assert_eq!(
+            get_adjacents(0, 1, 2, 3, Topology::Torus).collect::<Vec<_>>(),
+            &[(1, 0), (0, 0), (1, 1), (1, 2), (0, 2)]
+        );
+        assert_eq!(get_adjacents(0, 0, 1, 1, Topology::Torus).count(), 0);
+    }
+
     #[test]
     fn test_from_cells() {
-        let mut expected = Board::<3, 3>::new();
+        let mut expected = Board::new(3, 3);
         expected.make_alive(1, 0);
         expected.make_alive(1, 1);
         expected.make_alive(1, 2);
@@ -369,7 +850,7 @@ mod tests {
 
         assert_eq!(expected, board);
 
-        let mut expected = Board::<3, 3>::new();
+        let mut expected = Board::new(3, 3);
         expected.make_alive(0, 1);
         expected.make_alive(1, 1);
         expected.make_alive(2, 1);
@@ -381,26 +862,26 @@ mod tests {
 
     #[test]
     fn test_board_tick() {
-        let board: Board<3, 3> = Board::from_cells(&[&[0, 1, 0], &[0, 1, 0], &[0, 1, 0]]);
+        let board = Board::from_cells(&[&[0, 1, 0], &[0, 1, 0], &[0, 1, 0]]);
 
         let expected = Board::from_cells(&[&[0, 0, 0], &[1, 1, 1], &[0, 0, 0]]);
 
-        assert_eq!(board.tick(), expected)
+        assert_eq!(board.tick(&Rule::CONWAY), expected)
     }
 
     #[test]
     fn test_partial_eq() {
         assert_eq!(
-            Board::<3, 3>::from_cells(&[&[1, 1, 0], &[0, 1, 1], &[0, 1, 0],]),
+            Board::from_cells(&[&[1, 1, 0], &[0, 1, 1], &[0, 1, 0],]),
             Board::from_cells(&[&[1, 1, 0], &[0, 1, 1], &[0, 1, 0],]),
         );
 
         assert_ne!(
-            Board::<3, 3>::from_cells(&[&[0, 1, 1], &[0, 1, 0], &[1, 1, 1]]),
+            Board::from_cells(&[&[0, 1, 1], &[0, 1, 0], &[1, 1, 1]]),
             Board::from_cells(&[&[0, 1, 1], &[0, 1, 1], &[1, 1, 1]]),
         );
 
-        let board = Board::<3, 3>::from_cells(&[&[0, 1, 1], &[0, 1, 0], &[1, 1, 1]]);
+        let board = Board::from_cells(&[&[0, 1, 1], &[0, 1, 0], &[1, 1, 1]]);
 
         let expected = {
             let mut board = board.clone();
@@ -410,7 +891,7 @@ mod tests {
 
         assert_ne!(board, expected);
 
-        let board = Board::<3, 3>::from_cells(&[&[0, 1, 1], &[0, 1, 0], &[1, 1, 1]]);
+        let board = Board::from_cells(&[&[0, 1, 1], &[0, 1, 0], &[1, 1, 1]]);
 
         let expected = {
             let mut board = board.clone();
@@ -422,16 +903,29 @@ mod tests {
         assert_ne!(board, expected);
     }
 
-    fn test_simulation<const W: usize, const H: usize>(steps: &[&[&[usize]]]) {
+    /// A `width` by `height` board with `cells` in its top left corner.
+    fn sized(cells: &[&[usize]], width: usize, height: usize) -> Board {
+        Board::from_cells(cells).resized(width, height, Anchor::TopLeft)
+    }
+
+    fn test_simulation(size: (usize, usize), steps: &[&[&[usize]]]) {
+        test_simulation_with_rule(&Rule::CONWAY, size, steps)
+    }
+
+    fn test_simulation_with_rule(
+        rule: &Rule,
+        (width, height): (usize, usize),
+        steps: &[&[&[usize]]],
+    ) {
         let (initial, steps) = (&steps[0], &steps[1..]);
-        let mut board = Board::<W, H>::from_cells(initial);
+        let mut board = sized(initial, width, height);
 
         for next in steps.into_iter() {
-            board = board.tick();
+            board = board.tick(rule);
 
-            let expected: Board<W, H> = Board::from_cells(next);
+            let expected = sized(next, width, height);
 
-            for row in board.cells.iter() {
+            for row in board.cells.chunks(width) {
                 print!("[");
 
                 for cell in row {
@@ -449,7 +943,7 @@ mod tests {
 
             println!("----");
 
-            for row in expected.cells.iter() {
+            for row in expected.cells.chunks(width) {
                 print!("[");
 
                 for cell in row {
@@ -477,22 +971,409 @@ mod tests {
 
     #[test]
     fn test_blinker() {
-        test_simulation::<3, 3>(&[
-            &[&[0, 1, 0], &[0, 1, 0], &[0, 1, 0]],
-            &[&[0, 0, 0], &[1, 1, 1], &[0, 0, 0]],
-            &[&[0, 1, 0], &[0, 1, 0], &[0, 1, 0]],
-            &[&[0, 0, 0], &[1, 1, 1], &[0, 0, 0]],
-        ]);
+        test_simulation(
+            (3, 3),
+            &[
+                &[&[0, 1, 0], &[0, 1, 0], &[0, 1, 0]],
+                &[&[0, 0, 0], &[1, 1, 1], &[0, 0, 0]],
+                &[&[0, 1, 0], &[0, 1, 0], &[0, 1, 0]],
+                &[&[0, 0, 0], &[1, 1, 1], &[0, 0, 0]],
+            ],
+        );
     }
 
     #[test]
     fn test_glider() {
-        test_simulation::<4, 4>(&[
-            &[&[1, 0, 0], &[0, 1, 1], &[1, 1, 0]],
-            &[&[0, 1, 0], &[0, 0, 1], &[1, 1, 1]],
-            &[&[0, 0, 0], &[1, 0, 1], &[0, 1, 1], &[0, 1, 0]],
-            &[&[0, 0, 0], &[0, 0, 1], &[1, 0, 1], &[0, 1, 1]],
-            &[&[0, 0, 0, 0], &[0, 1, 0, 0], &[0, 0, 1, 1], &[0, 1, 1, 0]],
+        test_simulation(
+            (4, 4),
+            &[
+                &[&[1, 0, 0], &[0, 1, 1], &[1, 1, 0]],
+                &[&[0, 1, 0], &[0, 0, 1], &[1, 1, 1]],
+                &[&[0, 0, 0], &[1, 0, 1], &[0, 1, 1], &[0, 1, 0]],
+                &[&[0, 0, 0], &[0, 0, 1], &[1, 0, 1], &[0, 1, 1]],
+                &[&[0, 0, 0, 0], &[0, 1, 0, 0], &[0, 0, 1, 1], &[0, 1, 1, 0]],
+            ],
+        )
+    }
+
+    #[test]
+    fn test_highlife() {
+        let highlife = "B36/S23".parse().unwrap();
+
+        // The dead centre cell has six neighbours, so it is only born in HighLife.
+        test_simulation_with_rule(
+            &highlife,
+            (3, 3),
+            &[
+                &[&[1, 1, 1], &[1, 0, 1], &[1, 0, 0]],
+                &[&[1, 0, 1], &[1, 1, 1], &[0, 1, 0]],
+            ],
+        );
+
+        test_simulation(
+            (3, 3),
+            &[
+                &[&[1, 1, 1], &[1, 0, 1], &[1, 0, 0]],
+                &[&[1, 0, 1], &[1, 0, 1], &[0, 1, 0]],
+            ],
+        );
+    }
+
+    #[test]
+    fn test_seeds() {
+        let seeds = "B2/S".parse().unwrap();
+
+        test_simulation_with_rule(
+            &seeds,
+            (4, 3),
+            &[
+                &[&[0, 0, 0, 0], &[0, 1, 1, 0], &[0, 0, 0, 0]],
+                &[&[0, 1, 1, 0], &[0, 0, 0, 0], &[0, 1, 1, 0]],
+            ],
+        );
+    }
+
+    #[test]
+    fn test_day_and_night() {
+        let day_and_night = "B3678/S34678".parse().unwrap();
+
+        test_simulation_with_rule(
+            &day_and_night,
+            (3, 3),
+            &[
+                &[&[1, 1, 1], &[1, 1, 1], &[1, 1, 1]],
+                &[&[1, 0, 1], &[0, 1, 0], &[1, 0, 1]],
+            ],
+        );
+
+        test_simulation(
+            (3, 3),
+            &[
+                &[&[1, 1, 1], &[1, 1, 1], &[1, 1, 1]],
+                &[&[1, 0, 1], &[0, 0, 0], &[1, 0, 1]],
+            ],
+        );
+    }
+
+    const GLIDER: &[&[usize]] = &[&[0, 1, 0], &[0, 0, 1], &[1, 1, 1]];
+
+    #[test]
+    fn test_torus_glider_wraps_around() {
+        let start = sized(GLIDER, 8, 8).with_topology(Topology::Torus);
+        let mut board = start.clone();
+
+        // A glider moves one cell diagonally every four generations, so it
+        // needs 4 * 8 generations to travel across the whole board.
+        for generation in 1..=32 {
+            board = board.tick(&Rule::CONWAY);
+            assert_eq!(board.alive_cells().len(), 5);
+            assert_eq!(board == start, generation == 32, "generation {generation}");
+        }
+    }
+
+    #[test]
+    fn test_torus_glider_crosses_edge() {
+        // Fire a glider off the bottom right corner, it should come back in
+        // from the top left.
+        let mut board = Board::from_cells(&[
+            &[0, 0, 0, 0, 0, 0],
+            &[0, 0, 0, 0, 0, 0],
+            &[0, 0, 0, 0, 0, 0],
+            &[0, 0, 0, 0, 1, 0],
+            &[0, 0, 0, 0, 0, 1],
+            &[0, 0, 0, 1, 1, 1],
         ])
+        .with_topology(Topology::Torus);
+
+        for _ in 0..8 {
+            board = board.tick(&Rule::CONWAY);
+        }
+
+        let expected = Board::from_cells(&[
+            &[0, 1, 0, 0, 0, 0],
+            &[1, 1, 0, 0, 0, 1],
+            &[0, 0, 0, 0, 0, 0],
+            &[0, 0, 0, 0, 0, 0],
+            &[0, 0, 0, 0, 0, 0],
+            &[1, 0, 0, 0, 0, 0],
+        ])
+        .with_topology(Topology::Torus);
+
+        assert_eq!(board, expected);
+    }
+
+    #[test]
+    fn test_bounded_glider_dies_at_edge() {
+        let mut board = sized(GLIDER, 8, 8);
+
+        for _ in 0..32 {
+            board = board.tick(&Rule::CONWAY);
+        }
+
+        assert_ne!(board.alive_cells().len(), 5);
+    }
+
+    fn random_board(
+        (width, height): (usize, usize),
+        rng: &mut impl Rng,
+        p: f64,
+        topology: Topology,
+    ) -> Board {
+        let mut board = Board::new(width, height).with_topology(topology);
+
+        for y in 0..height {
+            for x in 0..width {
+                if rng.gen_bool(p) {
+                    board.make_alive(x, y);
+                }
+            }
+        }
+
+        board
+    }
+
+    #[test]
+    fn test_halo_rows() {
+        assert_eq!(halo_rows(0..3, 10, Topology::Bounded), &[0, 1, 2, 3]);
+        assert_eq!(halo_rows(3..6, 10, Topology::Bounded), &[2, 3, 4, 5, 6]);
+        assert_eq!(halo_rows(8..10, 10, Topology::Bounded), &[7, 8, 9]);
+        assert_eq!(halo_rows(0..3, 10, Topology::Torus), &[0, 1, 2, 3, 9]);
+        assert_eq!(
+            halo_rows(0..10, 10, Topology::Torus),
+            (0..10).collect::<Vec<_>>()
+        );
+        assert_eq!(halo_rows(1..2, 2, Topology::Torus), &[0, 1]);
+    }
+
+    #[test]
+    fn test_par_tick_matches_tick() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let rules: [Rule; 3] = [
+            Rule::CONWAY,
+            "B36/S23".parse().unwrap(),
+            "B3678/S34678".parse().unwrap(),
+        ];
+
+        for topology in [Topology::Bounded, Topology::Torus] {
+            for rule in &rules {
+                let p = rng.gen_range(0.1..0.9);
+                let mut board = random_board((37, 23), &mut rng, p, topology);
+
+                for generation in 0..16 {
+                    let threads = rng.gen_range(1..=30);
+                    let expected = board.tick(rule);
+                    let actual = board.par_tick(rule, threads);
+
+                    assert!(
+                        actual.cells == expected.cells,
+                        "{topology:?} {rule} generation {generation} with {threads} threads"
+                    );
+                    assert_eq!(actual, expected);
+
+                    board = actual;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_par_tick_small_boards() {
+        let board = Board::from_cells(&[&[0, 1, 0], &[0, 1, 0], &[0, 1, 0]]);
+        let expected = Board::from_cells(&[&[0, 0, 0], &[1, 1, 1], &[0, 0, 0]]);
+
+        for threads in [0, 1, 2, 3, 100] {
+            assert_eq!(board.par_tick(&Rule::CONWAY, threads), expected);
+        }
+
+        let board =
+            Board::from_cells(&[&[1, 1, 0, 0], &[0, 1, 0, 1]]).with_topology(Topology::Torus);
+
+        for threads in [1, 2] {
+            assert!(board.par_tick(&Rule::CONWAY, threads) == board.tick(&Rule::CONWAY));
+        }
+    }
+
+    #[test]
+    fn test_set_cells_matches_from_scratch() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(0);
+
+        for topology in [Topology::Bounded, Topology::Torus] {
+            let mut board = random_board((12, 9), &mut rng, 0.4, topology);
+
+            for _ in 0..8 {
+                // Edits go slightly past the edges to check they're ignored.
+                let edits: Vec<CellEdit> = (0..20)
+                    .map(|_| CellEdit {
+                        x: rng.gen_range(-2..14),
+                        y: rng.gen_range(-2..11),
+                        alive: rng.gen_bool(0.5),
+                    })
+                    .collect();
+
+                let mut expected = Board::new(12, 9).with_topology(topology);
+                let mut alive: HashSet<(isize, isize)> = board
+                    .alive_cells
+                    .iter()
+                    .map(|&(x, y)| (x as isize, y as isize))
+                    .collect();
+                for edit in &edits {
+                    if edit.alive {
+                        alive.insert((edit.x, edit.y));
+                    } else {
+                        alive.remove(&(edit.x, edit.y));
+                    }
+                }
+                for &(x, y) in &alive {
+                    if (0..12).contains(&x) && (0..9).contains(&y) {
+                        expected.make_alive(x as usize, y as usize);
+                    }
+                }
+
+                board.set_cells(&edits);
+                assert_eq!(board, expected, "{topology:?}");
+
+                board = board.tick(&Rule::CONWAY);
+            }
+        }
+    }
+
+    #[test]
+    fn test_rand_is_seeded() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let rand = |seed| Board::rand(64, 64, &mut StdRng::seed_from_u64(seed), 0.5);
+
+        assert_eq!(rand(42), rand(42));
+        assert_ne!(rand(42), rand(43));
+    }
+
+    #[test]
+    fn test_from_cells_size() {
+        let board = Board::from_cells(&[&[1], &[0, 0, 1], &[]]);
+
+        assert_eq!((board.width(), board.height()), (3, 3));
+        assert_ne!(board, sized(&[&[1], &[0, 0, 1]], 3, 2));
+        assert_eq!(board, sized(&[&[1], &[0, 0, 1]], 3, 3));
+    }
+
+    #[test]
+    fn test_resized() {
+        let board = Board::from_cells(&[&[1, 0, 0], &[0, 1, 0], &[0, 0, 1]]);
+
+        // Grows the board by two cells on both axes.
+        let cases: [(Anchor, &[&[usize]]); 9] = [
+            (Anchor::TopLeft, &[&[1, 0, 0], &[0, 1, 0], &[0, 0, 1]]),
+            (Anchor::Top, &[&[0, 1, 0], &[0, 0, 1], &[0, 0, 0, 1]]),
+            (
+                Anchor::TopRight,
+                &[&[0, 0, 1], &[0, 0, 0, 1], &[0, 0, 0, 0, 1]],
+            ),
+            (Anchor::Left, &[&[], &[1, 0, 0], &[0, 1, 0], &[0, 0, 1]]),
+            (
+                Anchor::Center,
+                &[&[], &[0, 1, 0], &[0, 0, 1], &[0, 0, 0, 1]],
+            ),
+            (
+                Anchor::Right,
+                &[&[], &[0, 0, 1], &[0, 0, 0, 1], &[0, 0, 0, 0, 1]],
+            ),
+            (
+                Anchor::BottomLeft,
+                &[&[], &[], &[1, 0, 0], &[0, 1, 0], &[0, 0, 1]],
+            ),
+            (
+                Anchor::Bottom,
+                &[&[], &[], &[0, 1, 0], &[0, 0, 1], &[0, 0, 0, 1]],
+            ),
+            (
+                Anchor::BottomRight,
+                &[&[], &[], &[0, 0, 1], &[0, 0, 0, 1], &[0, 0, 0, 0, 1]],
+            ),
+        ];
+
+        for (anchor, expected) in cases {
+            let resized = board.clone().resized(5, 5, anchor);
+
+            assert_eq!(resized, sized(expected, 5, 5), "{anchor:?}");
+            // Shrinking back with the same anchor undoes growing.
+            assert_eq!(resized.resized(3, 3, anchor), board, "{anchor:?}");
+        }
+    }
+
+    #[test]
+    fn test_from_pattern_anchored() {
+        let blinker = Pattern {
+            width: 3,
+            height: 1,
+            cells: vec![(0, 0), (1, 0), (2, 0)],
+            ..Pattern::default()
+        };
+
+        assert_eq!(
+            Board::from_pattern(&blinker, 5, 5, Anchor::Center),
+            Ok(sized(&[&[], &[], &[0, 1, 1, 1]], 5, 5))
+        );
+        assert_eq!(
+            Board::from_pattern(&blinker, 5, 3, Anchor::BottomRight),
+            Ok(sized(&[&[], &[], &[0, 0, 1, 1, 1]], 5, 3))
+        );
+        assert!(Board::from_pattern(&blinker, 2, 2, Anchor::Center).is_err());
+    }
+
+    #[test]
+    fn test_resized_crops() {
+        let board =
+            Board::from_cells(&[&[1, 0, 0, 1], &[0, 1, 1, 0], &[0, 1, 1, 0], &[1, 0, 0, 1]])
+                .with_topology(Topology::Torus);
+
+        let center = board.clone().resized(2, 2, Anchor::Center);
+        assert_eq!(center, Board::full(2, 2).with_topology(Topology::Torus));
+
+        let corner = board.resized(1, 1, Anchor::BottomRight);
+        assert_eq!(corner.alive_cells(), &[(0, 0)]);
+        assert_eq!(corner.topology(), Topology::Torus);
+    }
+
+    #[test]
+    fn test_ages() {
+        let ages = |board: &Board| -> Vec<Option<u32>> {
+            (0..9)
+                .map(|i| GameOfLifeBoard::age(board, i % 3, i / 3))
+                .collect()
+        };
+
+        let blinker = Board::from_cells(&[&[0, 1, 0], &[0, 1, 0], &[0, 1, 0]]);
+        assert_eq!(GameOfLifeBoard::age(&blinker, 1, 1), None);
+
+        let mut board = blinker.with_ages();
+        assert_eq!(ages(&board)[..3], [None, Some(0), None]);
+
+        for generation in 1..=4 {
+            let ticked = board.tick(&Rule::CONWAY);
+            assert_eq!(ages(&board.par_tick(&Rule::CONWAY, 2)), ages(&ticked));
+            board = ticked;
+
+            // The middle cell survives, while the ends are born again.
+            assert_eq!(ages(&board)[4], Some(generation));
+            assert_eq!(ages(&board).iter().flatten().sum::<u32>(), generation);
+        }
+
+        board.set_cells(&[CellEdit {
+            x: 0,
+            y: 0,
+            alive: true,
+        }]);
+        assert_eq!(ages(&board)[..5], [Some(0), Some(0), None, None, Some(4)]);
+
+        let board = board
+            .with_topology(Topology::Torus)
+            .resized(5, 5, Anchor::BottomRight);
+        assert_eq!(GameOfLifeBoard::age(&board, 3, 3), Some(4));
+        assert_eq!(GameOfLifeBoard::age(&board, 2, 2), Some(0));
+        assert_eq!(GameOfLifeBoard::age(&board, 0, 0), None);
     }
 }
@@ -0,0 +1,401 @@
+//! Splitting a board into separate objects and naming them with apgcodes,
+//! the names used by the Catagolue census of random soups.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+use crate::{
+    game_context::GameOfLifeBoard, infinite_board::InfiniteBoard, period::PeriodDetector,
+    rule::Rule,
+};
+
+/// Objects which don't repeat within this many generations of being
+/// separated from the rest of the board are named [`UNKNOWN`].
+const MAX_GENERATIONS: u64 = 1024;
+
+pub const UNKNOWN: &str = "zz_UNKNOWN";
+
+/// The digits of the Extended Wechsler format apgcodes are written in.
+const DIGITS: &[u8; 36] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+
+/// Objects with a common name, which is shown next to their apgcode.
+const COMMON_NAMES: &[(&str, &str)] = &[
+    ("xs4_33", "block"),
+    ("xs6_696", "beehive"),
+    ("xs7_2596", "loaf"),
+    ("xs5_253", "boat"),
+    ("xs6_356", "ship"),
+    ("xs4_252", "tub"),
+    ("xs8_6996", "pond"),
+    ("xs6_25a4", "barge"),
+    ("xs7_25ac", "long boat"),
+    ("xp2_7", "blinker"),
+    ("xp2_7e", "toad"),
+    ("xp2_318c", "beacon"),
+    ("xp3_co9nas0san9oczgoldlo0oldlogz1047210127401", "pulsar"),
+    ("xq4_153", "glider"),
+    ("xq4_6frc", "lightweight spaceship"),
+    ("xq4_27dee6", "middleweight spaceship"),
+    ("xq4_27deee6", "heavyweight spaceship"),
+];
+
+/// How many of each object a board contains.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Census {
+    /// The apgcode of every object and how many there are, from the most to
+    /// the least common.
+    pub objects: Vec<(String, usize)>,
+}
+
+impl Census {
+    /// Separates the live cells of `board` into objects and counts each kind
+    /// of object.
+    pub fn take<B: GameOfLifeBoard>(board: &B, rule: &Rule) -> Self {
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        // Ash is mostly made of the same few objects, so each shape is only
+        // simulated once.
+        let mut names: HashMap<Vec<(isize, isize)>, Option<String>> = HashMap::new();
+
+        for object in components(board.alive_cells()) {
+            let name = names
+                .entry(normalize(&object))
+                .or_insert_with(|| apgcode(&object, rule));
+
+            // Objects which die out aren't counted.
+            if let Some(name) = name {
+                *counts.entry(name.clone()).or_default() += 1;
+            }
+        }
+
+        Self::from_counts(counts)
+    }
+
+    /// Adds the objects counted by `other` to this census.
+    pub fn add(&mut self, other: &Census) {
+        let mut counts: HashMap<String, usize> = self.objects.drain(..).collect();
+
+        for (name, count) in &other.objects {
+            *counts.entry(name.clone()).or_default() += count;
+        }
+
+        *self = Self::from_counts(counts);
+    }
+
+    fn from_counts(counts: HashMap<String, usize>) -> Self {
+        let mut objects: Vec<(String, usize)> = counts.into_iter().collect();
+        objects.sort_unstable_by(|(a, a_count), (b, b_count)| {
+            b_count.cmp(a_count).then_with(|| a.cmp(b))
+        });
+
+        Self { objects }
+    }
+
+    /// The total number of objects.
+    pub fn total(&self) -> usize {
+        self.objects.iter().map(|(_, count)| count).sum()
+    }
+}
+
+impl fmt::Display for Census {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} objects", self.total())?;
+
+        for (name, count) in &self.objects {
+            match common_name(name) {
+                Some(common_name) => writeln!(f, "{count:>8}  {name} ({common_name})")?,
+                None => writeln!(f, "{count:>8}  {name}")?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+pub fn common_name(apgcode: &str) -> Option<&'static str> {
+    COMMON_NAMES
+        .iter()
+        .find(|(code, _)| *code == apgcode)
+        .map(|&(_, name)| name)
+}
+
+/// Groups `cells` into objects, where two cells belong to the same object if
+/// they are at most 2 cells apart in both directions, and so could affect a
+/// common neighbour.
+pub fn components(cells: impl Iterator<Item = (isize, isize)>) -> Vec<Vec<(isize, isize)>> {
+    let mut remaining: HashSet<(isize, isize)> = cells.collect();
+    let mut objects = Vec::new();
+
+    while let Some(&start) = remaining.iter().next() {
+        remaining.remove(&start);
+        let mut object = vec![start];
+        let mut next = 0;
+
+        while let Some(&(x, y)) = object.get(next) {
+            next += 1;
+
+            for dy in -2..=2 {
+                for dx in -2..=2 {
+                    if remaining.remove(&(x + dx, y + dy)) {
+                        object.push((x + dx, y + dy));
+                    }
+                }
+            }
+        }
+
+        objects.push(object);
+    }
+
+    objects
+}
+
+/// Names a single object by simulating it on its own under `rule`, or
+/// returns `None` if it dies out.
+pub fn apgcode(object: &[(isize, isize)], rule: &Rule) -> Option<String> {
+    let mut board = InfiniteBoard::from_alive_cells(object.iter().copied());
+    let mut detector = PeriodDetector::new();
+    let mut phases: Vec<Vec<(isize, isize)>> = Vec::new();
+
+    for generation in 0..=MAX_GENERATIONS {
+        if let Some(periodicity) = detector.observe(generation, &board) {
+            let cycle = &phases[periodicity.stabilized_at as usize..];
+            if cycle[0].is_empty() {
+                return None;
+            }
+
+            let code = cycle
+                .iter()
+                .map(|phase| wechsler(phase))
+                .min_by(|a, b| a.len().cmp(&b.len()).then_with(|| a.cmp(b)))
+                .unwrap_or_default();
+
+            let prefix = match (periodicity.period, periodicity.displacement) {
+                (1, (0, 0)) => format!("xs{}", cycle[0].len()),
+                (period, (0, 0)) => format!("xp{period}"),
+                (period, _) => format!("xq{period}"),
+            };
+
+            return Some(format!("{prefix}_{code}"));
+        }
+
+        phases.push(board.alive_cells().clone());
+        board = board.tick(rule);
+    }
+
+    Some(UNKNOWN.to_string())
+}
+
+/// Encodes `cells` in the Extended Wechsler format, picking the shortest of
+/// the encodings of its 8 rotations and reflections, and the first in
+/// alphabetical order of those.
+fn wechsler(cells: &[(isize, isize)]) -> String {
+    type Orientation = fn(isize, isize) -> (isize, isize);
+
+    let orientations: [Orientation; 8] = [
+        |x, y| (x, y),
+        |x, y| (-x, y),
+        |x, y| (x, -y),
+        |x, y| (-x, -y),
+        |x, y| (y, x),
+        |x, y| (-y, x),
+        |x, y| (y, -x),
+        |x, y| (-y, -x),
+    ];
+
+    orientations
+        .into_iter()
+        .map(|orient| {
+            let oriented: Vec<(isize, isize)> = cells.iter().map(|&(x, y)| orient(x, y)).collect();
+            encode(&normalize(&oriented))
+        })
+        .min_by(|a, b| a.len().cmp(&b.len()).then_with(|| a.cmp(b)))
+        .unwrap_or_default()
+}
+
+/// Encodes cells whose bounding box starts at the origin, 5 rows at a time,
+/// with each column of a strip written as a digit of its cells in binary and
+/// runs of empty columns abbreviated.
+fn encode(cells: &[(isize, isize)]) -> String {
+    let width = cells.iter().map(|&(x, _)| x + 1).max().unwrap_or(0) as usize;
+    let height = cells.iter().map(|&(_, y)| y + 1).max().unwrap_or(0) as usize;
+
+    let mut strips = vec![vec![0u8; width]; height.div_ceil(5)];
+    for &(x, y) in cells {
+        strips[y as usize / 5][x as usize] |= 1 << (y % 5);
+    }
+
+    let strips: Vec<String> = strips
+        .iter()
+        .map(|strip| {
+            let end = strip
+                .iter()
+                .rposition(|&column| column != 0)
+                .map_or(0, |i| i + 1);
+            let mut encoded = String::new();
+
+            for run in strip[..end].chunk_by(|a, b| *a == 0 && *b == 0) {
+                if run[0] != 0 {
+                    encoded.push(DIGITS[run[0] as usize] as char);
+                    continue;
+                }
+
+                let mut zeros = run.len();
+                while zeros > 0 {
+                    match zeros {
+                        1 => encoded.push('0'),
+                        2 => encoded.push('w'),
+                        3 => encoded.push('x'),
+                        _ => {
+                            let length = zeros.min(39);
+                            encoded.push('y');
+                            encoded.push(DIGITS[length - 4] as char);
+                            zeros -= length;
+                            continue;
+                        }
+                    }
+                    break;
+                }
+            }
+
+            encoded
+        })
+        .collect();
+
+    strips.join("z")
+}
+
+/// Moves `cells` so their bounding box starts at the origin, in a consistent
+/// order.
+fn normalize(cells: &[(isize, isize)]) -> Vec<(isize, isize)> {
+    let min_x = cells.iter().map(|&(x, _)| x).min().unwrap_or(0);
+    let min_y = cells.iter().map(|&(_, y)| y).min().unwrap_or(0);
+
+    let mut cells: Vec<(isize, isize)> =
+        cells.iter().map(|&(x, y)| (x - min_x, y - min_y)).collect();
+    cells.sort_unstable();
+    cells
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pattern::library;
+
+    fn library_apgcode(name: &str) -> String {
+        let entry = library::ENTRIES
+            .iter()
+            .find(|entry| entry.name == name)
+            .unwrap();
+
+        apgcode(
+            &entry.pattern().alive_cells().collect::<Vec<_>>(),
+            &Rule::CONWAY,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_apgcode() {
+        for (name, expected) in [
+            ("Block", "xs4_33"),
+            ("Beehive", "xs6_696"),
+            ("Loaf", "xs7_2596"),
+            ("Boat", "xs5_253"),
+            ("Tub", "xs4_252"),
+            ("Blinker", "xp2_7"),
+            ("Toad", "xp2_7e"),
+            ("Beacon", "xp2_318c"),
+            ("Pentadecathlon", "xp15_4r4z4r4"),
+            ("Glider", "xq4_153"),
+            ("Lightweight spaceship", "xq4_6frc"),
+            ("Middleweight spaceship", "xq4_27dee6"),
+            ("Heavyweight spaceship", "xq4_27deee6"),
+        ] {
+            assert_eq!(library_apgcode(name), expected, "{name}");
+        }
+    }
+
+    #[test]
+    fn test_apgcode_of_dying_object() {
+        assert_eq!(apgcode(&[(0, 0), (1, 0)], &Rule::CONWAY), None);
+        // Dies after a few generations rather than straight away.
+        assert_eq!(apgcode(&[(0, 0), (1, 1), (2, 2)], &Rule::CONWAY), None);
+
+        let board = InfiniteBoard::from_alive_cells([(0, 0), (1, 0), (10, 10)]);
+        assert_eq!(Census::take(&board, &Rule::CONWAY), Census::default());
+    }
+
+    #[test]
+    fn test_encode_runs_of_zeros() {
+        let columns = |xs: &[isize]| xs.iter().map(|&x| (x, 0)).collect::<Vec<_>>();
+
+        assert_eq!(encode(&columns(&[0, 2])), "101");
+        assert_eq!(encode(&columns(&[0, 3])), "1w1");
+        assert_eq!(encode(&columns(&[0, 4])), "1x1");
+        assert_eq!(encode(&columns(&[0, 5])), "1y01");
+        assert_eq!(encode(&columns(&[0, 41])), "1yz01");
+        // Every 5 rows start a new strip.
+        assert_eq!(encode(&[(0, 0), (0, 5)]), "1z1");
+    }
+
+    #[test]
+    fn test_components() {
+        let mut objects = components([(0, 0), (2, 2), (5, 2), (9, 9)].into_iter());
+        objects.iter_mut().for_each(|object| object.sort_unstable());
+        objects.sort_unstable();
+
+        assert_eq!(objects, [vec![(0, 0), (2, 2)], vec![(5, 2)], vec![(9, 9)]]);
+    }
+
+    #[test]
+    fn test_census() {
+        let block = [(0, 0), (1, 0), (0, 1), (1, 1)];
+        let blinker = [(0, 0), (1, 0), (2, 0)];
+        let beehive = [(1, 0), (2, 0), (0, 1), (3, 1), (1, 2), (2, 2)];
+
+        let at = |cells: &[(isize, isize)], (x, y): (isize, isize)| {
+            cells
+                .iter()
+                .map(move |&(dx, dy)| (x + dx, y + dy))
+                .collect::<Vec<_>>()
+        };
+        let board = InfiniteBoard::from_alive_cells(
+            [
+                at(&block, (0, 0)),
+                at(&block, (10, 0)),
+                at(&blinker, (0, 10)),
+                at(&beehive, (20, 20)),
+                at(&block, (-20, 5)),
+            ]
+            .concat(),
+        );
+
+        let census = Census::take(&board, &Rule::CONWAY);
+
+        assert_eq!(census.total(), 5);
+        assert_eq!(
+            census.objects,
+            [
+                ("xs4_33".to_string(), 3),
+                ("xp2_7".to_string(), 1),
+                ("xs6_696".to_string(), 1),
+            ]
+        );
+        assert!(census.to_string().contains("       3  xs4_33 (block)\n"));
+
+        let mut total = census.clone();
+        total.add(&Census {
+            objects: vec![("xp2_7".to_string(), 3), ("xq4_153".to_string(), 1)],
+        });
+        assert_eq!(
+            total.objects,
+            [
+                ("xp2_7".to_string(), 4),
+                ("xs4_33".to_string(), 3),
+                ("xq4_153".to_string(), 1),
+                ("xs6_696".to_string(), 1),
+            ]
+        );
+    }
+}
@@ -0,0 +1,906 @@
+use std::{fmt::Display, path::PathBuf, str::FromStr};
+
+use crate::{
+    ages::AgeColors,
+    board::{Anchor, Topology},
+    game_context::MAX_TICKED_STEP_LOG2,
+    hashlife::MAX_STEP_LOG2,
+    pattern::{png::PngOptions, Format},
+    recording::{GifOptions, Region},
+    rule::Rule,
+    simulation::Speed,
+};
+
+pub const HELP: &str = "\
+Conway's Game of Life in Rust
+
+USAGE:
+    game_of_life [OPTIONS]
+
+OPTIONS:
+    --board <KIND>          Board implementation: grid, packed, infinite or
+                            hashlife [default: grid]
+    --size <WxH>            Board size, or the size of the random soup on
+                            infinite boards [default: 1000x1000]
+    --topology <TOPOLOGY>   Edges of grid and packed boards: bounded or torus
+                            [default: bounded]
+    --density <P>           Chance of each cell in the random soup being alive,
+                            from 0 to 1 [default: 0.5]
+    --pattern <FILE>        Start from an RLE, plaintext, Life 1.06 or PNG
+                            pattern instead of a random soup
+    --anchor <ANCHOR>       Where the pattern goes on grid and packed boards:
+                            top-left, top, top-right, left, center, right,
+                            bottom-left, bottom or bottom-right
+                            [default: top-left]
+    --save <FILE>           File Ctrl+E exports the board to in the viewer, as
+                            RLE, plaintext or Life 1.06 depending on its
+                            extension [default: life.rle]
+    --rule <RULE>           Rule such as B3/S23 or 23/3, overriding the rule
+                            of the pattern [default: B3/S23]
+    --seed <N>              Seed for the random soup, which is picked at random
+                            and printed on startup if not given
+    --gps <N>               Target updates per second, or `max` to run as fast
+                            as possible [default: 60]
+    --step <N>              Advance 2^N generations per update, up to 48 on
+                            hashlife boards and 10 on the others [default: 0]
+    --window <WxH>          Window size in pixels [default: 960x960]
+    --paused                Start with the simulation paused
+    --terminal              Draw the board in the terminal instead of a window
+    -h, --help              Print this help
+
+BATCH OPTIONS:
+    --batch <N>             Run N random soups on infinite boards without
+                            opening a window, and write a summary of how they
+                            ended up. Soups are 16x16 unless `--size` is given
+                            and soup `i` is seeded with `--seed` plus `i`
+    --threads <N>           Soups to run at once [default: number of CPUs]
+    --max-generations <N>   Give up on soups which haven't stabilized after
+                            this many generations [default: 100000]
+    --output <FILE>         Summary file to write [default: soups.txt]
+
+GIF OPTIONS:
+    --gif <FILE>            File recordings are written to, which Ctrl+R
+                            starts and stops in the viewer [default: life.gif]
+    --gif-frames <N>        Record N updates without opening a window, then
+                            exit
+    --gif-cell <PX>         Pixels across every cell [default: 4]
+    --gif-region <X,Y,WxH>  Cells to record [default: the whole board, or
+                            everywhere the pattern reaches on infinite boards,
+                            or what's visible in the viewer]
+    --gif-delay <MS>        Time every frame is shown for [default: 100]
+    --gif-colors <A,D>      Colors of live and dead cells as hex RGB
+                            [default: ffffff,000000]
+
+PNG OPTIONS:
+    --png <FILE>            File Ctrl+S saves the board to in the viewer
+                            [default: life.png]
+    --png-cell <PX>         Pixels across every cell, when saving and loading
+                            [default: 1]
+    --png-colors <A,D>      Colors of live and dead cells as hex RGB. Loaded
+                            pixels are alive when their brightness is closer
+                            to that of live cells [default: ffffff,000000]
+
+AGE OPTIONS:
+    --ages                  Track how many generations cells have been alive
+                            for on grid boards, and color them by age in the
+                            viewer. Implied by the options below
+    --age-colors <C,...>    Colors cells fade through as they get older, as
+                            hex RGB [default: ffffff,ffc850,c84628,5a283c]
+    --born-color <C>        Color of cells born this generation
+                            [default: 78ff8c]
+    --age-span <N>          Generations cells take to fade to the last color
+                            [default: 100]
+";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Run(Box<Options>),
+    Help,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoardKind {
+    Grid,
+    Packed,
+    Infinite,
+    HashLife,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchOptions {
+    pub soups: u64,
+    /// `None` uses one thread per CPU.
+    pub threads: Option<usize>,
+    pub max_generations: u64,
+    pub output: PathBuf,
+}
+
+impl Default for BatchOptions {
+    fn default() -> Self {
+        Self {
+            soups: 0,
+            threads: None,
+            max_generations: 100_000,
+            output: PathBuf::from("soups.txt"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Options {
+    pub board: BoardKind,
+    pub size: (usize, usize),
+    pub topology: Topology,
+    /// Only set when given explicitly, as it can't be combined with
+    /// `pattern`.
+    pub density: Option<f64>,
+    pub pattern: Option<PathBuf>,
+    pub rule: Option<Rule>,
+    pub seed: Option<u64>,
+    /// `None` runs as fast as possible.
+    pub generations_per_second: Option<f64>,
+    pub step_log2: u32,
+    pub window_size: (u32, u32),
+    pub paused: bool,
+    /// Draws the board in the terminal instead of opening a window.
+    pub terminal: bool,
+    /// Runs soups without a window instead of opening the viewer.
+    pub batch: Option<BatchOptions>,
+    /// The part of grid and packed boards the pattern is placed at.
+    pub anchor: Anchor,
+    /// The pattern file the board is exported to.
+    pub save: PathBuf,
+    pub gif: GifOptions,
+    pub png: PngOptions,
+    /// Tracks the ages of cells and colors them by age when set.
+    pub ages: Option<AgeColors>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            board: BoardKind::Grid,
+            size: (1000, 1000),
+            topology: Topology::Bounded,
+            density: None,
+            pattern: None,
+            rule: None,
+            seed: None,
+            generations_per_second: Some(60.0),
+            step_log2: 0,
+            window_size: (960, 960),
+            paused: false,
+            terminal: false,
+            batch: None,
+            anchor: Anchor::TopLeft,
+            save: PathBuf::from("life.rle"),
+            gif: GifOptions::default(),
+            png: PngOptions::default(),
+            ages: None,
+        }
+    }
+}
+
+impl Options {
+    /// The speed the simulation starts out running at.
+    pub fn speed(&self) -> Speed {
+        Speed {
+            generations_per_second: self.generations_per_second,
+            step_log2: self.step_log2,
+        }
+    }
+}
+
+/// Parses the command line arguments, without the program name.
+pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Command, String> {
+    let mut options = Options::default();
+    let mut args = args.into_iter();
+    let mut batch = BatchOptions::default();
+    // Batch options given without `--batch`, and whether `--size` was given.
+    let mut batch_only_flag = None;
+    // The last option given which batches of soups don't use.
+    let mut not_batch_flag = None;
+    let mut anchor_given = false;
+    let mut size_given = false;
+    // The last option given which tracks ages.
+    let mut ages_flag = None;
+
+    while let Some(arg) = args.next() {
+        let (flag, inline_value) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => (flag, Some(value.to_string())),
+            _ => (arg.as_str(), None),
+        };
+
+        let mut value = || {
+            inline_value
+                .clone()
+                .or_else(|| args.next())
+                .ok_or_else(|| format!("`{flag}` expects a value"))
+        };
+
+        match flag {
+            "-h" | "--help" => return Ok(Command::Help),
+            "--board" => {
+                options.board = match value()?.as_str() {
+                    "grid" => BoardKind::Grid,
+                    "packed" => BoardKind::Packed,
+                    "infinite" => BoardKind::Infinite,
+                    "hashlife" => BoardKind::HashLife,
+                    other => {
+                        return Err(invalid(
+                            flag,
+                            other,
+                            "expected `grid`, `packed`, `infinite` or `hashlife`",
+                        ))
+                    }
+                };
+                not_batch_flag = Some(flag.to_string());
+            }
+            "--size" => {
+                options.size = parse_size(flag, &value()?)?;
+                size_given = true;
+            }
+            "--topology" => {
+                options.topology = match value()?.as_str() {
+                    "bounded" => Topology::Bounded,
+                    "torus" => Topology::Torus,
+                    other => return Err(invalid(flag, other, "expected `bounded` or `torus`")),
+                };
+                not_batch_flag = Some(flag.to_string());
+            }
+            "--density" => {
+                let value = value()?;
+                let density: f64 = parse_value(flag, &value)?;
+
+                if !(0.0..=1.0).contains(&density) {
+                    return Err(invalid(flag, &value, "must be between 0 and 1"));
+                }
+
+                options.density = Some(density);
+            }
+            "--pattern" => options.pattern = Some(PathBuf::from(value()?)),
+            "--rule" => options.rule = Some(parse_value(flag, &value()?)?),
+            "--seed" => options.seed = Some(parse_value(flag, &value()?)?),
+            "--gps" => {
+                let value = value()?;
+
+                options.generations_per_second = match value.as_str() {
+                    "max" => None,
+                    _ => {
+                        let generations_per_second: f64 = parse_value(flag, &value)?;
+
+                        if !(generations_per_second > 0.0 && generations_per_second.is_finite()) {
+                            return Err(invalid(
+                                flag,
+                                &value,
+                                "must be a positive number or `max`",
+                            ));
+                        }
+
+                        Some(generations_per_second)
+                    }
+                };
+            }
+            "--step" => {
+                let value = value()?;
+                options.step_log2 = parse_value(flag, &value)?;
+
+                if options.step_log2 > MAX_STEP_LOG2 {
+                    return Err(invalid(
+                        flag,
+                        &value,
+                        format!("must be at most {MAX_STEP_LOG2}"),
+                    ));
+                }
+
+                not_batch_flag = Some(flag.to_string());
+            }
+            "--window" => {
+                let (width, height) = parse_size(flag, &value()?)?;
+                options.window_size = (
+                    u32::try_from(width).map_err(|e| invalid(flag, &width.to_string(), e))?,
+                    u32::try_from(height).map_err(|e| invalid(flag, &height.to_string(), e))?,
+                );
+            }
+            "--paused" => options.paused = true,
+            "--terminal" => {
+                options.terminal = true;
+                not_batch_flag = Some(flag.to_string());
+            }
+            "--batch" => {
+                let value = value()?;
+                batch.soups = parse_value(flag, &value)?;
+
+                if batch.soups == 0 {
+                    return Err(invalid(flag, &value, "must be at least 1"));
+                }
+            }
+            "--threads" => {
+                let value = value()?;
+                let threads = parse_value(flag, &value)?;
+
+                if threads == 0 {
+                    return Err(invalid(flag, &value, "must be at least 1"));
+                }
+
+                batch.threads = Some(threads);
+                batch_only_flag = Some(flag.to_string());
+            }
+            "--max-generations" => {
+                batch.max_generations = parse_value(flag, &value()?)?;
+                batch_only_flag = Some(flag.to_string());
+            }
+            "--output" => {
+                batch.output = PathBuf::from(value()?);
+                batch_only_flag = Some(flag.to_string());
+            }
+            "--anchor" => {
+                options.anchor = match value()?.as_str() {
+                    "top-left" => Anchor::TopLeft,
+                    "top" => Anchor::Top,
+                    "top-right" => Anchor::TopRight,
+                    "left" => Anchor::Left,
+                    "center" => Anchor::Center,
+                    "right" => Anchor::Right,
+                    "bottom-left" => Anchor::BottomLeft,
+                    "bottom" => Anchor::Bottom,
+                    "bottom-right" => Anchor::BottomRight,
+                    other => {
+                        return Err(invalid(
+                            flag,
+                            other,
+                            "expected `top-left`, `top`, `top-right`, `left`, `center`, \
+                             `right`, `bottom-left`, `bottom` or `bottom-right`",
+                        ))
+                    }
+                };
+                anchor_given = true;
+                not_batch_flag = Some(flag.to_string());
+            }
+            "--save" => {
+                let value = value()?;
+                options.save = PathBuf::from(&value);
+
+                if Format::from_path(&options.save).is_none() {
+                    return Err(invalid(
+                        flag,
+                        &value,
+                        "expected a `.rle`, `.cells` or `.lif` extension",
+                    ));
+                }
+            }
+            "--gif" => options.gif.output = PathBuf::from(value()?),
+            "--gif-frames" => {
+                let value = value()?;
+                let frames = parse_value(flag, &value)?;
+
+                if frames == 0 {
+                    return Err(invalid(flag, &value, "must be at least 1"));
+                }
+
+                options.gif.frames = Some(frames);
+            }
+            "--gif-cell" => {
+                let value = value()?;
+                options.gif.cell_size = parse_value(flag, &value)?;
+
+                if options.gif.cell_size == 0 {
+                    return Err(invalid(flag, &value, "must be at least 1"));
+                }
+            }
+            "--gif-region" => options.gif.region = Some(parse_region(flag, &value()?)?),
+            "--gif-delay" => {
+                let value = value()?;
+                let milliseconds: u32 = parse_value(flag, &value)?;
+
+                // GIFs count in hundredths of a second.
+                options.gif.frame_delay = u16::try_from(milliseconds.saturating_add(5) / 10)
+                    .ok()
+                    .filter(|&delay| delay > 0)
+                    .ok_or_else(|| invalid(flag, &value, "must be between 10 and 655350"))?;
+            }
+            "--gif-colors" => {
+                (options.gif.alive_color, options.gif.dead_color) = parse_colors(flag, &value()?)?;
+            }
+            "--png" => options.png.output = PathBuf::from(value()?),
+            "--png-cell" => {
+                let value = value()?;
+                options.png.cell_size = parse_value(flag, &value)?;
+
+                if options.png.cell_size == 0 {
+                    return Err(invalid(flag, &value, "must be at least 1"));
+                }
+            }
+            "--png-colors" => {
+                (options.png.alive_color, options.png.dead_color) = parse_colors(flag, &value()?)?;
+            }
+            "--ages" => {
+                options.ages.get_or_insert_with(AgeColors::default);
+                ages_flag = Some(flag.to_string());
+            }
+            "--age-colors" => {
+                let value = value()?;
+                let gradient = value
+                    .split(',')
+                    .map(|color| parse_color(flag, color))
+                    .collect::<Result<_, _>>()?;
+
+                options.ages.get_or_insert_with(AgeColors::default).gradient = gradient;
+                ages_flag = Some(flag.to_string());
+            }
+            "--born-color" => {
+                options.ages.get_or_insert_with(AgeColors::default).born =
+                    parse_color(flag, &value()?)?;
+                ages_flag = Some(flag.to_string());
+            }
+            "--age-span" => {
+                let value = value()?;
+                let span = parse_value(flag, &value)?;
+
+                if span == 0 {
+                    return Err(invalid(flag, &value, "must be at least 1"));
+                }
+
+                options.ages.get_or_insert_with(AgeColors::default).span = span;
+                ages_flag = Some(flag.to_string());
+            }
+            _ => return Err(format!("unknown option `{arg}`")),
+        }
+    }
+
+    if batch.soups > 0 {
+        if options.pattern.is_some() {
+            return Err(
+                "`--batch` runs random soups and can't be used with `--pattern`".to_string(),
+            );
+        }
+
+        if !size_given {
+            options.size = (16, 16);
+        }
+
+        if options.gif.frames.is_some() {
+            return Err("`--gif-frames` can't be used with `--batch`".to_string());
+        }
+
+        if let Some(flag) = not_batch_flag {
+            return Err(format!("`{flag}` can't be used with `--batch`"));
+        }
+
+        options.batch = Some(batch);
+    } else if let Some(flag) = batch_only_flag {
+        return Err(format!("`{flag}` can only be used with `--batch`"));
+    }
+
+    if options.topology == Topology::Torus
+        && matches!(options.board, BoardKind::Infinite | BoardKind::HashLife)
+    {
+        return Err("`--topology torus` needs a `grid` or `packed` board".to_string());
+    }
+
+    if anchor_given && matches!(options.board, BoardKind::Infinite | BoardKind::HashLife) {
+        return Err("`--anchor` needs a `grid` or `packed` board".to_string());
+    }
+
+    if options.board != BoardKind::HashLife && options.step_log2 > MAX_TICKED_STEP_LOG2 {
+        return Err(format!(
+            "`--step` can be at most {MAX_TICKED_STEP_LOG2} without a `hashlife` board"
+        ));
+    }
+
+    if let Some(flag) = ages_flag {
+        if options.board != BoardKind::Grid {
+            return Err(format!("`{flag}` needs a `grid` board"));
+        }
+    }
+
+    if options.pattern.is_some() && options.density.is_some() {
+        return Err("`--density` can't be used together with `--pattern`".to_string());
+    }
+
+    Ok(Command::Run(Box::new(options)))
+}
+
+fn parse_value<T>(flag: &str, value: &str) -> Result<T, String>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    value.parse().map_err(|e| invalid(flag, value, e))
+}
+
+/// Parses a size such as `640x480`.
+fn parse_size(flag: &str, value: &str) -> Result<(usize, usize), String> {
+    let expected = "expected a size such as `640x480`";
+    let (width, height) = value
+        .split_once(['x', 'X'])
+        .ok_or_else(|| invalid(flag, value, expected))?;
+
+    match (width.parse(), height.parse()) {
+        (Ok(0), _) | (_, Ok(0)) => Err(invalid(flag, value, "size can't be 0")),
+        (Ok(width), Ok(height)) => Ok((width, height)),
+        _ => Err(invalid(flag, value, expected)),
+    }
+}
+
+/// Parses a region such as `-10,20,64x48`.
+fn parse_region(flag: &str, value: &str) -> Result<Region, String> {
+    let expected = "expected a region such as `-10,20,64x48`";
+    let mut parts = value.splitn(3, ',');
+
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(x), Some(y), Some(size)) => {
+            let (width, height) = parse_size(flag, size)?;
+
+            Ok(Region {
+                x: x.trim()
+                    .parse()
+                    .map_err(|_| invalid(flag, value, expected))?,
+                y: y.trim()
+                    .parse()
+                    .map_err(|_| invalid(flag, value, expected))?,
+                width,
+                height,
+            })
+        }
+        _ => Err(invalid(flag, value, expected)),
+    }
+}
+
+/// Parses the colors of live and dead cells, such as `ffffff,000000`.
+fn parse_colors(flag: &str, value: &str) -> Result<([u8; 3], [u8; 3]), String> {
+    let (alive, dead) = value
+        .split_once(',')
+        .ok_or_else(|| invalid(flag, value, "expected two colors such as `ffffff,000000`"))?;
+
+    Ok((parse_color(flag, alive)?, parse_color(flag, dead)?))
+}
+
+/// Parses a hex RGB color such as `ff8000` or `#ff8000`.
+fn parse_color(flag: &str, value: &str) -> Result<[u8; 3], String> {
+    let hex = value.trim().trim_start_matches('#');
+    let channel = |i: usize| {
+        hex.get(i * 2..i * 2 + 2)
+            .and_then(|channel| u8::from_str_radix(channel, 16).ok())
+    };
+
+    match (hex.len(), channel(0), channel(1), channel(2)) {
+        (6, Some(red), Some(green), Some(blue)) => Ok([red, green, blue]),
+        _ => Err(invalid(
+            flag,
+            value,
+            "expected a hex color such as `ff8000`",
+        )),
+    }
+}
+
+fn invalid(flag: &str, value: &str, reason: impl Display) -> String {
+    format!("invalid value `{value}` for `{flag}`: {reason}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Command, String> {
+        super::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn test_defaults() {
+        assert_eq!(parse(&[]), Ok(Command::Run(Box::default())));
+        assert_eq!(
+            parse(&["--gps", "max"]),
+            Ok(Command::Run(Box::new(Options {
+                generations_per_second: None,
+                ..Options::default()
+            })))
+        );
+        assert_eq!(parse(&["--board", "grid", "-h"]), Ok(Command::Help));
+    }
+
+    #[test]
+    fn test_parse_options() {
+        let command = parse(&[
+            "--board=packed",
+            "--size",
+            "256x128",
+            "--topology",
+            "torus",
+            "--density",
+            "0.25",
+            "--rule",
+            "B36/S23",
+            "--seed=42",
+            "--gps",
+            "7.5",
+            "--step",
+            "3",
+            "--window",
+            "800X600",
+            "--paused",
+            "--terminal",
+            "--save",
+            "board.cells",
+            "--anchor",
+            "bottom-right",
+        ]);
+
+        assert_eq!(
+            command,
+            Ok(Command::Run(Box::new(Options {
+                board: BoardKind::Packed,
+                size: (256, 128),
+                topology: Topology::Torus,
+                density: Some(0.25),
+                pattern: None,
+                rule: Some("B36/S23".parse().unwrap()),
+                seed: Some(42),
+                generations_per_second: Some(7.5),
+                step_log2: 3,
+                window_size: (800, 600),
+                paused: true,
+                terminal: true,
+                batch: None,
+                anchor: Anchor::BottomRight,
+                save: PathBuf::from("board.cells"),
+                gif: GifOptions::default(),
+                png: PngOptions::default(),
+                ages: None,
+            })))
+        );
+    }
+
+    #[test]
+    fn test_parse_save() {
+        assert_eq!(
+            parse(&["--save", "board.png"]),
+            Err(
+                "invalid value `board.png` for `--save`: expected a `.rle`, `.cells` or `.lif` \
+                 extension"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_parse_gif() {
+        let Ok(Command::Run(options)) = parse(&[
+            "--gif",
+            "out.gif",
+            "--gif-frames=50",
+            "--gif-cell",
+            "2",
+            "--gif-region",
+            "-10,20,64x48",
+            "--gif-delay",
+            "40",
+            "--gif-colors",
+            "#ff8000,102030",
+        ]) else {
+            panic!("expected options");
+        };
+
+        assert_eq!(
+            options.gif,
+            GifOptions {
+                output: PathBuf::from("out.gif"),
+                frames: Some(50),
+                cell_size: 2,
+                region: Some(Region {
+                    x: -10,
+                    y: 20,
+                    width: 64,
+                    height: 48,
+                }),
+                frame_delay: 4,
+                alive_color: [255, 128, 0],
+                dead_color: [16, 32, 48],
+            }
+        );
+
+        assert_eq!(
+            parse(&["--gif-region", "1,2"]),
+            Err(
+                "invalid value `1,2` for `--gif-region`: expected a region such as `-10,20,64x48`"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            parse(&["--gif-colors", "fff,000"]),
+            Err(
+                "invalid value `fff` for `--gif-colors`: expected a hex color such as `ff8000`"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            parse(&["--gif-delay", "1"]),
+            Err("invalid value `1` for `--gif-delay`: must be between 10 and 655350".to_string())
+        );
+        assert_eq!(
+            parse(&["--batch", "1", "--gif-frames", "5"]),
+            Err("`--gif-frames` can't be used with `--batch`".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_png() {
+        let Ok(Command::Run(options)) = parse(&[
+            "--png=board.png",
+            "--png-cell",
+            "8",
+            "--png-colors",
+            "000000,FFFFFF",
+        ]) else {
+            panic!("expected options");
+        };
+
+        assert_eq!(
+            options.png,
+            PngOptions {
+                output: PathBuf::from("board.png"),
+                cell_size: 8,
+                alive_color: [0; 3],
+                dead_color: [255; 3],
+            }
+        );
+
+        assert_eq!(
+            parse(&["--png-colors", "ffffff"]),
+            Err(
+                "invalid value `ffffff` for `--png-colors`: expected two colors such as \
+                 `ffffff,000000`"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_parse_ages() {
+        let Ok(Command::Run(options)) = parse(&["--ages"]) else {
+            panic!("expected options");
+        };
+        assert_eq!(options.ages, Some(AgeColors::default()));
+
+        let Ok(Command::Run(options)) = parse(&[
+            "--age-colors",
+            "ffffff,#000000",
+            "--born-color=00ff00",
+            "--age-span",
+            "20",
+        ]) else {
+            panic!("expected options");
+        };
+
+        assert_eq!(
+            options.ages,
+            Some(AgeColors {
+                born: [0, 255, 0],
+                gradient: vec![[255; 3], [0; 3]],
+                span: 20,
+            })
+        );
+
+        assert_eq!(
+            parse(&["--age-colors", "ffffff,"]),
+            Err(
+                "invalid value `` for `--age-colors`: expected a hex color such as `ff8000`"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            parse(&["--age-span", "0"]),
+            Err("invalid value `0` for `--age-span`: must be at least 1".to_string())
+        );
+        assert_eq!(
+            parse(&["--board", "hashlife", "--born-color", "00ff00"]),
+            Err("`--born-color` needs a `grid` board".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_batch() {
+        let Ok(Command::Run(options)) = parse(&["--batch", "100", "--threads=4", "--seed", "7"])
+        else {
+            panic!("expected options");
+        };
+
+        assert_eq!(options.size, (16, 16));
+        assert_eq!(options.seed, Some(7));
+        assert_eq!(
+            options.batch,
+            Some(BatchOptions {
+                soups: 100,
+                threads: Some(4),
+                ..BatchOptions::default()
+            })
+        );
+
+        let Ok(Command::Run(options)) = parse(&["--size", "32x32", "--batch", "1"]) else {
+            panic!("expected options");
+        };
+        assert_eq!(options.size, (32, 32));
+
+        assert_eq!(
+            parse(&["--output", "soups.txt"]),
+            Err("`--output` can only be used with `--batch`".to_string())
+        );
+        assert_eq!(
+            parse(&["--batch", "0"]),
+            Err("invalid value `0` for `--batch`: must be at least 1".to_string())
+        );
+        assert_eq!(
+            parse(&["--batch", "5", "--pattern", "glider.rle"]),
+            Err("`--batch` runs random soups and can't be used with `--pattern`".to_string())
+        );
+        assert_eq!(
+            parse(&["--board", "grid", "--batch", "5"]),
+            Err("`--board` can't be used with `--batch`".to_string())
+        );
+        assert_eq!(
+            parse(&["--batch", "5", "--terminal"]),
+            Err("`--terminal` can't be used with `--batch`".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse(&["--frobnicate"]),
+            Err("unknown option `--frobnicate`".to_string())
+        );
+        assert_eq!(
+            parse(&["--seed"]),
+            Err("`--seed` expects a value".to_string())
+        );
+        assert_eq!(
+            parse(&["--seed", "-1"]),
+            Err("invalid value `-1` for `--seed`: invalid digit found in string".to_string())
+        );
+        assert_eq!(
+            parse(&["--board", "hexagonal"]),
+            Err("invalid value `hexagonal` for `--board`: expected `grid`, `packed`, `infinite` or `hashlife`".to_string())
+        );
+        assert_eq!(
+            parse(&["--size", "100"]),
+            Err("invalid value `100` for `--size`: expected a size such as `640x480`".to_string())
+        );
+        assert_eq!(
+            parse(&["--size", "0x100"]),
+            Err("invalid value `0x100` for `--size`: size can't be 0".to_string())
+        );
+        assert_eq!(
+            parse(&["--density", "1.5"]),
+            Err("invalid value `1.5` for `--density`: must be between 0 and 1".to_string())
+        );
+        assert_eq!(
+            parse(&["--rule", "B3S23"]),
+            Err("invalid value `B3S23` for `--rule`: rule must have exactly one `/`, e.g. `B3/S23` or `23/3`".to_string())
+        );
+        assert_eq!(
+            parse(&["--gps", "0"]),
+            Err("invalid value `0` for `--gps`: must be a positive number or `max`".to_string())
+        );
+        assert_eq!(
+            parse(&["--step", "49"]),
+            Err("invalid value `49` for `--step`: must be at most 48".to_string())
+        );
+        assert_eq!(
+            parse(&["--step", "11"]),
+            Err("`--step` can be at most 10 without a `hashlife` board".to_string())
+        );
+        assert!(parse(&["--board", "hashlife", "--step", "48"]).is_ok());
+        assert_eq!(
+            parse(&["--board", "hashlife", "--topology", "torus"]),
+            Err("`--topology torus` needs a `grid` or `packed` board".to_string())
+        );
+        assert_eq!(
+            parse(&["--board", "infinite", "--anchor", "center"]),
+            Err("`--anchor` needs a `grid` or `packed` board".to_string())
+        );
+        assert_eq!(
+            parse(&["--pattern", "glider.rle", "--density", "0.1"]),
+            Err("`--density` can't be used together with `--pattern`".to_string())
+        );
+    }
+}
@@ -0,0 +1,165 @@
+use crate::{
+    game_context::{CellEdit, GameOfLifeBoard},
+    pattern::Pattern,
+};
+
+/// Live cells copied out of a board, which can be rotated and mirrored
+/// before being stamped back onto it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Clipboard {
+    /// The copied region's size, which may be bigger than its live cells.
+    pub width: usize,
+    pub height: usize,
+    /// The live cells, relative to the top left corner of the region.
+    cells: Vec<(isize, isize)>,
+}
+
+impl Clipboard {
+    /// Creates a clipboard holding `cells` in a region of the given size.
+    pub fn new(
+        width: usize,
+        height: usize,
+        cells: impl IntoIterator<Item = (isize, isize)>,
+    ) -> Self {
+        let mut clipboard = Self {
+            width,
+            height,
+            cells: cells.into_iter().collect(),
+        };
+
+        clipboard.cells.sort_unstable_by_key(|&(x, y)| (y, x));
+        clipboard
+    }
+
+    /// Copies the live cells of `board` within the rectangle between the
+    /// tiles `from` and `to`, both inclusive.
+    pub fn copy<B: GameOfLifeBoard>(board: &B, from: (isize, isize), to: (isize, isize)) -> Self {
+        let (min_x, min_y) = (from.0.min(to.0), from.1.min(to.1));
+        let (max_x, max_y) = (from.0.max(to.0), from.1.max(to.1));
+
+        let cells = board
+            .alive_cells()
+            .filter(|&(x, y)| (min_x..=max_x).contains(&x) && (min_y..=max_y).contains(&y))
+            .map(|(x, y)| (x - min_x, y - min_y));
+
+        Self::new(
+            (max_x - min_x) as usize + 1,
+            (max_y - min_y) as usize + 1,
+            cells,
+        )
+    }
+
+    pub fn from_pattern(pattern: &Pattern) -> Self {
+        Self::new(pattern.width, pattern.height, pattern.alive_cells())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn cells(&self) -> &[(isize, isize)] {
+        &self.cells
+    }
+
+    /// Rotates the region by 90° clockwise.
+    pub fn rotate(&mut self) {
+        let height = self.height as isize;
+        *self = Self::new(
+            self.height,
+            self.width,
+            self.cells.iter().map(|&(x, y)| (height - 1 - y, x)),
+        );
+    }
+
+    /// Mirrors the region from left to right.
+    pub fn flip_horizontal(&mut self) {
+        let width = self.width as isize;
+        *self = Self::new(
+            self.width,
+            self.height,
+            self.cells.iter().map(|&(x, y)| (width - 1 - x, y)),
+        );
+    }
+
+    /// Mirrors the region from top to bottom.
+    pub fn flip_vertical(&mut self) {
+        let height = self.height as isize;
+        *self = Self::new(
+            self.width,
+            self.height,
+            self.cells.iter().map(|&(x, y)| (x, height - 1 - y)),
+        );
+    }
+
+    /// The edits bringing the live cells to life with the top left corner of
+    /// the region at `(x, y)`.
+    pub fn stamp(&self, x: isize, y: isize) -> Vec<CellEdit> {
+        self.cells
+            .iter()
+            .map(|&(dx, dy)| CellEdit {
+                x: x + dx,
+                y: y + dy,
+                alive: true,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infinite_board::InfiniteBoard;
+
+    /// An L shaped tromino in the top left of a 3x2 region.
+    fn tromino() -> Clipboard {
+        Clipboard::new(3, 2, [(0, 0), (0, 1), (1, 1)])
+    }
+
+    #[test]
+    fn test_copy() {
+        let board = InfiniteBoard::from_alive_cells([(-1, -1), (0, 0), (2, 1), (5, 5)]);
+        let clipboard = Clipboard::copy(&board, (2, 2), (-1, 0));
+
+        assert_eq!((clipboard.width, clipboard.height), (4, 3));
+        assert_eq!(clipboard.cells(), &[(1, 0), (3, 1)]);
+    }
+
+    #[test]
+    fn test_rotate() {
+        let mut clipboard = tromino();
+        clipboard.rotate();
+
+        assert_eq!((clipboard.width, clipboard.height), (2, 3));
+        assert_eq!(clipboard.cells(), &[(0, 0), (1, 0), (0, 1)]);
+
+        for _ in 0..3 {
+            clipboard.rotate();
+        }
+        assert_eq!(clipboard, tromino());
+    }
+
+    #[test]
+    fn test_flip() {
+        let mut clipboard = tromino();
+
+        clipboard.flip_horizontal();
+        assert_eq!(clipboard.cells(), &[(2, 0), (1, 1), (2, 1)]);
+
+        clipboard.flip_vertical();
+        assert_eq!(clipboard.cells(), &[(1, 0), (2, 0), (2, 1)]);
+
+        // Mirroring both ways is the same as rotating by 180°.
+        let mut rotated = tromino();
+        rotated.rotate();
+        rotated.rotate();
+        assert_eq!(clipboard, rotated);
+    }
+
+    #[test]
+    fn test_stamp() {
+        assert_eq!(
+            tromino().stamp(10, -4),
+            [(10, -4), (10, -3), (11, -3)].map(|(x, y)| CellEdit { x, y, alive: true })
+        );
+    }
+}
@@ -0,0 +1,136 @@
+//! A tiny 5x7 bitmap font, so text can be drawn without any system fonts.
+
+use sdl2::{rect::FRect, render::WindowCanvas};
+
+pub const GLYPH_WIDTH: f32 = 5.0;
+pub const GLYPH_HEIGHT: f32 = 7.0;
+
+/// The space between characters and between lines, in font pixels.
+const SPACING: f32 = 1.0;
+const LINE_SPACING: f32 = 2.0;
+
+/// Returns the rows of `c` from top to bottom, with the leftmost column in
+/// the highest of the 5 bits. Letters are always upper case, and characters
+/// without a glyph are drawn as `?`.
+#[rustfmt::skip]
+pub fn glyph(c: char) -> [u8; 7] {
+    match c.to_ascii_uppercase() {
+        'A' => [0b01110, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001],
+        'B' => [0b11110, 0b10001, 0b10001, 0b11110, 0b10001, 0b10001, 0b11110],
+        'C' => [0b01110, 0b10001, 0b10000, 0b10000, 0b10000, 0b10001, 0b01110],
+        'D' => [0b11110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b11110],
+        'E' => [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b11111],
+        'F' => [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b10000],
+        'G' => [0b01110, 0b10001, 0b10000, 0b10111, 0b10001, 0b10001, 0b01111],
+        'H' => [0b10001, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001],
+        'I' => [0b01110, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110],
+        'J' => [0b00111, 0b00010, 0b00010, 0b00010, 0b00010, 0b10010, 0b01100],
+        'K' => [0b10001, 0b10010, 0b10100, 0b11000, 0b10100, 0b10010, 0b10001],
+        'L' => [0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b11111],
+        'M' => [0b10001, 0b11011, 0b10101, 0b10101, 0b10001, 0b10001, 0b10001],
+        'N' => [0b10001, 0b10001, 0b11001, 0b10101, 0b10011, 0b10001, 0b10001],
+        'O' => [0b01110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110],
+        'P' => [0b11110, 0b10001, 0b10001, 0b11110, 0b10000, 0b10000, 0b10000],
+        'Q' => [0b01110, 0b10001, 0b10001, 0b10001, 0b10101, 0b10010, 0b01101],
+        'R' => [0b11110, 0b10001, 0b10001, 0b11110, 0b10100, 0b10010, 0b10001],
+        'S' => [0b01111, 0b10000, 0b10000, 0b01110, 0b00001, 0b00001, 0b11110],
+        'T' => [0b11111, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100],
+        'U' => [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110],
+        'V' => [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01010, 0b00100],
+        'W' => [0b10001, 0b10001, 0b10001, 0b10101, 0b10101, 0b10101, 0b01010],
+        'X' => [0b10001, 0b10001, 0b01010, 0b00100, 0b01010, 0b10001, 0b10001],
+        'Y' => [0b10001, 0b10001, 0b01010, 0b00100, 0b00100, 0b00100, 0b00100],
+        'Z' => [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b11111],
+        '0' => [0b01110, 0b10001, 0b10011, 0b10101, 0b11001, 0b10001, 0b01110],
+        '1' => [0b00100, 0b01100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110],
+        '2' => [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b01000, 0b11111],
+        '3' => [0b11111, 0b00010, 0b00100, 0b00010, 0b00001, 0b10001, 0b01110],
+        '4' => [0b00010, 0b00110, 0b01010, 0b10010, 0b11111, 0b00010, 0b00010],
+        '5' => [0b11111, 0b10000, 0b11110, 0b00001, 0b00001, 0b10001, 0b01110],
+        '6' => [0b00110, 0b01000, 0b10000, 0b11110, 0b10001, 0b10001, 0b01110],
+        '7' => [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b01000, 0b01000],
+        '8' => [0b01110, 0b10001, 0b10001, 0b01110, 0b10001, 0b10001, 0b01110],
+        '9' => [0b01110, 0b10001, 0b10001, 0b01111, 0b00001, 0b00010, 0b01100],
+        ' ' => [0; 7],
+        '.' => [0, 0, 0, 0, 0, 0b01100, 0b01100],
+        ',' => [0, 0, 0, 0, 0b01100, 0b00100, 0b01000],
+        ':' => [0, 0b01100, 0b01100, 0, 0b01100, 0b01100, 0],
+        '-' => [0, 0, 0, 0b11111, 0, 0, 0],
+        '+' => [0, 0b00100, 0b00100, 0b11111, 0b00100, 0b00100, 0],
+        '=' => [0, 0, 0b11111, 0, 0b11111, 0, 0],
+        '/' => [0, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0],
+        '(' => [0b00010, 0b00100, 0b01000, 0b01000, 0b01000, 0b00100, 0b00010],
+        ')' => [0b01000, 0b00100, 0b00010, 0b00010, 0b00010, 0b00100, 0b01000],
+        '%' => [0b11000, 0b11001, 0b00010, 0b00100, 0b01000, 0b10011, 0b00011],
+        '_' => [0, 0, 0, 0, 0, 0, 0b11111],
+        _ => [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0, 0b00100],
+    }
+}
+
+/// The width of the longest line of `text` in pixels, drawn at `scale`.
+pub fn text_width(text: &str, scale: f32) -> f32 {
+    let characters = text.lines().map(|line| line.chars().count()).max();
+
+    match characters {
+        Some(0) | None => 0.0,
+        Some(characters) => (characters as f32 * (GLYPH_WIDTH + SPACING) - SPACING) * scale,
+    }
+}
+
+/// The height of `text` in pixels, drawn at `scale`.
+pub fn text_height(text: &str, scale: f32) -> f32 {
+    let lines = text.lines().count() as f32;
+    (lines * (GLYPH_HEIGHT + LINE_SPACING) - LINE_SPACING).max(0.0) * scale
+}
+
+/// Draws `text` with its top left corner at the pixel `(x, y)`, in the
+/// current draw color, with every font pixel `scale` pixels wide.
+pub fn draw_text(canvas: &mut WindowCanvas, text: &str, (x, y): (f32, f32), scale: f32) {
+    let mut rects = Vec::new();
+
+    for (row, line) in text.lines().enumerate() {
+        let top = y + row as f32 * (GLYPH_HEIGHT + LINE_SPACING) * scale;
+
+        for (column, c) in line.chars().enumerate() {
+            let left = x + column as f32 * (GLYPH_WIDTH + SPACING) * scale;
+
+            for (dy, bits) in glyph(c).into_iter().enumerate() {
+                for dx in 0..GLYPH_WIDTH as usize {
+                    if bits & (0b10000 >> dx) != 0 {
+                        rects.push(FRect::new(
+                            left + dx as f32 * scale,
+                            top + dy as f32 * scale,
+                            scale,
+                            scale,
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    let _ = canvas.fill_frects(&rects);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glyph() {
+        assert_eq!(glyph('a'), glyph('A'));
+        assert_eq!(glyph('~'), glyph('?'));
+        assert_ne!(glyph('O'), glyph('0'));
+        assert!(('A'..='Z')
+            .chain('0'..='9')
+            .all(|c| glyph(c).iter().all(|&row| row < 1 << GLYPH_WIDTH as u8)));
+    }
+
+    #[test]
+    fn test_text_size() {
+        assert_eq!(text_width("", 2.0), 0.0);
+        assert_eq!(text_width("AB", 1.0), 11.0);
+        assert_eq!(text_width("A\nBCD", 2.0), 34.0);
+        assert_eq!(text_height("A\nB", 1.0), 16.0);
+    }
+}
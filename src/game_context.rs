@@ -1,51 +1,285 @@
-use std::{collections::HashSet, time::Duration};
+use std::{collections::HashSet, mem, path::PathBuf, sync::mpsc::Sender, time::Duration};
 
 use colored::Colorize;
 use sdl2::{
     event::Event,
-    keyboard::Keycode,
+    keyboard::{Keycode, Mod},
+    mouse::MouseButton,
     pixels::Color,
     rect::{FPoint, FRect},
-    render::WindowCanvas,
+    render::{BlendMode, WindowCanvas},
 };
 
+use crate::{
+    ages::AgeColors,
+    clipboard::Clipboard,
+    font,
+    pattern::{
+        self,
+        library::{self, Entry},
+        png::{self, PngOptions},
+        Pattern,
+    },
+    recording::{GifOptions, Recorder, Region},
+    rule::Rule,
+    simulation::{Control, Snapshot, Speed},
+};
+
+/// The number of generations `N` advances the board by, unless a number has
+/// been typed.
+pub const ADVANCE_GENERATIONS: u64 = 100;
+
+/// Boards which tick cell by cell are advanced by at most
+/// `2^MAX_TICKED_STEP_LOG2` generations at once, so the simulation doesn't
+/// stop responding for long.
+pub const MAX_TICKED_STEP_LOG2: u32 = 10;
+
+/// The number of generations Page Up and Page Down jump by.
+const JUMP_GENERATIONS: u64 = 1000;
+
+/// The size of, and the gap between, the pattern previews in the library
+/// picker, in pixels.
+const THUMBNAIL_SIZE: f32 = 112.0;
+const THUMBNAIL_GAP: f32 = 8.0;
+
+/// The size of the pixels of the heads-up display's text.
+const HUD_TEXT_SCALE: f32 = 2.0;
+
+/// The height of the scrub bar along the bottom of the window, in pixels.
+const SCRUB_BAR_HEIGHT: f32 = 16.0;
+
+/// A change to a single cell made from the viewer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CellEdit {
+    pub x: isize,
+    pub y: isize,
+    pub alive: bool,
+}
+
 pub trait GameOfLifeBoard: Clone {
-    fn alive_cells(&self) -> &[(usize, usize)];
-    fn tick(&self) -> Self;
+    fn alive_cells(&self) -> impl Iterator<Item = (isize, isize)> + '_;
+    fn tick(&self, rule: &Rule) -> Self;
     fn size(&self) -> Option<(usize, usize)>;
+    /// Returns `false` for cells outside of the board.
+    fn is_alive(&self, x: isize, y: isize) -> bool;
+    /// Applies `edits` in order, ignoring cells outside of the board.
+    fn set_cells(&mut self, edits: &[CellEdit]);
+
+    /// How many generations the live cell at `x`, `y` has been alive for,
+    /// with 0 for cells born this generation, on boards tracking ages.
+    fn age(&self, _x: isize, _y: isize) -> Option<u32> {
+        None
+    }
+
+    fn population(&self) -> usize {
+        self.alive_cells().count()
+    }
+
+    /// Whether the board is advanced without looking at every live cell, so
+    /// anything else which does would slow it down.
+    fn steps_without_visiting_cells(&self) -> bool {
+        false
+    }
+
+    /// A copy of the board which can be read from another thread without
+    /// waiting for this one to finish stepping.
+    fn detached(&self) -> Self {
+        self.clone()
+    }
+
+    /// The largest update the board can be advanced by in one go, as the
+    /// log2 of the generations.
+    fn max_step_log2(&self) -> u32 {
+        MAX_TICKED_STEP_LOG2
+    }
+
+    /// Returns the top left and bottom right corners (inclusive) of the
+    /// smallest rectangle containing every live cell.
+    fn bounding_box(&self) -> Option<((isize, isize), (isize, isize))> {
+        let mut cells = self.alive_cells();
+        let first = cells.next()?;
+
+        Some(cells.fold(
+            (first, first),
+            |((min_x, min_y), (max_x, max_y)), (x, y)| {
+                ((min_x.min(x), min_y.min(y)), (max_x.max(x), max_y.max(y)))
+            },
+        ))
+    }
+
+    /// Advances the board by `2^step_log2` generations.
+    fn step_pow2(&self, rule: &Rule, step_log2: u32) -> Self {
+        let mut board = self.tick(rule);
+
+        for _ in 1..1u64 << step_log2 {
+            board = board.tick(rule);
+        }
+
+        board
+    }
+
+    /// Advances the board by `generations` generations.
+    fn step(&self, rule: &Rule, generations: u64) -> Self {
+        let mut board = self.clone();
+
+        for _ in 0..generations {
+            board = board.tick(rule);
+        }
+
+        board
+    }
 }
 
 #[derive(Clone, Copy)]
 pub struct EmptyBoard;
 
 impl GameOfLifeBoard for EmptyBoard {
-    fn alive_cells(&self) -> &[(usize, usize)] {
-        &[]
+    fn alive_cells(&self) -> impl Iterator<Item = (isize, isize)> + '_ {
+        std::iter::empty()
     }
 
-    fn tick(&self) -> Self {
+    fn tick(&self, _rule: &Rule) -> Self {
         Self
     }
 
     fn size(&self) -> Option<(usize, usize)> {
         None
     }
+
+    fn is_alive(&self, _x: isize, _y: isize) -> bool {
+        false
+    }
+
+    fn set_cells(&mut self, _edits: &[CellEdit]) {}
 }
 
 pub struct RenderContext {
     camera: Camera,
     key_presses: HashSet<Keycode>,
+    controls: Sender<Control>,
+    speed: Speed,
+    paused: bool,
+    /// Whether cells are being painted alive or erased while a mouse button
+    /// is held down.
+    brush: Option<bool>,
+    /// The tile the mouse was last over while painting, so fast strokes can
+    /// be joined up with a line.
+    last_painted: Option<(isize, isize)>,
+    edits: Vec<CellEdit>,
+    /// Set once the mouse button painting `edits` is released.
+    stroke_finished: bool,
+    /// The generation the scrub bar is being dragged to.
+    scrubbing: Option<u64>,
+    /// The digits typed so far of a generation to jump to.
+    typed_generation: String,
+    /// The modifier keys last known to be held down.
+    keymod: Mod,
+    /// The tile under the mouse.
+    mouse_tile: Option<(isize, isize)>,
+    /// The corners of the selected rectangle of tiles, both inclusive.
+    selection: Option<((isize, isize), (isize, isize))>,
+    /// Set while the selection is being dragged out.
+    selecting: bool,
+    clipboard: Clipboard,
+    /// Whether the clipboard follows the mouse, ready to be stamped.
+    pasting: bool,
+    /// The patterns in the built in library.
+    library: Vec<(&'static Entry, Clipboard)>,
+    /// Whether the library picker is covering the board.
+    picker_open: bool,
+    /// The mouse position in pixels.
+    mouse_pixel: (f32, f32),
+    hud_visible: bool,
+    /// The smoothed frames drawn per second.
+    fps: f64,
+    /// The seed of the random soup being shown.
+    seed: Option<u64>,
+    gif: GifOptions,
+    /// Set while the shown generations are being recorded to a GIF.
+    recording: Option<Recorder>,
+    png: PngOptions,
+    /// The pattern file the board is exported to, and the rule written to it.
+    save: Option<(PathBuf, Rule)>,
+    /// How cells are colored by age, on boards tracking ages.
+    ages: Option<AgeColors>,
 }
 
 impl RenderContext {
-    pub fn new() -> Self {
+    /// Creates a viewer controlling the simulation thread through
+    /// `controls`, which starts out running at `speed` unless `paused`.
+    pub fn new(controls: Sender<Control>, speed: Speed, paused: bool) -> Self {
         Self {
             camera: Camera::default(),
             key_presses: HashSet::default(),
+            controls,
+            speed,
+            paused,
+            brush: None,
+            last_painted: None,
+            edits: Vec::new(),
+            stroke_finished: false,
+            scrubbing: None,
+            typed_generation: String::new(),
+            keymod: Mod::NOMOD,
+            mouse_tile: None,
+            selection: None,
+            selecting: false,
+            clipboard: Clipboard::default(),
+            pasting: false,
+            library: library::ENTRIES
+                .iter()
+                .map(|entry| (entry, Clipboard::from_pattern(&entry.pattern())))
+                .collect(),
+            picker_open: false,
+            mouse_pixel: (0.0, 0.0),
+            hud_visible: true,
+            fps: 0.0,
+            seed: None,
+            gif: GifOptions::default(),
+            recording: None,
+            png: PngOptions::default(),
+            save: None,
+            ages: None,
         }
     }
 
+    /// Shows `seed` in the heads-up display, so the soup can be recreated.
+    pub fn with_seed(mut self, seed: Option<u64>) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Records to GIFs as described by `gif` when `Ctrl+R` is pressed.
+    pub fn with_gif(mut self, gif: GifOptions) -> Self {
+        self.gif = gif;
+        self
+    }
+
+    /// Saves the board as described by `png` when `Ctrl+S` is pressed.
+    pub fn with_png(mut self, png: PngOptions) -> Self {
+        self.png = png;
+        self
+    }
+
+    /// Exports the board to the pattern file `path`, along with `rule`, when
+    /// `Ctrl+E` is pressed.
+    pub fn with_save(mut self, path: PathBuf, rule: Rule) -> Self {
+        self.save = Some((path, rule));
+        self
+    }
+
+    /// Colors live cells by their age as described by `ages`, instead of
+    /// drawing them all white.
+    pub fn with_ages(mut self, ages: Option<AgeColors>) -> Self {
+        self.ages = ages;
+        self
+    }
+
     pub fn tick(mut self, delta: Duration) -> Self {
+        if !delta.is_zero() {
+            self.fps = self.fps * 0.9 + 0.1 / delta.as_secs_f64();
+        }
+
         self.move_camera(delta);
         self.scale_camera(delta);
         self
@@ -98,16 +332,337 @@ impl RenderContext {
             .any(|keycode| self.is_key_pressed(keycode))
     }
 
-    pub fn handle_event(&mut self, event: Event) {
+    /// Applies the cells painted or erased with the mouse since this was
+    /// last called to `board`, and sends them to the simulation thread.
+    pub fn flush_edits<B: GameOfLifeBoard>(&mut self, board: &mut B) {
+        if !self.edits.is_empty() {
+            let edits = mem::take(&mut self.edits);
+            board.set_cells(&edits);
+            self.send(Control::Edit(edits));
+        }
+
+        // Each stroke is undone as a whole.
+        if mem::take(&mut self.stroke_finished) {
+            self.send(Control::EndEdit);
+        }
+    }
+
+    fn send(&self, control: Control) {
+        // The simulation thread only stops when the program exits.
+        let _ = self.controls.send(control);
+    }
+
+    /// Handles the keys controlling the simulation.
+    fn handle_control_key(&mut self, keycode: Keycode, repeat: bool, max_step_log2: u32) {
+        match keycode {
+            Keycode::Space if !repeat => {
+                self.paused = !self.paused;
+                self.send(Control::SetPaused(self.paused));
+            }
+            Keycode::Period => {
+                self.paused = true;
+                self.send(Control::Step(1));
+            }
+            Keycode::N => {
+                let generations = mem::take(&mut self.typed_generation)
+                    .parse()
+                    .ok()
+                    .filter(|&generations| generations > 0)
+                    .unwrap_or(ADVANCE_GENERATIONS);
+
+                self.paused = true;
+                self.send(Control::Step(generations));
+            }
+            Keycode::Equals | Keycode::Plus | Keycode::KpPlus => {
+                self.speed = self.speed.faster(max_step_log2);
+                self.send(Control::SetSpeed(self.speed));
+            }
+            Keycode::Minus | Keycode::KpMinus => {
+                self.speed = self.speed.slower();
+                self.send(Control::SetSpeed(self.speed));
+            }
+            Keycode::Comma => self.undo(),
+            Keycode::H => self.hud_visible = !self.hud_visible,
+            Keycode::C => self.send(Control::Census),
+            _ => (),
+        }
+    }
+
+    /// Handles the keys jumping to another generation, returning `true` if
+    /// `keycode` was one of them.
+    fn handle_timeline_key<B>(&mut self, keycode: Keycode, snapshot: &Snapshot<B>) -> bool {
+        let digit = match keycode {
+            Keycode::Num0 | Keycode::Kp0 => Some(0),
+            Keycode::Num1 | Keycode::Kp1 => Some(1),
+            Keycode::Num2 | Keycode::Kp2 => Some(2),
+            Keycode::Num3 | Keycode::Kp3 => Some(3),
+            Keycode::Num4 | Keycode::Kp4 => Some(4),
+            Keycode::Num5 | Keycode::Kp5 => Some(5),
+            Keycode::Num6 | Keycode::Kp6 => Some(6),
+            Keycode::Num7 | Keycode::Kp7 => Some(7),
+            Keycode::Num8 | Keycode::Kp8 => Some(8),
+            Keycode::Num9 | Keycode::Kp9 => Some(9),
+            _ => None,
+        };
+
+        if let Some(digit) = digit {
+            // Anything longer wouldn't fit in a `u64`.
+            if self.typed_generation.len() < 19 {
+                self.typed_generation.push(char::from(b'0' + digit));
+            }
+            return true;
+        }
+
+        match keycode {
+            Keycode::G | Keycode::Return | Keycode::KpEnter => {
+                if let Ok(generation) = mem::take(&mut self.typed_generation).parse() {
+                    self.seek(generation);
+                }
+            }
+            Keycode::Backspace => {
+                self.typed_generation.pop();
+            }
+            Keycode::Escape => self.typed_generation.clear(),
+            Keycode::Home => self.seek(snapshot.earliest_generation),
+            Keycode::End => self.seek(snapshot.latest_generation),
+            Keycode::PageUp => self.seek(snapshot.generation.saturating_sub(JUMP_GENERATIONS)),
+            Keycode::PageDown => self.seek(snapshot.generation.saturating_add(JUMP_GENERATIONS)),
+            _ => return false,
+        }
+
+        true
+    }
+
+    /// Handles the keys transforming the clipboard while pasting and
+    /// cancelling the selection, returning `true` if `keycode` was used.
+    fn handle_clipboard_key(&mut self, keycode: Keycode) -> bool {
+        match keycode {
+            Keycode::R if self.pasting => self.clipboard.rotate(),
+            Keycode::F if self.pasting => self.clipboard.flip_horizontal(),
+            Keycode::V if self.pasting => self.clipboard.flip_vertical(),
+            Keycode::L => self.picker_open = !self.picker_open,
+            Keycode::Escape if self.picker_open || self.pasting || self.selection.is_some() => {
+                self.picker_open = false;
+                self.pasting = false;
+                self.selection = None;
+                self.selecting = false;
+            }
+            _ => return false,
+        }
+
+        true
+    }
+
+    /// The library entry under the mouse in the picker.
+    fn hovered_entry(&self, canvas: &WindowCanvas) -> Option<usize> {
+        let (x, y) = self.mouse_pixel;
+
+        (0..self.library.len()).find(|&index| {
+            let slot = picker_slot(canvas, index);
+            (slot.x()..slot.x() + slot.width()).contains(&x)
+                && (slot.y()..slot.y() + slot.height()).contains(&y)
+        })
+    }
+
+    /// Copies the live cells in the selection to the clipboard, erasing them
+    /// from `board` if `cut`.
+    fn copy<B: GameOfLifeBoard>(&mut self, board: &B, cut: bool) {
+        let Some((from, to)) = self.selection else {
+            return;
+        };
+
+        self.clipboard = Clipboard::copy(board, from, to);
+
+        if cut {
+            let (x, y) = (from.0.min(to.0), from.1.min(to.1));
+            self.edits
+                .extend(self.clipboard.stamp(x, y).into_iter().map(|edit| CellEdit {
+                    alive: false,
+                    ..edit
+                }));
+            self.stroke_finished = true;
+        }
+    }
+
+    /// The tile the top left corner of the clipboard is stamped at, so that
+    /// it is centered on the mouse.
+    fn paste_origin(&self) -> Option<(isize, isize)> {
+        let (x, y) = self.mouse_tile?;
+
+        Some((
+            x - self.clipboard.width as isize / 2,
+            y - self.clipboard.height as isize / 2,
+        ))
+    }
+
+    /// Saves the shown board as a PNG image.
+    fn save_png<B: GameOfLifeBoard>(&self, snapshot: &Snapshot<B>) {
+        let pattern = Pattern::from_board(&snapshot.board);
+
+        match png::save(&self.png.output, &pattern, &self.png) {
+            Ok(()) => println!(
+                "Saved generation {} to `{}`",
+                snapshot.generation,
+                self.png.output.display()
+            ),
+            Err(e) => eprintln!(
+                "{}",
+                format!("WARN: could not save the board: {e}").yellow()
+            ),
+        }
+    }
+
+    /// Exports the shown board to the pattern file it is saved to.
+    fn save_pattern<B: GameOfLifeBoard>(&self, snapshot: &Snapshot<B>) {
+        let Some((path, rule)) = &self.save else {
+            return;
+        };
+        let pattern = Pattern {
+            rule: Some(*rule),
+            ..Pattern::from_board(&snapshot.board)
+        };
+
+        match pattern::save(path, &pattern) {
+            Ok(()) => println!(
+                "Saved generation {} to `{}`",
+                snapshot.generation,
+                path.display()
+            ),
+            Err(e) => eprintln!(
+                "{}",
+                format!("WARN: could not save the board: {e}").yellow()
+            ),
+        }
+    }
+
+    /// Starts recording `gif.region`, or the tiles visible in the window.
+    fn start_recording<B: GameOfLifeBoard>(&mut self, board: &B, canvas: &WindowCanvas) {
+        let region = self.gif.region.unwrap_or_else(|| {
+            let (width, height) = canvas.window().size();
+            let (left, top) = self.window_to_tile(board, canvas, 0, 0);
+            let (right, bottom) =
+                self.window_to_tile(board, canvas, width as i32 - 1, height as i32 - 1);
+
+            Region {
+                x: left,
+                y: top,
+                width: (right - left + 1).max(1) as usize,
+                height: (bottom - top + 1).max(1) as usize,
+            }
+        });
+
+        match Recorder::create(&self.gif, region) {
+            Ok(recorder) => {
+                println!("Recording to `{}`", recorder.output().display());
+                self.recording = Some(recorder);
+            }
+            Err(e) => eprintln!(
+                "{}",
+                format!("WARN: could not start recording: {e}").yellow()
+            ),
+        }
+    }
+
+    /// Finishes the GIF being recorded, if any.
+    pub fn stop_recording(&mut self) {
+        let Some(recorder) = self.recording.take() else {
+            return;
+        };
+        let output = recorder.output().to_path_buf();
+
+        match recorder.finish() {
+            Ok(frames) => println!("Recorded {frames} frames to `{}`", output.display()),
+            Err(e) => eprintln!(
+                "{}",
+                format!("WARN: could not finish recording: {e}").yellow()
+            ),
+        }
+    }
+
+    /// Adds the shown generation to the GIF being recorded, if it hasn't
+    /// been added yet.
+    pub fn record<B: GameOfLifeBoard>(&mut self, snapshot: &Snapshot<B>) {
+        let Some(recorder) = &mut self.recording else {
+            return;
+        };
+
+        if let Err(e) = recorder.record(snapshot.generation, &snapshot.board) {
+            eprintln!("{}", format!("WARN: stopped recording: {e}").yellow());
+            self.recording = None;
+        }
+    }
+
+    fn seek(&mut self, generation: u64) {
+        self.paused = true;
+        self.send(Control::Seek(generation));
+    }
+
+    fn undo(&mut self) {
+        self.paused = true;
+        self.send(Control::Undo);
+    }
+
+    fn redo(&mut self) {
+        self.paused = true;
+        self.send(Control::Redo);
+    }
+
+    pub fn handle_event<B: GameOfLifeBoard>(
+        &mut self,
+        event: Event,
+        snapshot: &Snapshot<B>,
+        canvas: &WindowCanvas,
+    ) {
+        let board = &snapshot.board;
+
+        if let Event::KeyDown { keymod, .. } | Event::KeyUp { keymod, .. } = event {
+            self.keymod = keymod;
+        }
+        if let Event::MouseMotion { x, y, .. } | Event::MouseButtonDown { x, y, .. } = event {
+            self.mouse_pixel = window_to_pixels(canvas, x, y);
+        }
+
         match event {
             Event::KeyDown {
-                keycode, keymod, ..
+                keycode,
+                keymod,
+                repeat,
+                ..
             } if keymod.is_empty() => match keycode {
                 Some(keycode) => {
+                    if !self.handle_clipboard_key(keycode)
+                        && !self.handle_timeline_key(keycode, snapshot)
+                    {
+                        self.handle_control_key(keycode, repeat, snapshot.board.max_step_log2());
+                    }
                     self.key_presses.insert(keycode);
                 }
                 None => (),
             },
+            Event::KeyDown {
+                keycode: Some(keycode),
+                keymod,
+                ..
+            } if keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD) => {
+                let shift = keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD);
+
+                match keycode {
+                    Keycode::Z if shift => self.redo(),
+                    Keycode::Z => self.undo(),
+                    Keycode::Y => self.redo(),
+                    Keycode::C => self.copy(board, false),
+                    Keycode::X => self.copy(board, true),
+                    Keycode::V if !self.clipboard.is_empty() => {
+                        self.pasting = true;
+                        self.selection = None;
+                    }
+                    Keycode::R if self.recording.is_some() => self.stop_recording(),
+                    Keycode::R => self.start_recording(board, canvas),
+                    Keycode::S => self.save_png(snapshot),
+                    Keycode::E => self.save_pattern(snapshot),
+                    _ => (),
+                }
+            }
             Event::KeyUp {
                 keycode, keymod, ..
             } if keymod.is_empty() => match keycode {
@@ -116,10 +671,165 @@ impl RenderContext {
                 }
                 None => (),
             },
+            Event::MouseButtonDown {
+                mouse_btn: MouseButton::Left,
+                x,
+                y,
+                ..
+            } if self.is_over_scrub_bar(canvas, y) => {
+                self.scrubbing = Some(snapshot.generation);
+                self.scrub(snapshot, canvas, x);
+            }
+            Event::MouseMotion { x, .. } if self.scrubbing.is_some() => {
+                self.scrub(snapshot, canvas, x)
+            }
+            Event::MouseButtonUp {
+                mouse_btn: MouseButton::Left,
+                ..
+            } if self.scrubbing.is_some() => self.scrubbing = None,
+            Event::MouseButtonDown { mouse_btn, .. } if self.picker_open => {
+                let hovered = self.hovered_entry(canvas);
+
+                if let (MouseButton::Left, Some(index)) = (mouse_btn, hovered) {
+                    self.clipboard = self.library[index].1.clone();
+                    self.pasting = true;
+                    self.selection = None;
+                }
+                self.picker_open = false;
+            }
+            Event::MouseButtonDown {
+                mouse_btn, x, y, ..
+            } if self.pasting => {
+                self.mouse_tile = Some(self.window_to_tile(board, canvas, x, y));
+
+                match mouse_btn {
+                    MouseButton::Left => {
+                        if let Some((x, y)) = self.paste_origin() {
+                            self.edits.extend(self.clipboard.stamp(x, y));
+                            self.stroke_finished = true;
+                        }
+                    }
+                    MouseButton::Right => self.pasting = false,
+                    _ => (),
+                }
+            }
+            Event::MouseButtonDown {
+                mouse_btn: MouseButton::Left,
+                x,
+                y,
+                ..
+            } if self.keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD) => {
+                let tile = self.window_to_tile(board, canvas, x, y);
+                self.selection = Some((tile, tile));
+                self.selecting = true;
+            }
+            Event::MouseButtonUp {
+                mouse_btn: MouseButton::Left,
+                ..
+            } if self.selecting => self.selecting = false,
+            Event::MouseButtonDown {
+                mouse_btn, x, y, ..
+            } => {
+                self.brush = match mouse_btn {
+                    MouseButton::Left => Some(true),
+                    MouseButton::Right => Some(false),
+                    _ => return,
+                };
+                self.last_painted = None;
+                self.paint(board, canvas, x, y);
+            }
+            Event::MouseMotion { x, y, .. } => {
+                let tile = self.window_to_tile(board, canvas, x, y);
+                self.mouse_tile = Some(tile);
+
+                if let (true, Some((_, end))) = (self.selecting, &mut self.selection) {
+                    *end = tile;
+                }
+                self.paint(board, canvas, x, y);
+            }
+            Event::MouseButtonUp { mouse_btn, .. } => {
+                if matches!(
+                    (mouse_btn, self.brush),
+                    (MouseButton::Left, Some(true)) | (MouseButton::Right, Some(false))
+                ) {
+                    self.brush = None;
+                    self.stroke_finished = true;
+                }
+            }
             _ => (),
         }
     }
 
+    /// Whether the window position `y` is over the scrub bar.
+    fn is_over_scrub_bar(&self, canvas: &WindowCanvas, y: i32) -> bool {
+        let (_, height) = canvas.output_size().unwrap_or_default();
+        let (_, y) = window_to_pixels(canvas, 0, y);
+
+        y >= height as f32 - SCRUB_BAR_HEIGHT
+    }
+
+    /// Seeks to the generation under the window position `x` on the scrub
+    /// bar.
+    fn scrub<B>(&mut self, snapshot: &Snapshot<B>, canvas: &WindowCanvas, x: i32) {
+        let (width, _) = canvas.output_size().unwrap_or_default();
+        let (x, _) = window_to_pixels(canvas, x, 0);
+
+        let (earliest, latest) = scrub_range(snapshot);
+        let fraction = (x / width.max(1) as f32).clamp(0.0, 1.0) as f64;
+        let generation = earliest + ((latest - earliest) as f64 * fraction).round() as u64;
+
+        if self.scrubbing != Some(generation) {
+            self.scrubbing = Some(generation);
+            self.seek(generation);
+        }
+    }
+
+    /// Paints the tile under the window position `(x, y)` with the current
+    /// brush, along with every tile between it and the last painted one.
+    fn paint<B: GameOfLifeBoard>(&mut self, board: &B, canvas: &WindowCanvas, x: i32, y: i32) {
+        let Some(alive) = self.brush else {
+            return;
+        };
+
+        let tile = self.window_to_tile(board, canvas, x, y);
+        let from = self.last_painted.unwrap_or(tile);
+
+        self.edits
+            .extend(line(from, tile).map(|(x, y)| CellEdit { x, y, alive }));
+        self.last_painted = Some(tile);
+    }
+
+    /// The inverse of [`RenderContext::world_to_screen`], taking a position
+    /// in window coordinates such as those of mouse events.
+    pub fn window_to_world<B: GameOfLifeBoard>(
+        &self,
+        board: &B,
+        canvas: &WindowCanvas,
+        x: i32,
+        y: i32,
+    ) -> (f32, f32) {
+        let (x, y) = window_to_pixels(canvas, x, y);
+
+        // `world_to_screen` only ever translates, the scale is applied by
+        // the canvas when drawing.
+        let scale = 2.0f32.powf(self.camera.scale);
+        let (origin_x, origin_y) = self.world_to_screen(board, canvas, 0.0, 0.0);
+
+        (x / scale - origin_x, y / scale - origin_y)
+    }
+
+    /// The tile under a position in window coordinates.
+    fn window_to_tile<B: GameOfLifeBoard>(
+        &self,
+        board: &B,
+        canvas: &WindowCanvas,
+        x: i32,
+        y: i32,
+    ) -> (isize, isize) {
+        let (x, y) = self.window_to_world(board, canvas, x, y);
+        (x.floor() as isize, y.floor() as isize)
+    }
+
     pub fn world_to_screen<B: GameOfLifeBoard>(
         &self,
         board: &B,
@@ -159,8 +869,8 @@ impl RenderContext {
         &self,
         board: &B,
         canvas: &WindowCanvas,
-        x: usize,
-        y: usize,
+        x: isize,
+        y: isize,
     ) -> FRect {
         let (x, y) = self.world_to_screen(board, canvas, x as f32, y as f32);
         FRect::new(x, y, 1.0, 1.0)
@@ -170,8 +880,8 @@ impl RenderContext {
         &self,
         board: &B,
         canvas: &WindowCanvas,
-        x: usize,
-        y: usize,
+        x: isize,
+        y: isize,
     ) -> FPoint {
         let (x, y) = self.world_to_screen(board, canvas, x as f32, y as f32);
         FPoint::new(x, y)
@@ -183,7 +893,8 @@ impl RenderContext {
         };
 
         let top_left = self.tile_to_screen_point(board, canvas, 0, 0);
-        let bottom_right = self.tile_to_screen_point(board, canvas, board_width, board_height);
+        let bottom_right =
+            self.tile_to_screen_point(board, canvas, board_width as isize, board_height as isize);
         let top_right = FPoint::new(bottom_right.x, top_left.y);
         let bottom_left = FPoint::new(top_left.x, bottom_right.y);
 
@@ -192,15 +903,220 @@ impl RenderContext {
             .draw_flines([top_left, top_right, bottom_right, bottom_left, top_left].as_slice());
     }
 
-    pub fn draw<B: GameOfLifeBoard>(&self, board: &B, canvas: &mut WindowCanvas) {
+    /// Outlines the selected tiles.
+    pub fn draw_selection<B: GameOfLifeBoard>(&self, board: &B, canvas: &mut WindowCanvas) {
+        let Some((from, to)) = self.selection else {
+            return;
+        };
+
+        let top_left = self.tile_to_screen_point(board, canvas, from.0.min(to.0), from.1.min(to.1));
+        let bottom_right =
+            self.tile_to_screen_point(board, canvas, from.0.max(to.0) + 1, from.1.max(to.1) + 1);
+
+        canvas.set_draw_color(Color::RGB(90, 160, 255));
+        let _ = canvas.draw_frect(FRect::new(
+            top_left.x,
+            top_left.y,
+            bottom_right.x - top_left.x,
+            bottom_right.y - top_left.y,
+        ));
+    }
+
+    /// Draws the clipboard where it would be stamped.
+    pub fn draw_paste<B: GameOfLifeBoard>(&self, board: &B, canvas: &mut WindowCanvas) {
+        let Some((origin_x, origin_y)) = self.paste_origin().filter(|_| self.pasting) else {
+            return;
+        };
+
+        canvas.set_draw_color(Color::RGB(90, 160, 255));
+        for &(x, y) in self.clipboard.cells() {
+            let _ = canvas.fill_frect(self.tile_to_screen_rect(
+                board,
+                canvas,
+                origin_x + x,
+                origin_y + y,
+            ));
+        }
+    }
+
+    /// Covers the board with previews of every pattern in the library.
+    pub fn draw_picker(&self, canvas: &mut WindowCanvas) {
+        if !self.picker_open {
+            return;
+        }
+
+        let _ = canvas.set_scale(1.0, 1.0);
+        canvas.set_draw_color(self.background_color());
+        let _ = canvas.fill_rect(None);
+
+        let hovered = self.hovered_entry(canvas);
+
+        for (index, (_, clipboard)) in self.library.iter().enumerate() {
+            let slot = picker_slot(canvas, index);
+
+            canvas.set_draw_color(if hovered == Some(index) {
+                Color::RGB(70, 70, 110)
+            } else {
+                Color::RGB(30, 30, 45)
+            });
+            let _ = canvas.fill_frect(slot);
+
+            // Fits the pattern in the slot with a margin of a cell, keeping
+            // it centered.
+            let cells = (clipboard.width.max(clipboard.height) + 2) as f32;
+            let cell_size = (slot.width() / cells).min(THUMBNAIL_SIZE / 8.0);
+            let left = slot.x() + (slot.width() - clipboard.width as f32 * cell_size) / 2.0;
+            let top = slot.y() + (slot.height() - clipboard.height as f32 * cell_size) / 2.0;
+
+            canvas.set_draw_color(Color::WHITE);
+            for &(x, y) in clipboard.cells() {
+                let _ = canvas.fill_frect(FRect::new(
+                    left + x as f32 * cell_size,
+                    top + y as f32 * cell_size,
+                    cell_size,
+                    cell_size,
+                ));
+            }
+        }
+    }
+
+    /// Draws the state of the simulation and the camera in the top left of
+    /// the window.
+    pub fn draw_hud<B>(&self, snapshot: &Snapshot<B>, canvas: &mut WindowCanvas) {
+        if !self.hud_visible {
+            return;
+        }
+
+        let (camera_x, camera_y) = self.camera.position;
+        let mut text = format!(
+            "GENERATION {}{}\n\
+             POPULATION {}\n\
+             BIRTHS {} DEATHS {}\n\
+             GENERATIONS/S {:.1}\n\
+             FPS {:.0}\n\
+             CAMERA {:.1}, {:.1}\n\
+             ZOOM {:.2}X",
+            self.scrubbing.unwrap_or(snapshot.generation),
+            if self.paused { " (PAUSED)" } else { "" },
+            snapshot.population,
+            count(snapshot.births),
+            count(snapshot.deaths),
+            snapshot.generations_per_second,
+            self.fps,
+            camera_x,
+            camera_y,
+            2.0f32.powf(self.camera.scale),
+        );
+        if let Some(seed) = self.seed {
+            text += &format!("\nSEED {seed}");
+        }
+        if let Some(periodicity) = snapshot.periodicity {
+            text += &format!(
+                "\nPERIOD {} FROM GEN {}",
+                periodicity.period, periodicity.stabilized_at
+            );
+            if periodicity.displacement != (0, 0) {
+                let (dx, dy) = periodicity.displacement;
+                text += &format!(" MOVING {dx}, {dy}");
+            }
+        }
+        if !self.typed_generation.is_empty() {
+            text += &format!("\nGO TO {}_", self.typed_generation);
+        }
+        if self.recording.is_some() {
+            text += "\nRECORDING";
+        }
+
+        let padding = 4.0 * HUD_TEXT_SCALE;
+        let panel = FRect::new(
+            0.0,
+            0.0,
+            font::text_width(&text, HUD_TEXT_SCALE) + padding * 2.0,
+            font::text_height(&text, HUD_TEXT_SCALE) + padding * 2.0,
+        );
+
+        let _ = canvas.set_scale(1.0, 1.0);
+        canvas.set_blend_mode(BlendMode::Blend);
+        canvas.set_draw_color(Color::RGBA(0, 0, 0, 160));
+        let _ = canvas.fill_frect(panel);
+        canvas.set_blend_mode(BlendMode::None);
+
+        canvas.set_draw_color(Color::WHITE);
+        font::draw_text(canvas, &text, (padding, padding), HUD_TEXT_SCALE);
+    }
+
+    /// Draws a bar along the bottom of the window showing where the board is
+    /// between the earliest and latest generations it can be sought to.
+    pub fn draw_scrub_bar<B>(&self, snapshot: &Snapshot<B>, canvas: &mut WindowCanvas) {
+        let Ok((width, height)) = canvas.output_size() else {
+            return;
+        };
+        let (width, top) = (width as f32, height as f32 - SCRUB_BAR_HEIGHT);
+
+        let (earliest, latest) = scrub_range(snapshot);
+        let generation = self.scrubbing.unwrap_or(snapshot.generation);
+        let fraction = match latest - earliest {
+            0 => 1.0,
+            range => (generation.saturating_sub(earliest) as f64 / range as f64) as f32,
+        };
+
+        let _ = canvas.set_scale(1.0, 1.0);
+
+        canvas.set_draw_color(Color::RGB(30, 30, 45));
+        let _ = canvas.fill_frect(FRect::new(0.0, top, width, SCRUB_BAR_HEIGHT));
+
+        canvas.set_draw_color(Color::RGB(70, 70, 110));
+        let _ = canvas.fill_frect(FRect::new(0.0, top, width * fraction, SCRUB_BAR_HEIGHT));
+
+        canvas.set_draw_color(Color::WHITE);
+        let handle_x = (width * fraction - 2.0).clamp(0.0, width - 4.0);
+        let _ = canvas.fill_frect(FRect::new(handle_x, top, 4.0, SCRUB_BAR_HEIGHT));
+    }
+
+    /// Shows the generation, and the one being typed in to jump to, in the
+    /// window title.
+    pub fn update_title<B>(&self, snapshot: &Snapshot<B>, canvas: &mut WindowCanvas) {
+        if self.picker_open {
+            let title = match self.hovered_entry(canvas) {
+                Some(index) => {
+                    let (entry, _) = self.library[index];
+                    format!(
+                        "The Game of Life - Rust - {} ({})",
+                        entry.name, entry.category
+                    )
+                }
+                None => "The Game of Life - Rust - Pattern library".to_string(),
+            };
+
+            if canvas.window().title() != title {
+                let _ = canvas.window_mut().set_title(&title);
+            }
+            return;
+        }
+
+        let mut title = format!(
+            "The Game of Life - Rust - Generation {}",
+            self.scrubbing.unwrap_or(snapshot.generation)
+        );
+        if !self.typed_generation.is_empty() {
+            title += &format!(" - Go to {}", self.typed_generation);
+        }
+
+        if canvas.window().title() != title {
+            let _ = canvas.window_mut().set_title(&title);
+        }
+    }
+
+    pub fn draw<B: GameOfLifeBoard>(&self, snapshot: &Snapshot<B>, canvas: &mut WindowCanvas) {
+        let board = &snapshot.board;
         let scale = 2.0f32.powf(self.camera.scale);
         let _ = canvas.set_scale(scale, scale);
 
         self.draw_border(board, canvas);
 
         for (x, y) in board.alive_cells() {
-            canvas.set_draw_color(Color::WHITE);
-            let ret = canvas.draw_frect(self.tile_to_screen_rect(board, canvas, *x, *y));
+            canvas.set_draw_color(self.cell_color(board, x, y));
+            let ret = canvas.draw_frect(self.tile_to_screen_rect(board, canvas, x, y));
             #[cfg(debug_assertions)]
             if let Err(e) = ret {
                 eprintln!(
@@ -209,6 +1125,24 @@ impl RenderContext {
                 )
             }
         }
+
+        self.draw_selection(board, canvas);
+        self.draw_paste(board, canvas);
+        self.draw_scrub_bar(snapshot, canvas);
+        self.draw_hud(snapshot, canvas);
+        self.draw_picker(canvas);
+        self.update_title(snapshot, canvas);
+    }
+
+    /// The color of the live cell at `x`, `y`.
+    fn cell_color<B: GameOfLifeBoard>(&self, board: &B, x: isize, y: isize) -> Color {
+        match (&self.ages, board.age(x, y)) {
+            (Some(ages), Some(age)) => {
+                let [red, green, blue] = ages.color(age);
+                Color::RGB(red, green, blue)
+            }
+            _ => Color::WHITE,
+        }
     }
 
     pub fn background_color(&self) -> Color {
@@ -216,6 +1150,63 @@ impl RenderContext {
     }
 }
 
+/// Converts a position in window coordinates to pixels, which differ on high
+/// DPI displays.
+fn window_to_pixels(canvas: &WindowCanvas, x: i32, y: i32) -> (f32, f32) {
+    let (window_width, window_height) = canvas.window().size();
+
+    match canvas.output_size() {
+        Ok((width, height)) if window_width > 0 && window_height > 0 => (
+            x as f32 * width as f32 / window_width as f32,
+            y as f32 * height as f32 / window_height as f32,
+        ),
+        _ => (x as f32, y as f32),
+    }
+}
+
+/// Formats a count for the heads-up display, with a dash for counts which
+/// aren't known.
+fn count(count: Option<usize>) -> String {
+    count.map_or_else(|| "-".to_string(), |count| count.to_string())
+}
+
+/// Where the preview of the library entry at `index` goes in the picker.
+fn picker_slot(canvas: &WindowCanvas, index: usize) -> FRect {
+    let (width, _) = canvas.output_size().unwrap_or_default();
+    let stride = THUMBNAIL_SIZE + THUMBNAIL_GAP;
+    let columns = ((width as f32 - THUMBNAIL_GAP) / stride).max(1.0) as usize;
+
+    FRect::new(
+        THUMBNAIL_GAP + (index % columns) as f32 * stride,
+        THUMBNAIL_GAP + (index / columns) as f32 * stride,
+        THUMBNAIL_SIZE,
+        THUMBNAIL_SIZE,
+    )
+}
+
+/// The generations at either end of the scrub bar.
+fn scrub_range<B>(snapshot: &Snapshot<B>) -> (u64, u64) {
+    let latest = snapshot.latest_generation.max(snapshot.generation);
+    (snapshot.earliest_generation.min(latest), latest)
+}
+
+/// Returns the tiles on the line from `from` to `to`, both inclusive.
+fn line(from: (isize, isize), to: (isize, isize)) -> impl Iterator<Item = (isize, isize)> {
+    let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+    let steps = dx.abs().max(dy.abs());
+
+    (0..=steps).map(move |i| {
+        if steps == 0 {
+            return from;
+        }
+
+        (
+            from.0 + (dx * i + steps / 2 * dx.signum()) / steps,
+            from.1 + (dy * i + steps / 2 * dy.signum()) / steps,
+        )
+    })
+}
+
 pub struct Camera {
     pub position: (f32, f32),
     pub scale: f32,
@@ -236,3 +1227,21 @@ impl Default for Camera {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line() {
+        assert_eq!(line((2, 3), (2, 3)).collect::<Vec<_>>(), &[(2, 3)]);
+        assert_eq!(
+            line((0, 0), (3, 1)).collect::<Vec<_>>(),
+            &[(0, 0), (1, 0), (2, 1), (3, 1)]
+        );
+        assert_eq!(
+            line((1, 1), (-1, -3)).collect::<Vec<_>>(),
+            &[(1, 1), (0, 0), (0, -1), (-1, -2), (-1, -3)]
+        );
+    }
+}
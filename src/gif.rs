@@ -0,0 +1,305 @@
+//! A small GIF89a encoder for looping animations with a handful of colors.
+
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+};
+
+/// Codes are at most 12 bits long, so there are at most this many of them.
+const MAX_CODES: u16 = 4096;
+
+/// Writes an animated GIF frame by frame.
+pub struct Encoder<W: Write> {
+    writer: W,
+    width: u16,
+    height: u16,
+    /// The bits needed for every palette index, and at least 2 as GIF
+    /// requires.
+    min_code_size: u8,
+}
+
+impl<W: Write> Encoder<W> {
+    /// Starts a `width` by `height` animation which loops forever, with
+    /// frames indexing into `palette`, which must have between 1 and 256
+    /// colors.
+    pub fn new(mut writer: W, width: u16, height: u16, palette: &[[u8; 3]]) -> io::Result<Self> {
+        assert!(
+            (1..=256).contains(&palette.len()),
+            "a GIF palette has 1 to 256 colors"
+        );
+
+        // The color table holds a power of two colors, and at least 2.
+        let table_bits = palette.len().next_power_of_two().trailing_zeros().max(1);
+
+        writer.write_all(b"GIF89a")?;
+        writer.write_all(&width.to_le_bytes())?;
+        writer.write_all(&height.to_le_bytes())?;
+        // A global color table of 8 bit colors, no background color and
+        // square pixels.
+        writer.write_all(&[0xf0 | (table_bits - 1) as u8, 0, 0])?;
+        for index in 0..1 << table_bits {
+            writer.write_all(palette.get(index).unwrap_or(&[0; 3]))?;
+        }
+        // The Netscape extension, which makes the animation loop forever.
+        writer.write_all(b"\x21\xff\x0bNETSCAPE2.0\x03\x01\x00\x00\x00")?;
+
+        Ok(Self {
+            writer,
+            width,
+            height,
+            min_code_size: table_bits.max(2) as u8,
+        })
+    }
+
+    /// Adds a frame of palette indices, row by row from the top left, shown
+    /// for `delay` hundredths of a second.
+    pub fn write_frame(&mut self, pixels: &[u8], delay: u16) -> io::Result<()> {
+        assert_eq!(
+            pixels.len(),
+            usize::from(self.width) * usize::from(self.height),
+            "a frame covers the whole animation"
+        );
+
+        // A graphic control extension with the delay and no transparency.
+        self.writer.write_all(&[0x21, 0xf9, 0x04, 0x00])?;
+        self.writer.write_all(&delay.to_le_bytes())?;
+        self.writer.write_all(&[0x00, 0x00])?;
+
+        // An image descriptor covering the whole animation, using the global
+        // color table.
+        self.writer.write_all(&[0x2c, 0, 0, 0, 0])?;
+        self.writer.write_all(&self.width.to_le_bytes())?;
+        self.writer.write_all(&self.height.to_le_bytes())?;
+        self.writer.write_all(&[0x00, self.min_code_size])?;
+
+        for block in compress(pixels, self.min_code_size).chunks(255) {
+            self.writer.write_all(&[block.len() as u8])?;
+            self.writer.write_all(block)?;
+        }
+        self.writer.write_all(&[0])
+    }
+
+    /// Ends the animation, returning the writer it was written to.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.writer.write_all(&[0x3b])?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Packs codes of varying lengths into bytes, starting from the lowest bits.
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    bits: u32,
+    bit_count: u32,
+}
+
+impl BitWriter {
+    fn write(&mut self, code: u16, size: u8) {
+        self.bits |= u32::from(code) << self.bit_count;
+        self.bit_count += u32::from(size);
+
+        while self.bit_count >= 8 {
+            self.bytes.push(self.bits as u8);
+            self.bits >>= 8;
+            self.bit_count -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.bit_count > 0 {
+            self.bytes.push(self.bits as u8);
+        }
+        self.bytes
+    }
+}
+
+/// Compresses `indices` with the variable length LZW of GIF, starting with
+/// codes of `min_code_size + 1` bits.
+fn compress(indices: &[u8], min_code_size: u8) -> Vec<u8> {
+    let clear = 1u16 << min_code_size;
+    let end = clear + 1;
+
+    let mut writer = BitWriter::default();
+    let mut table: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut next_code = end + 1;
+    let mut code_size = min_code_size + 1;
+
+    writer.write(clear, code_size);
+
+    let Some((&first, rest)) = indices.split_first() else {
+        writer.write(end, code_size);
+        return writer.finish();
+    };
+    let mut prefix = u16::from(first);
+
+    for &index in rest {
+        if let Some(&code) = table.get(&(prefix, index)) {
+            prefix = code;
+            continue;
+        }
+
+        writer.write(prefix, code_size);
+
+        if next_code < MAX_CODES {
+            table.insert((prefix, index), next_code);
+            next_code += 1;
+
+            // The decoder adds every code a step later, so it widens its
+            // codes when there is one more than fits.
+            if next_code > 1 << code_size && code_size < 12 {
+                code_size += 1;
+            }
+        } else {
+            writer.write(clear, code_size);
+            table.clear();
+            next_code = end + 1;
+            code_size = min_code_size + 1;
+        }
+
+        prefix = u16::from(index);
+    }
+
+    writer.write(prefix, code_size);
+
+    // The decoder adds a code after the last one too, which can widen the
+    // end code.
+    if next_code == 1 << code_size && code_size < 12 {
+        code_size += 1;
+    }
+    writer.write(end, code_size);
+
+    writer.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Decompresses GIF LZW data the way decoders do.
+    fn decompress(data: &[u8], min_code_size: u8) -> Vec<u8> {
+        let clear = 1usize << min_code_size;
+        let end = clear + 1;
+        let initial: Vec<Vec<u8>> = (0..=end).map(|index| vec![index as u8]).collect();
+
+        let mut table = initial.clone();
+        let mut code_size = min_code_size + 1;
+        let mut position = 0;
+        let mut previous: Option<usize> = None;
+        let mut output = Vec::new();
+
+        loop {
+            let code = (0..usize::from(code_size))
+                .map(|bit| {
+                    let bit_position = position + bit;
+                    usize::from(data[bit_position / 8] >> (bit_position % 8) & 1) << bit
+                })
+                .sum::<usize>();
+            position += usize::from(code_size);
+
+            if code == clear {
+                table = initial.clone();
+                code_size = min_code_size + 1;
+                previous = None;
+                continue;
+            }
+            if code == end {
+                return output;
+            }
+
+            let entry = match table.get(code) {
+                Some(entry) => entry.clone(),
+                None => {
+                    let mut entry = table[previous.unwrap()].clone();
+                    entry.push(entry[0]);
+                    entry
+                }
+            };
+
+            if let Some(previous) = previous {
+                if table.len() < usize::from(MAX_CODES) {
+                    let mut added = table[previous].clone();
+                    added.push(entry[0]);
+                    table.push(added);
+                }
+            }
+
+            output.extend(&entry);
+            previous = Some(code);
+
+            if table.len() == 1 << code_size && code_size < 12 {
+                code_size += 1;
+            }
+        }
+    }
+
+    /// Pseudo random indices below `colors`, with runs so some codes repeat.
+    fn noise(length: usize, colors: u8) -> Vec<u8> {
+        let mut state = 1u32;
+
+        (0..length)
+            .map(|i| {
+                state = state.wrapping_mul(1664525).wrapping_add(1013904223);
+                if i % 7 < 3 {
+                    0
+                } else {
+                    (state >> 24) as u8 % colors
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_compress_round_trip() {
+        for (indices, min_code_size) in [
+            (vec![], 2),
+            (vec![1], 2),
+            (vec![0; 10_000], 2),
+            (noise(100_000, 2), 2),
+            (noise(100_000, 16), 4),
+            (noise(100_000, 255), 8),
+        ] {
+            let compressed = compress(&indices, min_code_size);
+            assert_eq!(decompress(&compressed, min_code_size), indices);
+        }
+
+        // Long runs compress well.
+        assert!(compress(&[0; 10_000], 2).len() < 200);
+    }
+
+    #[test]
+    fn test_encoder() {
+        let frames = [vec![0, 1, 1, 0, 0, 1], vec![1; 6]];
+
+        let mut encoder = Encoder::new(Vec::new(), 3, 2, &[[0; 3], [255, 128, 0]]).unwrap();
+        for frame in &frames {
+            encoder.write_frame(frame, 10).unwrap();
+        }
+        let gif = encoder.finish().unwrap();
+
+        assert_eq!(&gif[..13], b"GIF89a\x03\x00\x02\x00\xf0\x00\x00");
+        assert_eq!(&gif[13..19], [0, 0, 0, 255, 128, 0]);
+        assert_eq!(gif.last(), Some(&0x3b));
+
+        // Walks the frames, reading the image data back.
+        let mut rest = &gif[19 + 19..];
+        for frame in &frames {
+            assert_eq!(&rest[..8], [0x21, 0xf9, 0x04, 0x00, 10, 0, 0, 0]);
+            assert_eq!(&rest[8..18], [0x2c, 0, 0, 0, 0, 3, 0, 2, 0, 0]);
+            assert_eq!(rest[18], 2);
+            rest = &rest[19..];
+
+            let mut data = Vec::new();
+            while rest[0] != 0 {
+                let length = usize::from(rest[0]);
+                data.extend(&rest[1..=length]);
+                rest = &rest[length + 1..];
+            }
+            rest = &rest[1..];
+
+            assert_eq!(&decompress(&data, 2), frame);
+        }
+        assert_eq!(rest, [0x3b]);
+    }
+}
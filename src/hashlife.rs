@@ -0,0 +1,951 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt, mem,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc, Mutex, Weak,
+    },
+};
+
+use crate::{
+    game_context::{CellEdit, GameOfLifeBoard},
+    rule::Rule,
+};
+
+type NodeId = u32;
+
+const DEAD: NodeId = 0;
+const ALIVE: NodeId = 1;
+
+/// The largest supported step is `2^MAX_STEP_LOG2` generations, anything
+/// bigger would make the board outgrow its coordinates.
+pub const MAX_STEP_LOG2: u32 = 48;
+
+/// Memoized results are thrown away once there are this many of them, so
+/// long runs don't eat all of the memory.
+const MAX_CACHED_RESULTS: usize = 1 << 22;
+
+/// The universe is compacted down to the nodes boards still use once it
+/// holds this many nodes.
+const MAX_NODES: usize = 1 << 23;
+
+/// A board simulated with Bill Gosper's HashLife algorithm.
+///
+/// The board is a quadtree whose identical subtrees are shared, and the
+/// future of every subtree is memoized, so repetitive patterns can be
+/// advanced by huge numbers of generations at once with
+/// [`HashLifeBoard::step_pow2`].
+///
+/// Clones share the same node store, so cloning is cheap.
+#[derive(Clone)]
+pub struct HashLifeBoard {
+    universe: Arc<Mutex<Universe>>,
+    root: Arc<Root>,
+    /// The position of the top left corner of `root`.
+    origin: (isize, isize),
+    generation: u64,
+}
+
+impl HashLifeBoard {
+    pub fn new() -> Self {
+        Self::from_alive_cells([])
+    }
+
+    /// Creates a board from a list of live cells, ignoring duplicates.
+    pub fn from_alive_cells(cells: impl IntoIterator<Item = (isize, isize)>) -> Self {
+        let mut universe = Universe::new(Rule::default());
+        let (root, origin) = universe.build_root(cells.into_iter().collect());
+        let root = universe.register(root);
+
+        Self {
+            universe: Arc::new(Mutex::new(universe)),
+            root,
+            origin,
+            generation: 0,
+        }
+    }
+
+    /// The number of generations this board has been advanced by.
+    #[cfg(test)]
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Copies the board into a universe of its own, so it can be read while
+    /// this one is locked for a long step.
+    pub fn detached(&self) -> Self {
+        let universe = self.universe.lock().unwrap();
+        let mut detached = Universe::new(universe.rule);
+        let mut ids = HashMap::from([(DEAD, DEAD), (ALIVE, ALIVE)]);
+        let root = detached.copy(&universe, self.root.id(), &mut ids);
+        let root = detached.register(root);
+
+        Self {
+            universe: Arc::new(Mutex::new(detached)),
+            root,
+            origin: self.origin,
+            generation: self.generation,
+        }
+    }
+
+    pub fn population(&self) -> u64 {
+        let universe = self.universe.lock().unwrap();
+        universe.node(self.root.id()).population
+    }
+
+    /// Returns the top left and bottom right corners (inclusive) of the
+    /// smallest rectangle containing every live cell, without visiting
+    /// repeated parts of the pattern more than once.
+    pub fn bounding_box(&self) -> Option<((isize, isize), (isize, isize))> {
+        let universe = self.universe.lock().unwrap();
+        let mut memo = HashMap::new();
+
+        let mut edge = |side| {
+            memo.clear();
+            universe
+                .edge(self.root.id(), side, &mut memo)
+                .map(|offset| offset as isize)
+        };
+
+        let (left, top) = (edge(Side::Left)?, edge(Side::Top)?);
+        let (right, bottom) = (edge(Side::Right)?, edge(Side::Bottom)?);
+        let (x, y) = self.origin;
+
+        Some(((x + left, y + top), (x + right, y + bottom)))
+    }
+
+    pub fn alive_cells(&self) -> Vec<(isize, isize)> {
+        let universe = self.universe.lock().unwrap();
+        let root = self.root.id();
+        let mut cells = Vec::with_capacity(universe.node(root).population as usize);
+        universe.collect_alive(root, self.origin, &mut cells);
+        cells
+    }
+
+    pub fn is_alive(&self, x: isize, y: isize) -> bool {
+        let universe = self.universe.lock().unwrap();
+        let root = self.root.id();
+        let side = 1u64 << universe.node(root).level;
+
+        let offset = |position: isize, origin: isize| {
+            position
+                .checked_sub(origin)
+                .and_then(|offset| u64::try_from(offset).ok())
+                .filter(|&offset| offset < side)
+        };
+
+        match (offset(x, self.origin.0), offset(y, self.origin.1)) {
+            (Some(x), Some(y)) => universe.is_alive(root, (x, y)),
+            _ => false,
+        }
+    }
+
+    pub fn set_cells(&mut self, edits: &[CellEdit]) {
+        let mut cells: HashSet<(isize, isize)> = self.alive_cells().into_iter().collect();
+
+        for edit in edits {
+            if edit.alive {
+                cells.insert((edit.x, edit.y));
+            } else {
+                cells.remove(&(edit.x, edit.y));
+            }
+        }
+
+        let mut universe = self.universe.lock().unwrap();
+        let root;
+        (root, self.origin) = universe.build_root(cells.into_iter().collect());
+        self.root = universe.register(root);
+    }
+
+    /// Advances the board by `2^step_log2` generations in one step.
+    pub fn step_pow2(&self, rule: &Rule, step_log2: u32) -> Self {
+        assert!(
+            step_log2 <= MAX_STEP_LOG2,
+            "step_log2 (is {step_log2}) <= {MAX_STEP_LOG2}"
+        );
+
+        let mut universe = self.universe.lock().unwrap();
+        universe.set_rule(*rule);
+
+        let (mut root, mut origin) = (self.root.id(), self.origin);
+
+        // The result of a step is the centre half of the root, so make sure
+        // there is enough empty space around the pattern for it to grow into.
+        while universe.node(root).level < step_log2 as u8 + 2 || !universe.is_centred(root) {
+            (root, origin) = universe.expand(root, origin);
+        }
+        (root, origin) = universe.expand(root, origin);
+
+        let quarter = 1 << (universe.node(root).level - 2);
+        let root = universe.successor(root, step_log2 as u8);
+        let (root, origin) = universe.crop(root, (origin.0 + quarter, origin.1 + quarter));
+
+        let root = universe.register(root);
+
+        if universe.nodes.len() > universe.node_limit {
+            universe.compact();
+        } else if universe.results.len() > MAX_CACHED_RESULTS {
+            universe.results.clear();
+        }
+
+        Self {
+            universe: self.universe.clone(),
+            root,
+            origin,
+            generation: self.generation.saturating_add(1 << step_log2),
+        }
+    }
+
+    /// Advances the board by any number of generations, using the biggest
+    /// steps possible.
+    pub fn step(&self, rule: &Rule, generations: u64) -> Self {
+        let mut board = self.clone();
+
+        // Anything past the largest step is made up of repeats of it.
+        for _ in 0..generations >> MAX_STEP_LOG2 {
+            board = board.step_pow2(rule, MAX_STEP_LOG2);
+        }
+        for step_log2 in (0..MAX_STEP_LOG2).rev() {
+            if generations & (1 << step_log2) != 0 {
+                board = board.step_pow2(rule, step_log2);
+            }
+        }
+
+        board
+    }
+
+    pub fn tick(&self, rule: &Rule) -> Self {
+        self.step_pow2(rule, 0)
+    }
+}
+
+impl Default for HashLifeBoard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GameOfLifeBoard for HashLifeBoard {
+    fn alive_cells(&self) -> impl Iterator<Item = (isize, isize)> + '_ {
+        self.alive_cells().into_iter()
+    }
+
+    fn tick(&self, rule: &Rule) -> Self {
+        self.tick(rule)
+    }
+
+    fn size(&self) -> Option<(usize, usize)> {
+        None
+    }
+
+    fn is_alive(&self, x: isize, y: isize) -> bool {
+        self.is_alive(x, y)
+    }
+
+    fn set_cells(&mut self, edits: &[CellEdit]) {
+        self.set_cells(edits)
+    }
+
+    fn population(&self) -> usize {
+        self.population() as usize
+    }
+
+    fn bounding_box(&self) -> Option<((isize, isize), (isize, isize))> {
+        self.bounding_box()
+    }
+
+    fn steps_without_visiting_cells(&self) -> bool {
+        true
+    }
+
+    fn detached(&self) -> Self {
+        self.detached()
+    }
+
+    fn max_step_log2(&self) -> u32 {
+        MAX_STEP_LOG2
+    }
+
+    fn step_pow2(&self, rule: &Rule, step_log2: u32) -> Self {
+        self.step_pow2(rule, step_log2)
+    }
+
+    fn step(&self, rule: &Rule, generations: u64) -> Self {
+        self.step(rule, generations)
+    }
+}
+
+impl PartialEq for HashLifeBoard {
+    fn eq(&self, other: &Self) -> bool {
+        if self.population() != other.population() {
+            return false;
+        }
+
+        let mut cells = self.alive_cells();
+        let mut other_cells = other.alive_cells();
+        cells.sort_unstable();
+        other_cells.sort_unstable();
+
+        cells == other_cells
+    }
+}
+
+impl fmt::Debug for HashLifeBoard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut alive_cells = self.alive_cells();
+        alive_cells.sort_by_key(|&(x, y)| (y, x));
+
+        f.debug_struct("HashLifeBoard")
+            .field("generation", &self.generation)
+            .field("alive_cells", &alive_cells)
+            .finish()
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Node {
+    /// The `nw`, `ne`, `sw` and `se` quadrants of this node.
+    children: [NodeId; 4],
+    /// A node of level `n` is `2^n` cells wide, leaves are level 0.
+    level: u8,
+    population: u64,
+}
+
+#[derive(Clone, Copy)]
+enum Side {
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+/// A handle on the root node of a board, which keeps the node alive when
+/// the universe is compacted and follows it to its new id.
+///
+/// Ids are only meaningful while the universe is locked.
+struct Root(AtomicU32);
+
+impl Root {
+    fn id(&self) -> NodeId {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// The shared store of every node and memoized result.
+struct Universe {
+    nodes: Vec<Node>,
+    lookup: HashMap<[NodeId; 4], NodeId>,
+    empty: Vec<NodeId>,
+    results: HashMap<(NodeId, u8), NodeId>,
+    rule: Rule,
+    /// The roots of every board sharing the universe.
+    roots: Vec<Weak<Root>>,
+    /// The number of nodes the universe is compacted at, which is at least
+    /// `max_nodes`.
+    node_limit: usize,
+    max_nodes: usize,
+}
+
+impl Universe {
+    fn new(rule: Rule) -> Self {
+        let leaf = |population| Node {
+            children: [DEAD; 4],
+            level: 0,
+            population,
+        };
+
+        Self {
+            nodes: vec![leaf(0), leaf(1)],
+            lookup: HashMap::new(),
+            empty: vec![DEAD],
+            results: HashMap::new(),
+            rule,
+            roots: Vec::new(),
+            node_limit: MAX_NODES,
+            max_nodes: MAX_NODES,
+        }
+    }
+
+    fn node(&self, id: NodeId) -> Node {
+        self.nodes[id as usize]
+    }
+
+    /// Returns the node made of `children`, reusing an existing one if
+    /// possible.
+    fn join(&mut self, children: [NodeId; 4]) -> NodeId {
+        if let Some(&id) = self.lookup.get(&children) {
+            return id;
+        }
+
+        let id = NodeId::try_from(self.nodes.len()).expect("the universe ran out of node ids");
+        self.nodes.push(Node {
+            children,
+            level: self.node(children[0]).level + 1,
+            population: children.iter().map(|&id| self.node(id).population).sum(),
+        });
+        self.lookup.insert(children, id);
+
+        id
+    }
+
+    /// Keeps the node `id` and everything under it alive until the returned
+    /// root is dropped.
+    fn register(&mut self, id: NodeId) -> Arc<Root> {
+        // Forgets the roots of dropped boards whenever the list would grow.
+        if self.roots.len() == self.roots.capacity() {
+            self.roots.retain(|root| root.strong_count() > 0);
+        }
+
+        let root = Arc::new(Root(AtomicU32::new(id)));
+        self.roots.push(Arc::downgrade(&root));
+        root
+    }
+
+    /// Rebuilds the universe from the nodes under the roots of boards which
+    /// are still around, throwing away every other node and memoized result.
+    fn compact(&mut self) {
+        let mut compacted = Universe::new(self.rule);
+        let mut ids = HashMap::from([(DEAD, DEAD), (ALIVE, ALIVE)]);
+
+        self.roots.retain(|root| root.strong_count() > 0);
+        for root in self.roots.iter().filter_map(Weak::upgrade) {
+            let id = compacted.copy(self, root.id(), &mut ids);
+            root.0.store(id, Ordering::Relaxed);
+        }
+
+        compacted.roots = mem::take(&mut self.roots);
+        // Compacting again before the universe has doubled in size wouldn't
+        // free much.
+        compacted.max_nodes = self.max_nodes;
+        compacted.node_limit = self.max_nodes.max(compacted.nodes.len() * 2);
+        *self = compacted;
+    }
+
+    /// Copies the node `id` of `from` and everything under it, with `ids`
+    /// mapping nodes of `from` to the ones already copied.
+    fn copy(&mut self, from: &Universe, id: NodeId, ids: &mut HashMap<NodeId, NodeId>) -> NodeId {
+        if let Some(&copied) = ids.get(&id) {
+            return copied;
+        }
+
+        let children = from
+            .node(id)
+            .children
+            .map(|child| self.copy(from, child, ids));
+        let copied = self.join(children);
+        ids.insert(id, copied);
+
+        copied
+    }
+
+    fn empty(&mut self, level: u8) -> NodeId {
+        while self.empty.len() <= level as usize {
+            let empty = self.empty[self.empty.len() - 1];
+            let next = self.join([empty; 4]);
+            self.empty.push(next);
+        }
+
+        self.empty[level as usize]
+    }
+
+    fn set_rule(&mut self, rule: Rule) {
+        if self.rule != rule {
+            self.rule = rule;
+            self.results.clear();
+        }
+    }
+
+    /// Builds the smallest root node holding `cells`, returning it along
+    /// with the position of its top left corner.
+    fn build_root(&mut self, cells: Vec<(isize, isize)>) -> (NodeId, (isize, isize)) {
+        let (min_x, min_y, max_x, max_y) = cells.iter().fold(
+            (isize::MAX, isize::MAX, isize::MIN, isize::MIN),
+            |(min_x, min_y, max_x, max_y), &(x, y)| {
+                (min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y))
+            },
+        );
+
+        let (origin, side) = if cells.is_empty() {
+            ((0, 0), 1)
+        } else {
+            (
+                (min_x, min_y),
+                (max_x - min_x).max(max_y - min_y) as usize + 1,
+            )
+        };
+
+        let level = side.next_power_of_two().trailing_zeros().max(3) as u8;
+
+        (self.build(cells, origin, level), origin)
+    }
+
+    fn build(&mut self, cells: Vec<(isize, isize)>, (x, y): (isize, isize), level: u8) -> NodeId {
+        if cells.is_empty() {
+            return self.empty(level);
+        }
+
+        if level == 0 {
+            return ALIVE;
+        }
+
+        let half = 1 << (level - 1);
+        let (top, bottom): (Vec<_>, Vec<_>) = cells.into_iter().partition(|&(_, cy)| cy < y + half);
+        let (nw, ne): (Vec<_>, Vec<_>) = top.into_iter().partition(|&(cx, _)| cx < x + half);
+        let (sw, se): (Vec<_>, Vec<_>) = bottom.into_iter().partition(|&(cx, _)| cx < x + half);
+
+        let children = [
+            self.build(nw, (x, y), level - 1),
+            self.build(ne, (x + half, y), level - 1),
+            self.build(sw, (x, y + half), level - 1),
+            self.build(se, (x + half, y + half), level - 1),
+        ];
+
+        self.join(children)
+    }
+
+    /// Returns whether the cell at `(x, y)` from the top left corner of the
+    /// node is alive.
+    fn is_alive(&self, mut id: NodeId, (mut x, mut y): (u64, u64)) -> bool {
+        loop {
+            let node = self.node(id);
+
+            if node.population == 0 || node.level == 0 {
+                return node.population != 0;
+            }
+
+            let half = 1 << (node.level - 1);
+            id = node.children[usize::from(y >= half) * 2 + usize::from(x >= half)];
+            (x, y) = (x % half, y % half);
+        }
+    }
+
+    fn collect_alive(&self, id: NodeId, (x, y): (isize, isize), cells: &mut Vec<(isize, isize)>) {
+        let node = self.node(id);
+
+        if node.population == 0 {
+            return;
+        }
+
+        if node.level == 0 {
+            cells.push((x, y));
+            return;
+        }
+
+        let half = 1 << (node.level - 1);
+        let [nw, ne, sw, se] = node.children;
+        self.collect_alive(nw, (x, y), cells);
+        self.collect_alive(ne, (x + half, y), cells);
+        self.collect_alive(sw, (x, y + half), cells);
+        self.collect_alive(se, (x + half, y + half), cells);
+    }
+
+    /// Returns the offset of the outermost live cell on `side` of the node,
+    /// visiting every distinct node at most once.
+    fn edge(&self, id: NodeId, side: Side, memo: &mut HashMap<NodeId, u64>) -> Option<u64> {
+        let node = self.node(id);
+
+        if node.population == 0 {
+            return None;
+        }
+
+        if node.level == 0 {
+            return Some(0);
+        }
+
+        if let Some(&offset) = memo.get(&id) {
+            return Some(offset);
+        }
+
+        let half = 1 << (node.level - 1);
+        let [nw, ne, sw, se] = node.children;
+
+        // The `near` quadrants are the ones closest to `side`, if they have
+        // any live cells then the `far` ones don't need to be looked at.
+        let (near, far, near_offset, far_offset) = match side {
+            Side::Left => ([nw, sw], [ne, se], 0, half),
+            Side::Top => ([nw, ne], [sw, se], 0, half),
+            Side::Right => ([ne, se], [nw, sw], half, 0),
+            Side::Bottom => ([sw, se], [nw, ne], half, 0),
+        };
+
+        let mut outermost = |quadrants: [NodeId; 2], offset: u64| {
+            let a = self.edge(quadrants[0], side, memo);
+            let b = self.edge(quadrants[1], side, memo);
+
+            let edge = match (a, b) {
+                (Some(a), Some(b)) => match side {
+                    Side::Left | Side::Top => a.min(b),
+                    Side::Right | Side::Bottom => a.max(b),
+                },
+                (a, b) => a.or(b)?,
+            };
+
+            Some(edge + offset)
+        };
+
+        let offset = outermost(near, near_offset).or_else(|| outermost(far, far_offset))?;
+        memo.insert(id, offset);
+
+        Some(offset)
+    }
+
+    /// Returns `true` if every live cell is inside of the centre half of the
+    /// node.
+    fn is_centred(&self, id: NodeId) -> bool {
+        let [nw, ne, sw, se] = self.node(id).children;
+
+        [(nw, 3), (ne, 2), (sw, 1), (se, 0)]
+            .into_iter()
+            .all(|(quadrant, inner)| {
+                let quadrant = self.node(quadrant);
+                quadrant.population == self.node(quadrant.children[inner]).population
+            })
+    }
+
+    /// Surrounds the node with empty space, doubling its size while keeping
+    /// its cells in place.
+    fn expand(&mut self, id: NodeId, (x, y): (isize, isize)) -> (NodeId, (isize, isize)) {
+        let node = self.node(id);
+        let empty = self.empty(node.level - 1);
+        let [nw, ne, sw, se] = node.children;
+
+        let children = [
+            self.join([empty, empty, empty, nw]),
+            self.join([empty, empty, ne, empty]),
+            self.join([empty, sw, empty, empty]),
+            self.join([se, empty, empty, empty]),
+        ];
+
+        let half = 1 << (node.level - 1);
+        (self.join(children), (x - half, y - half))
+    }
+
+    /// Removes empty space around the node, the opposite of
+    /// [`Universe::expand`].
+    fn crop(&mut self, mut id: NodeId, (mut x, mut y): (isize, isize)) -> (NodeId, (isize, isize)) {
+        while self.node(id).level > 3 && self.is_centred(id) {
+            let quarter = 1 << (self.node(id).level - 2);
+            id = self.centre(id);
+            (x, y) = (x + quarter, y + quarter);
+        }
+
+        (id, (x, y))
+    }
+
+    fn centre(&mut self, id: NodeId) -> NodeId {
+        let [nw, ne, sw, se] = self.node(id).children;
+
+        self.join([
+            self.node(nw).children[3],
+            self.node(ne).children[2],
+            self.node(sw).children[1],
+            self.node(se).children[0],
+        ])
+    }
+
+    fn centre_horizontal(&mut self, west: NodeId, east: NodeId) -> NodeId {
+        let [_, west_ne, _, west_se] = self.node(west).children;
+        let [east_nw, _, east_sw, _] = self.node(east).children;
+
+        self.join([west_ne, east_nw, west_se, east_sw])
+    }
+
+    fn centre_vertical(&mut self, north: NodeId, south: NodeId) -> NodeId {
+        let [_, _, north_sw, north_se] = self.node(north).children;
+        let [south_nw, south_ne, _, _] = self.node(south).children;
+
+        self.join([north_sw, north_se, south_nw, south_ne])
+    }
+
+    /// Returns the centre half of the node advanced by `2^step_log2`
+    /// generations, `step_log2` can be at most the node's level minus 2.
+    fn successor(&mut self, id: NodeId, step_log2: u8) -> NodeId {
+        let node = self.node(id);
+        debug_assert!(node.level >= 2 && step_log2 <= node.level - 2);
+
+        if node.population == 0 {
+            return self.empty(node.level - 1);
+        }
+
+        if let Some(&result) = self.results.get(&(id, step_log2)) {
+            return result;
+        }
+
+        let result = if node.level == 2 {
+            self.successor_base(id)
+        } else {
+            let [nw, ne, sw, se] = node.children;
+            let centre = self.centre(id);
+            let top = self.centre_horizontal(nw, ne);
+            let left = self.centre_vertical(nw, sw);
+            let right = self.centre_vertical(ne, se);
+            let bottom = self.centre_horizontal(sw, se);
+
+            // Nine overlapping nodes, each half the size of `node`.
+            let parts = [nw, top, ne, left, centre, right, sw, bottom, se];
+
+            // Advancing at full speed means advancing twice by half of the
+            // step, otherwise the first half is skipped by only taking the
+            // centres.
+            let full_speed = step_log2 == node.level - 2;
+            let parts = parts.map(|part| {
+                if full_speed {
+                    self.successor(part, node.level - 3)
+                } else {
+                    self.centre(part)
+                }
+            });
+            let step_log2 = if full_speed {
+                node.level - 3
+            } else {
+                step_log2
+            };
+
+            let children = [
+                self.join([parts[0], parts[1], parts[3], parts[4]]),
+                self.join([parts[1], parts[2], parts[4], parts[5]]),
+                self.join([parts[3], parts[4], parts[6], parts[7]]),
+                self.join([parts[4], parts[5], parts[7], parts[8]]),
+            ]
+            .map(|child| self.successor(child, step_log2));
+
+            self.join(children)
+        };
+
+        self.results.insert((id, step_log2), result);
+        result
+    }
+
+    /// Advances the centre 2x2 cells of a 4x4 node by one generation.
+    fn successor_base(&mut self, id: NodeId) -> NodeId {
+        let mut cells = [[false; 4]; 4];
+        let [nw, ne, sw, se] = self.node(id).children;
+
+        for (quadrant, (x, y)) in [(nw, (0, 0)), (ne, (2, 0)), (sw, (0, 2)), (se, (2, 2))] {
+            for (i, leaf) in self.node(quadrant).children.into_iter().enumerate() {
+                cells[y + i / 2][x + i % 2] = leaf == ALIVE;
+            }
+        }
+
+        let next = [(1, 1), (2, 1), (1, 2), (2, 2)].map(|(x, y): (usize, usize)| {
+            let neighbour_count = (y - 1..=y + 1)
+                .flat_map(|ny| (x - 1..=x + 1).map(move |nx| (nx, ny)))
+                .filter(|&(nx, ny)| (nx, ny) != (x, y) && cells[ny][nx])
+                .count() as i8;
+
+            let is_alive = if cells[y][x] {
+                self.rule.survives(neighbour_count)
+            } else {
+                self.rule.is_born(neighbour_count)
+            };
+
+            if is_alive {
+                ALIVE
+            } else {
+                DEAD
+            }
+        });
+
+        self.join(next)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+    use crate::infinite_board::InfiniteBoard;
+
+    const GLIDER: [(isize, isize); 5] = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+
+    fn sorted(mut cells: Vec<(isize, isize)>) -> Vec<(isize, isize)> {
+        cells.sort_unstable();
+        cells
+    }
+
+    #[test]
+    fn test_from_alive_cells() {
+        let board = HashLifeBoard::from_alive_cells(GLIDER.map(|(x, y)| (x - 20, y + 7)));
+
+        assert_eq!(board.population(), 5);
+        assert_eq!(
+            sorted(board.alive_cells()),
+            sorted(GLIDER.map(|(x, y)| (x - 20, y + 7)).to_vec())
+        );
+        assert_eq!(board.bounding_box(), Some(((-20, 7), (-18, 9))));
+
+        let empty = HashLifeBoard::new();
+        assert_eq!(empty.population(), 0);
+        assert_eq!(empty.bounding_box(), None);
+    }
+
+    #[test]
+    fn test_blinker() {
+        let vertical = HashLifeBoard::from_alive_cells([(1, 0), (1, 1), (1, 2)]);
+        let horizontal = HashLifeBoard::from_alive_cells([(0, 1), (1, 1), (2, 1)]);
+
+        assert_eq!(vertical.tick(&Rule::CONWAY), horizontal);
+        assert_eq!(vertical.step(&Rule::CONWAY, 2), vertical);
+        assert_eq!(vertical.step_pow2(&Rule::CONWAY, 10), vertical);
+
+        // Steps bigger than the largest one are split up.
+        let huge = (3 << MAX_STEP_LOG2) + 1;
+        assert_eq!(vertical.step(&Rule::CONWAY, huge), horizontal);
+    }
+
+    #[test]
+    fn test_glider_step_pow2() {
+        let board = HashLifeBoard::from_alive_cells(GLIDER);
+
+        // A glider moves one cell diagonally every four generations.
+        let board = board.step_pow2(&Rule::CONWAY, 20);
+        let offset = 1 << 18;
+
+        assert_eq!(board.generation(), 1 << 20);
+        assert_eq!(board.population(), 5);
+        assert_eq!(
+            board.bounding_box(),
+            Some(((offset, offset), (offset + 2, offset + 2)))
+        );
+        assert_eq!(
+            sorted(board.alive_cells()),
+            sorted(GLIDER.map(|(x, y)| (x + offset, y + offset)).to_vec())
+        );
+    }
+
+    #[test]
+    fn test_matches_infinite_board() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let soup: Vec<_> = (0..16)
+            .flat_map(|y| (0..16).map(move |x| (x, y)))
+            .filter(|_| rng.gen_bool(0.5))
+            .collect();
+
+        let highlife = "B36/S23".parse().unwrap();
+
+        for rule in [Rule::CONWAY, highlife] {
+            let mut board = HashLifeBoard::from_alive_cells(soup.iter().cloned());
+            let mut expected = InfiniteBoard::from_alive_cells(soup.iter().cloned());
+
+            for _ in 0..64 {
+                board = board.tick(&rule);
+                expected = expected.tick(&rule);
+
+                assert_eq!(
+                    sorted(board.alive_cells()),
+                    sorted(expected.alive_cells().clone())
+                );
+            }
+
+            for _ in 0..64 {
+                expected = expected.tick(&rule);
+            }
+            let board = board.step_pow2(&rule, 6);
+
+            assert_eq!(board.generation(), 128);
+            assert_eq!(
+                sorted(board.alive_cells()),
+                sorted(expected.alive_cells().clone())
+            );
+        }
+    }
+
+    #[test]
+    fn test_r_pentomino_stabilizes() {
+        let board = HashLifeBoard::from_alive_cells([(1, 0), (2, 0), (0, 1), (1, 1), (1, 2)]);
+
+        let board = board.step(&Rule::CONWAY, 1103);
+
+        assert_eq!(board.generation(), 1103);
+        assert_eq!(board.population(), 116);
+    }
+
+    #[test]
+    fn test_compact() {
+        let rule = "B36/S23".parse().unwrap();
+        let start = HashLifeBoard::from_alive_cells(GLIDER);
+        {
+            let mut universe = start.universe.lock().unwrap();
+            universe.max_nodes = 200;
+            universe.node_limit = 200;
+        }
+
+        let mut board = start.clone();
+        let mut states = vec![start.clone()];
+        for _ in 0..64 {
+            board = board.step(&rule, 3);
+            states.push(board.clone());
+        }
+        // Only every other state is kept, so there is something to free.
+        let states: Vec<_> = states.into_iter().step_by(2).collect();
+
+        for _ in 0..64 {
+            board = board.tick(&rule);
+        }
+
+        let universe = board.universe.lock().unwrap();
+        // Without compacting there are thousands of nodes.
+        assert!(universe.nodes.len() < 1000);
+        assert!(universe.roots.len() < 200);
+        drop(universe);
+
+        // Boards kept from before compacting still hold the same cells.
+        for (i, state) in states.iter().enumerate() {
+            let expected = HashLifeBoard::from_alive_cells(GLIDER).step(&rule, 6 * i as u64);
+            assert_eq!(sorted(state.alive_cells()), sorted(expected.alive_cells()));
+        }
+        assert_eq!(board.population(), 5);
+        assert_eq!(
+            board,
+            HashLifeBoard::from_alive_cells(GLIDER).step(&rule, 256)
+        );
+    }
+
+    #[test]
+    fn test_detached() {
+        let board = HashLifeBoard::from_alive_cells(GLIDER).step(&Rule::CONWAY, 1000);
+        let detached = board.detached();
+
+        // Reading the copy doesn't wait for the original's universe.
+        let _locked = board.universe.lock().unwrap();
+        assert_eq!(detached.population(), 5);
+        assert_eq!(detached.bounding_box(), Some(((250, 250), (252, 252))));
+    }
+
+    #[test]
+    fn test_set_cells() {
+        let mut board = HashLifeBoard::from_alive_cells(GLIDER).step(&Rule::CONWAY, 4);
+
+        board.set_cells(&[
+            CellEdit {
+                x: 3,
+                y: 3,
+                alive: false,
+            },
+            CellEdit {
+                x: -100,
+                y: 50,
+                alive: true,
+            },
+        ]);
+
+        let mut expected: Vec<_> = GLIDER.iter().map(|&(x, y)| (x + 1, y + 1)).collect();
+        expected.retain(|&cell| cell != (3, 3));
+        expected.push((-100, 50));
+
+        assert_eq!(board.generation(), 4);
+        assert_eq!(sorted(board.alive_cells()), sorted(expected.clone()));
+
+        for y in -120..60 {
+            for x in -120..10 {
+                assert_eq!(
+                    board.is_alive(x, y),
+                    expected.contains(&(x, y)),
+                    "({x}, {y})"
+                );
+            }
+        }
+        assert!(!board.is_alive(isize::MIN, isize::MAX));
+    }
+}
@@ -0,0 +1,256 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::game_context::{CellEdit, GameOfLifeBoard};
+
+/// At most this many changes are kept to be undone.
+const MAX_CHANGES: usize = 1000;
+
+/// The oldest changes are forgotten once the undo history holds more live
+/// cells than this, so a few generations of a huge soup can't eat all of the
+/// memory.
+const MAX_STORED_CELLS: usize = 8_000_000;
+
+/// The cells which changed between two states of a board.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Change {
+    born: Vec<(isize, isize)>,
+    died: Vec<(isize, isize)>,
+    /// How many generations the board advanced by, which is 0 for edits.
+    generations: u64,
+}
+
+impl Change {
+    fn len(&self) -> usize {
+        self.born.len() + self.died.len()
+    }
+
+    /// Returns the edits which redo this change, or undo it if `forwards` is
+    /// `false`.
+    fn edits(&self, forwards: bool) -> Vec<CellEdit> {
+        let edit = |alive| move |&(x, y): &(isize, isize)| CellEdit { x, y, alive };
+
+        self.born
+            .iter()
+            .map(edit(forwards))
+            .chain(self.died.iter().map(edit(!forwards)))
+            .collect()
+    }
+}
+
+/// A bounded undo/redo history of edits and generations, storing only the
+/// cells each of them changed.
+#[derive(Default)]
+pub struct History {
+    undo: VecDeque<Change>,
+    redo: Vec<Change>,
+    /// The original state of every cell changed by the edit in progress.
+    pending_edit: HashMap<(isize, isize), bool>,
+    stored_cells: usize,
+}
+
+impl History {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Applies `edits` to `board` as part of the edit in progress, which
+    /// becomes a single undo step once [`History::end_edit`] is called.
+    pub fn edit<B: GameOfLifeBoard>(&mut self, board: &mut B, edits: &[CellEdit]) {
+        for edit in edits {
+            self.pending_edit
+                .entry((edit.x, edit.y))
+                .or_insert_with(|| board.is_alive(edit.x, edit.y));
+        }
+
+        board.set_cells(edits);
+    }
+
+    /// Finishes the edit in progress on `board`.
+    pub fn end_edit<B: GameOfLifeBoard>(&mut self, board: &B) {
+        if self.pending_edit.is_empty() {
+            return;
+        }
+
+        let mut change = Change {
+            born: Vec::new(),
+            died: Vec::new(),
+            generations: 0,
+        };
+
+        for ((x, y), was_alive) in self.pending_edit.drain() {
+            match (was_alive, board.is_alive(x, y)) {
+                (false, true) => change.born.push((x, y)),
+                (true, false) => change.died.push((x, y)),
+                _ => (),
+            }
+        }
+
+        self.push(change);
+    }
+
+    /// Records `board` having advanced by `generations` generations to
+    /// `next`, returning how many cells were born and how many died.
+    pub fn record_step<B: GameOfLifeBoard>(
+        &mut self,
+        board: &B,
+        next: &B,
+        generations: u64,
+    ) -> (usize, usize) {
+        self.end_edit(board);
+
+        // Looking cells up on the other board is much cheaper than hashing
+        // every live cell of both.
+        let change = Change {
+            born: next
+                .alive_cells()
+                .filter(|&(x, y)| !board.is_alive(x, y))
+                .collect(),
+            died: board
+                .alive_cells()
+                .filter(|&(x, y)| !next.is_alive(x, y))
+                .collect(),
+            generations,
+        };
+        let counts = (change.born.len(), change.died.len());
+
+        self.push(change);
+        counts
+    }
+
+    /// Reverts the last change to `board`, returning the number of
+    /// generations it went back by, or `None` if there is nothing to undo.
+    pub fn undo<B: GameOfLifeBoard>(&mut self, board: &mut B) -> Option<u64> {
+        self.end_edit(board);
+
+        let change = self.undo.pop_back()?;
+        self.stored_cells -= change.len();
+
+        board.set_cells(&change.edits(false));
+        let generations = change.generations;
+        self.redo.push(change);
+
+        Some(generations)
+    }
+
+    /// Reapplies the last undone change to `board`, returning the number of
+    /// generations it went forward by, or `None` if there is nothing to redo.
+    pub fn redo<B: GameOfLifeBoard>(&mut self, board: &mut B) -> Option<u64> {
+        self.end_edit(board);
+
+        let change = self.redo.pop()?;
+        board.set_cells(&change.edits(true));
+        let generations = change.generations;
+
+        self.stored_cells += change.len();
+        self.undo.push_back(change);
+        self.forget_oldest();
+
+        Some(generations)
+    }
+
+    /// Forgets every change, for when the board jumps to another generation.
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    fn push(&mut self, change: Change) {
+        self.redo.clear();
+
+        self.stored_cells += change.len();
+        self.undo.push_back(change);
+        self.forget_oldest();
+    }
+
+    fn forget_oldest(&mut self) {
+        while self.undo.len() > MAX_CHANGES
+            || self.stored_cells > MAX_STORED_CELLS && self.undo.len() > 1
+        {
+            let change = self.undo.pop_front().unwrap();
+            self.stored_cells -= change.len();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        board::{Anchor, Board},
+        rule::Rule,
+    };
+
+    fn edit(x: isize, y: isize, alive: bool) -> CellEdit {
+        CellEdit { x, y, alive }
+    }
+
+    #[test]
+    fn test_undo_redo_edits() {
+        let start = Board::from_cells(&[&[1, 1]]).resized(8, 8, Anchor::TopLeft);
+        let mut board = start.clone();
+        let mut history = History::new();
+
+        // A single stroke, painting over the same cells more than once.
+        history.edit(&mut board, &[edit(0, 0, false), edit(2, 0, true)]);
+        history.edit(&mut board, &[edit(0, 0, true), edit(3, 3, true)]);
+        history.end_edit(&board);
+        let painted = board.clone();
+
+        history.edit(&mut board, &[edit(1, 0, false)]);
+        let erased = board.clone();
+
+        assert_eq!(history.undo(&mut board), Some(0));
+        assert_eq!(board, painted);
+        assert_eq!(history.undo(&mut board), Some(0));
+        assert_eq!(board, start);
+        assert_eq!(history.undo(&mut board), None);
+
+        assert_eq!(history.redo(&mut board), Some(0));
+        assert_eq!(board, painted);
+        assert_eq!(history.redo(&mut board), Some(0));
+        assert_eq!(board, erased);
+        assert_eq!(history.redo(&mut board), None);
+    }
+
+    #[test]
+    fn test_step_backwards() {
+        let mut board =
+            Board::from_cells(&[&[0, 1, 0], &[0, 0, 1], &[1, 1, 1]]).resized(6, 6, Anchor::TopLeft);
+        let mut history = History::new();
+        let mut generations = vec![board.clone()];
+
+        for _ in 0..6 {
+            let next = board.tick(&Rule::CONWAY);
+            history.record_step(&board, &next, 1);
+            board = next;
+            generations.push(board.clone());
+        }
+
+        for expected in generations.iter().rev().skip(1) {
+            assert_eq!(history.undo(&mut board), Some(1));
+            assert_eq!(&board, expected);
+        }
+
+        history.redo(&mut board);
+        history.redo(&mut board);
+        assert_eq!(board, generations[2]);
+
+        // Anything new throws away what could have been redone.
+        history.edit(&mut board, &[edit(5, 5, true)]);
+        history.end_edit(&board);
+        assert_eq!(history.redo(&mut board), None);
+    }
+
+    #[test]
+    fn test_bounded() {
+        let mut board = Board::new(4, 4);
+        let mut history = History::new();
+
+        for i in 0..MAX_CHANGES + 10 {
+            history.edit(&mut board, &[edit(0, 0, i % 2 == 0)]);
+            history.end_edit(&board);
+        }
+
+        assert_eq!(history.undo.len(), MAX_CHANGES);
+        assert_eq!(history.stored_cells, MAX_CHANGES);
+    }
+}
@@ -0,0 +1,355 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+use crate::{
+    board::Cell,
+    game_context::{CellEdit, GameOfLifeBoard},
+    rule::Rule,
+};
+
+const CHUNK_SIZE: usize = 32;
+
+type Chunk = [[Cell; CHUNK_SIZE]; CHUNK_SIZE];
+
+/// An unbounded board with signed coordinates.
+///
+/// Cells are stored in fixed size chunks which are only allocated around
+/// live cells, so the board grows and shrinks with the pattern on it.
+#[derive(Clone, Default)]
+pub struct InfiniteBoard {
+    chunks: HashMap<(isize, isize), Box<Chunk>>,
+    alive_cells: Vec<(isize, isize)>,
+}
+
+impl InfiniteBoard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[cfg(test)]
+    pub fn from_cells(cells: &[&[usize]]) -> Self {
+        let mut board = Self::new();
+
+        for (y, row) in cells.iter().enumerate() {
+            for (x, cell) in row.iter().enumerate() {
+                if *cell != 0 {
+                    board.make_alive(x as isize, y as isize);
+                }
+            }
+        }
+
+        board
+    }
+
+    /// Creates a board from a list of live cells, ignoring duplicates.
+    pub fn from_alive_cells(cells: impl IntoIterator<Item = (isize, isize)>) -> Self {
+        let mut board = Self::new();
+
+        for (x, y) in cells {
+            if board.cell(x, y).is_dead() {
+                board.make_alive(x, y);
+            }
+        }
+
+        board
+    }
+
+    pub fn with_alive_capacity(capacity: usize) -> Self {
+        Self {
+            chunks: HashMap::new(),
+            alive_cells: Vec::with_capacity(capacity),
+        }
+    }
+
+    pub fn alive_cells(&self) -> &Vec<(isize, isize)> {
+        &self.alive_cells
+    }
+
+    pub fn cell(&self, x: isize, y: isize) -> Cell {
+        let ((chunk_x, chunk_y), (local_x, local_y)) = split_position(x, y);
+
+        match self.chunks.get(&(chunk_x, chunk_y)) {
+            Some(chunk) => chunk[local_y][local_x],
+            None => Cell::empty(),
+        }
+    }
+
+    /// Sets the state of every cell in `edits`.
+    pub fn set_cells(&mut self, edits: &[CellEdit]) {
+        // Killed cells are removed from `alive_cells` all at once at the end.
+        let mut killed = HashSet::new();
+
+        for edit in edits {
+            let (x, y) = (edit.x, edit.y);
+
+            if self.cell(x, y).is_alive() == edit.alive {
+                continue;
+            }
+
+            if edit.alive {
+                if !killed.remove(&(x, y)) {
+                    self.alive_cells.push((x, y));
+                }
+
+                self.propagate_neighbours(x, y);
+                self.cell_mut(x, y).make_alive();
+            } else {
+                killed.insert((x, y));
+
+                for (other_x, other_y) in get_adjacents(x, y) {
+                    self.cell_mut(other_x, other_y).remove_neighbour();
+                }
+                self.cell_mut(x, y).make_dead();
+            }
+        }
+
+        if !killed.is_empty() {
+            self.alive_cells.retain(|cell| !killed.contains(cell));
+        }
+    }
+
+    pub fn tick(&self, rule: &Rule) -> Self {
+        let mut board = Self::with_alive_capacity(self.alive_cells.len());
+
+        for (x, y) in self.alive_cells.iter().cloned() {
+            if self.cell(x, y).next_alive_state(rule) {
+                board.make_alive(x, y);
+            }
+
+            for (x, y) in get_adjacents(x, y) {
+                let cell = self.cell(x, y);
+
+                if cell.is_dead() && board.cell(x, y).is_dead() && cell.next_dead_state(rule) {
+                    board.make_alive(x, y);
+                }
+            }
+        }
+
+        board
+    }
+
+    fn make_alive(&mut self, x: isize, y: isize) {
+        self.alive_cells.push((x, y));
+        self.propagate_neighbours(x, y);
+        self.cell_mut(x, y).make_alive();
+    }
+
+    fn propagate_neighbours(&mut self, x: isize, y: isize) {
+        for (other_x, other_y) in get_adjacents(x, y) {
+            self.cell_mut(other_x, other_y).add_neighbour();
+        }
+    }
+
+    fn cell_mut(&mut self, x: isize, y: isize) -> &mut Cell {
+        let (chunk, (local_x, local_y)) = split_position(x, y);
+
+        let chunk = self
+            .chunks
+            .entry(chunk)
+            .or_insert_with(|| Box::new([[Cell::empty(); CHUNK_SIZE]; CHUNK_SIZE]));
+
+        &mut chunk[local_y][local_x]
+    }
+}
+
+impl GameOfLifeBoard for InfiniteBoard {
+    fn alive_cells(&self) -> impl Iterator<Item = (isize, isize)> + '_ {
+        self.alive_cells.iter().cloned()
+    }
+
+    fn tick(&self, rule: &Rule) -> Self {
+        self.tick(rule)
+    }
+
+    fn size(&self) -> Option<(usize, usize)> {
+        None
+    }
+
+    fn is_alive(&self, x: isize, y: isize) -> bool {
+        self.cell(x, y).is_alive()
+    }
+
+    fn set_cells(&mut self, edits: &[CellEdit]) {
+        self.set_cells(edits)
+    }
+}
+
+impl PartialEq for InfiniteBoard {
+    fn eq(&self, other: &Self) -> bool {
+        if self.alive_cells.len() != other.alive_cells.len() {
+            return false;
+        }
+
+        let map: HashSet<&(isize, isize)> = HashSet::from_iter(self.alive_cells.iter());
+
+        other.alive_cells.iter().all(|key| map.contains(key))
+    }
+}
+
+impl fmt::Debug for InfiniteBoard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut alive_cells = self.alive_cells.clone();
+        alive_cells.sort_by_key(|&(x, y)| (y, x));
+
+        f.debug_struct("InfiniteBoard")
+            .field("chunks", &self.chunks.len())
+            .field("alive_cells", &alive_cells)
+            .finish()
+    }
+}
+
+/// Splits a position into the position of its chunk and its position
+/// inside of that chunk.
+fn split_position(x: isize, y: isize) -> ((isize, isize), (usize, usize)) {
+    const SIZE: isize = CHUNK_SIZE as isize;
+
+    (
+        (x.div_euclid(SIZE), y.div_euclid(SIZE)),
+        (x.rem_euclid(SIZE) as usize, y.rem_euclid(SIZE) as usize),
+    )
+}
+
+fn get_adjacents(to_x: isize, to_y: isize) -> impl Iterator<Item = (isize, isize)> {
+    (-1isize..=1).flat_map(move |y| {
+        (-1isize..=1).filter_map(move |x| {
+            if x == 0 && y == 0 {
+                None
+            } else {
+                Some((to_x + x, to_y + y))
+            }
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+    use crate::board::Board;
+
+    #[test]
+    fn test_split_position() {
+        assert_eq!(split_position(0, 0), ((0, 0), (0, 0)));
+        assert_eq!(split_position(31, 32), ((0, 1), (31, 0)));
+        assert_eq!(split_position(-1, -32), ((-1, -1), (31, 0)));
+        assert_eq!(split_position(-33, 5), ((-2, 0), (31, 5)));
+    }
+
+    #[test]
+    fn test_blinker() {
+        let vertical = InfiniteBoard::from_cells(&[&[0, 1, 0], &[0, 1, 0], &[0, 1, 0]]);
+        let horizontal = InfiniteBoard::from_cells(&[&[0, 0, 0], &[1, 1, 1], &[0, 0, 0]]);
+
+        assert_eq!(vertical.tick(&Rule::CONWAY), horizontal);
+        assert_eq!(horizontal.tick(&Rule::CONWAY), vertical);
+    }
+
+    #[test]
+    fn test_neighbours_across_chunks() {
+        let board = InfiniteBoard::from_alive_cells([(-1, -1), (0, 0), (-1, 0)]);
+
+        assert_eq!(board.cell(0, -1).neighbour_count(), 3);
+        assert_eq!(board.cell(-2, 1).neighbour_count(), 1);
+        assert!(board.cell(0, -1).next_dead_state(&Rule::CONWAY));
+    }
+
+    #[test]
+    fn test_glider_travels_into_negative_coordinates() {
+        let glider = [(0, 0), (1, 0), (2, 0), (0, 1), (1, 2)];
+        let mut board = InfiniteBoard::from_alive_cells(glider);
+
+        // A glider moves one cell diagonally every four generations.
+        for _ in 0..400 {
+            board = board.tick(&Rule::CONWAY);
+        }
+
+        let expected = InfiniteBoard::from_alive_cells(glider.map(|(x, y)| (x - 100, y - 100)));
+
+        assert_eq!(board, expected);
+        assert_eq!(board.bounding_box(), Some(((-100, -100), (-98, -98))));
+        assert!(board.chunks.len() <= 4);
+    }
+
+    #[test]
+    fn test_matches_board_away_from_edges() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut soup = Vec::new();
+
+        for y in 24..40 {
+            for x in 24..40 {
+                if rng.gen_bool(0.5) {
+                    soup.push((x, y));
+                }
+            }
+        }
+
+        let mut cells = vec![vec![0; 64]; 64];
+        for &(x, y) in &soup {
+            cells[y][x] = 1;
+        }
+        let cells: Vec<&[usize]> = cells.iter().map(|row| row.as_slice()).collect();
+
+        let mut board = Board::from_cells(&cells);
+        let mut infinite_board =
+            InfiniteBoard::from_alive_cells(soup.iter().map(|&(x, y)| (x as isize, y as isize)));
+
+        // The soup cannot grow by more than one cell per generation, so it
+        // never reaches the edges of `board`.
+        for _ in 0..20 {
+            board = board.tick(&Rule::CONWAY);
+            infinite_board = infinite_board.tick(&Rule::CONWAY);
+
+            let expected = InfiniteBoard::from_alive_cells(
+                board
+                    .alive_cells()
+                    .iter()
+                    .map(|&(x, y)| (x as isize, y as isize)),
+            );
+
+            assert_eq!(infinite_board, expected);
+        }
+    }
+
+    #[test]
+    fn test_set_cells() {
+        let mut board = InfiniteBoard::from_alive_cells([(0, 0), (1, 0), (2, 0)]);
+
+        board.set_cells(&[
+            CellEdit {
+                x: 0,
+                y: 0,
+                alive: false,
+            },
+            CellEdit {
+                x: -40,
+                y: 3,
+                alive: true,
+            },
+            CellEdit {
+                x: 0,
+                y: 0,
+                alive: true,
+            },
+            CellEdit {
+                x: 2,
+                y: 0,
+                alive: false,
+            },
+            CellEdit {
+                x: 5,
+                y: 5,
+                alive: false,
+            },
+        ]);
+
+        assert_eq!(
+            board,
+            InfiniteBoard::from_alive_cells([(0, 0), (1, 0), (-40, 3)])
+        );
+        assert_eq!(board.cell(1, 1).neighbour_count(), 2);
+        assert_eq!(board.cell(3, 0).neighbour_count(), 0);
+    }
+}
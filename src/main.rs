@@ -1,34 +1,216 @@
 use std::{
-    sync::Arc,
-    time::{Duration, Instant},
+    env, process,
+    sync::{
+        mpsc::{self, Sender},
+        Arc, Mutex,
+    },
+    time::Instant,
 };
 
 use board::Board;
+use cli::{BoardKind, Command, Options};
 use colored::Colorize;
 use game_context::{GameOfLifeBoard, RenderContext};
-use rand::thread_rng;
+use hashlife::HashLifeBoard;
+use infinite_board::InfiniteBoard;
+use packed_board::PackedBoard;
+use pattern::Pattern;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rule::Rule;
 use sdl2::event::Event;
+use simulation::{Control, Simulation, Snapshot};
 
+mod ages;
+mod batch;
 mod board;
+mod census;
+mod cli;
+mod clipboard;
+mod font;
 mod game_context;
+mod gif;
+mod hashlife;
+mod history;
+mod infinite_board;
+mod packed_board;
+mod pattern;
+mod period;
+mod png;
+mod recording;
+mod rule;
+mod simulation;
+mod terminal;
+mod timeline;
+mod zlib;
 
-// Optimization idea
-// Generate multiple variations of board and then combine with par iter
-// Call them `BoardPiece`s a board piece is kinda like a slice with an offset and len but for 2d space
-// BoardPieces can be combined
-// Generate many board pieces from chunks and then combine them together in parellel
+/// Evaluates `$body` with the constants `$width` and `$height` set to the
+/// board size `$size`. `PackedBoard` needs its dimensions at compile time, so
+/// only the sizes listed here can be picked at runtime.
+macro_rules! with_board_size {
+    ($size:expr, |$width:ident, $height:ident| $body:expr) => {
+        with_board_size!($size, |$width, $height| $body, [64, 128, 256, 512, 1000])
+    };
+    ($size:expr, |$width:ident, $height:ident| $body:expr, [$($n:literal),*]) => {
+        match $size {
+            $(($n, $n) => {
+                const $width: usize = $n;
+                const $height: usize = $n;
+                $body
+            })*
+            (width, height) => Err(format!(
+                "a {width}x{height} board is not supported by `--board packed`, use one of {} or a \
+                 `grid`, `infinite` or `hashlife` board",
+                [$(concat!($n, "x", $n)),*].join(", ")
+            )),
+        }
+    };
+}
 
-fn main() -> Result<(), String> {
+fn main() {
     #[cfg(debug_assertions)]
     eprintln!("{}", "WARN: you are running in debug. debug is slower and less optimized than release. to run in release do `cargo run --release`".yellow());
 
+    let options = match cli::parse(env::args().skip(1)) {
+        Ok(Command::Run(options)) => *options,
+        Ok(Command::Help) => {
+            print!("{}", cli::HELP);
+            return;
+        }
+        Err(e) => {
+            eprintln!("{}", format!("error: {e}").red());
+            eprintln!("run with `--help` to see the available options");
+            process::exit(2);
+        }
+    };
+
+    if let Err(e) = start(options) {
+        eprintln!("{}", format!("error: {e}").red());
+        process::exit(1);
+    }
+}
+
+/// Creates the board described by `options` and runs it.
+fn start(mut options: Options) -> Result<(), String> {
+    let pattern = options
+        .pattern
+        .as_deref()
+        .map(|path| pattern::load(path, &options.png))
+        .transpose()?;
+    let rule = options
+        .rule
+        .or(pattern.as_ref().and_then(|pattern| pattern.rule))
+        .unwrap_or_default();
+
+    if let Some(batch) = &options.batch {
+        return batch::run(&options, batch, rule);
+    }
+
+    // Random soups can be recreated by passing the seed back in with `--seed`.
+    let seed = *options.seed.get_or_insert_with(rand::random);
+    if pattern.is_none() {
+        println!("Seed: {seed}");
+    }
+    let mut rng = StdRng::seed_from_u64(seed);
+    let density = options.density.unwrap_or(0.5);
+
+    let fits = |e: pattern::PatternError| match &options.pattern {
+        Some(path) => format!("{}: {e}", path.display()),
+        None => e.to_string(),
+    };
+
+    match options.board {
+        BoardKind::Grid => {
+            let (width, height) = options.size;
+            let board = match &pattern {
+                Some(pattern) => {
+                    Board::from_pattern(pattern, width, height, options.anchor).map_err(fits)?
+                }
+                None => Board::rand(width, height, &mut rng, density),
+            };
+            let board = board.with_topology(options.topology);
+
+            if options.ages.is_some() {
+                run(board.with_ages(), rule, &options)
+            } else {
+                run(board, rule, &options)
+            }
+        }
+        BoardKind::Packed => with_board_size!(options.size, |WIDTH, HEIGHT| {
+            let board = match &pattern {
+                Some(pattern) => {
+                    PackedBoard::<WIDTH, HEIGHT>::from_pattern(pattern, options.anchor)
+                        .map_err(fits)?
+                }
+                None => PackedBoard::rand(&mut rng, density),
+            };
+
+            run(board.with_topology(options.topology), rule, &options)
+        }),
+        BoardKind::Infinite => {
+            let cells = initial_cells(pattern.as_ref(), &mut rng, options.size, density);
+            run(InfiniteBoard::from_alive_cells(cells), rule, &options)
+        }
+        BoardKind::HashLife => {
+            let cells = initial_cells(pattern.as_ref(), &mut rng, options.size, density);
+            run(HashLifeBoard::from_alive_cells(cells), rule, &options)
+        }
+    }
+}
+
+/// The live cells of `pattern`, or of a random soup, centered on the origin
+/// of an unbounded board.
+fn initial_cells(
+    pattern: Option<&Pattern>,
+    rng: &mut impl Rng,
+    (width, height): (usize, usize),
+    density: f64,
+) -> Vec<(isize, isize)> {
+    let (cells, width, height) = match pattern {
+        Some(pattern) => (pattern.cells.clone(), pattern.width, pattern.height),
+        None => {
+            let cells = (0..height)
+                .flat_map(|y| (0..width).map(move |x| (x, y)))
+                .filter(|_| rng.gen_bool(density))
+                .collect();
+
+            (cells, width, height)
+        }
+    };
+
+    let (offset_x, offset_y) = (width as isize / 2, height as isize / 2);
+
+    cells
+        .into_iter()
+        .map(|(x, y)| (x as isize - offset_x, y as isize - offset_y))
+        .collect()
+}
+
+/// Opens the viewer and simulates `board`, advancing it by
+/// `2^options.step_log2` generations every update.
+fn run<B: GameOfLifeBoard + Send + 'static>(
+    board: B,
+    rule: Rule,
+    options: &Options,
+) -> Result<(), String> {
+    if options.gif.frames.is_some() {
+        return recording::run(board, rule, options);
+    }
+
+    if options.terminal {
+        return terminal::run(board, rule, options);
+    }
+
     // Initialize SDL2
 
     let sdl_context = sdl2::init()?;
     let video_subsystem = sdl_context.video()?;
 
     let mut canvas = video_subsystem
-        .window("The Game of Life - Rust", 480 * 2, 480 * 2)
+        .window(
+            "The Game of Life - Rust",
+            options.window_size.0,
+            options.window_size.1,
+        )
         .resizable()
         .build()
         .expect("could not initialize video subsystem")
@@ -38,40 +220,17 @@ fn main() -> Result<(), String> {
 
     // Initialize Game
 
-    let mut board = Board::<1000, 1000>::rand(&mut thread_rng(), 0.5);
-    let draw_board = Arc::new(std::sync::Mutex::new(board.clone()));
-    let draw_board_ref = draw_board.clone();
-
-    std::thread::spawn(move || {
-        let mut last_update = Instant::now();
-
-        loop {
-            let old_board = board;
-            let before = Instant::now();
-            board = old_board.tick();
-            println!(
-                "Generated {:?} tiles in {:?}",
-                old_board.alive_cells().len(),
-                Instant::now() - before
-            );
-
-            let mut cached_board = draw_board_ref.lock().unwrap();
-            *cached_board = old_board;
-            drop(cached_board);
-
-            let delta = Instant::now() - last_update;
-            last_update = Instant::now();
-
-            // Cap framerate to 16ms or 60fps
-            let frame_time = Duration::from_nanos(16000000);
-            let excess = frame_time.checked_sub(delta);
-            if let Some(excess) = excess {
-                ::std::thread::sleep(excess)
-            }
-        }
-    });
+    let (controls, snapshot) = spawn_simulation(board, rule, options);
 
-    let mut game_context = RenderContext::new();
+    let mut game_context = RenderContext::new(controls, options.speed(), options.paused);
+    if options.pattern.is_none() {
+        game_context = game_context.with_seed(options.seed);
+    }
+    game_context = game_context
+        .with_gif(options.gif.clone())
+        .with_png(options.png.clone())
+        .with_save(options.save.clone(), rule)
+        .with_ages(options.ages.clone());
     let mut event_pump = sdl_context.event_pump()?;
     let mut last_update: Instant = Instant::now();
 
@@ -81,25 +240,52 @@ fn main() -> Result<(), String> {
         let delta = Instant::now() - last_update;
         last_update = Instant::now();
 
+        let mut shown = snapshot.lock().unwrap();
+
         for event in event_pump.poll_iter() {
             if let Event::Quit { .. } = event {
                 break 'main;
             }
 
-            game_context.handle_event(event);
+            game_context.handle_event(event, &*shown, &canvas);
         }
 
+        // Shows the edits straight away instead of waiting for the
+        // simulation thread to send the board back.
+        game_context.flush_edits(&mut shown.board);
+        drop(shown);
+
         game_context = game_context.tick(delta);
 
         canvas.set_draw_color(game_context.background_color());
         canvas.clear();
 
-        let board = draw_board.lock().unwrap();
-        game_context.draw(&*board, &mut canvas);
-        drop(board);
+        let shown = snapshot.lock().unwrap();
+        game_context.draw(&*shown, &mut canvas);
+        game_context.record(&*shown);
+        drop(shown);
 
         canvas.present();
     }
 
+    game_context.stop_recording();
+
     Ok(())
 }
+
+/// Starts simulating `board` on its own thread, returning the channel
+/// controlling it and the latest state it shared.
+fn spawn_simulation<B: GameOfLifeBoard + Send + 'static>(
+    board: B,
+    rule: Rule,
+    options: &Options,
+) -> (Sender<Control>, Arc<Mutex<Snapshot<B>>>) {
+    let (controls, control_receiver) = mpsc::channel();
+
+    let simulation = Simulation::new(board, rule, options.speed(), options.paused);
+    let snapshot = Arc::new(Mutex::new(simulation.snapshot()));
+    let snapshot_ref = snapshot.clone();
+    std::thread::spawn(move || simulation.run(control_receiver, snapshot_ref));
+
+    (controls, snapshot)
+}
@@ -0,0 +1,501 @@
+use std::fmt::{self, Write};
+
+use rand::Rng;
+
+use crate::{
+    board::{Anchor, Board, Topology},
+    game_context::{CellEdit, GameOfLifeBoard},
+    pattern::{Pattern, PatternError},
+    rule::Rule,
+};
+
+/// A board storing a single bit per cell, packed 64 cells to a word.
+///
+/// Instead of counting neighbours cell by cell, [`PackedBoard::tick`] adds up
+/// the eight neighbours of 64 cells at once with bitwise adders, which is
+/// much faster than [`Board`] for dense patterns such as random soups.
+#[derive(Clone, PartialEq, Eq)]
+pub struct PackedBoard<const WIDTH: usize, const HEIGHT: usize> {
+    /// `HEIGHT` rows of `Self::WORDS` words each. Bit `i` of word `j` in a
+    /// row is the cell at `x = j * 64 + i`, bits past `WIDTH` are always 0.
+    words: Vec<u64>,
+    topology: Topology,
+}
+
+impl<const WIDTH: usize, const HEIGHT: usize> PackedBoard<WIDTH, HEIGHT> {
+    const WORDS: usize = WIDTH.div_ceil(64);
+
+    pub fn new() -> Self {
+        Self {
+            words: vec![0; Self::WORDS * HEIGHT],
+            topology: Topology::default(),
+        }
+    }
+
+    #[cfg(test)]
+    pub fn from_cells(cells: &[&[usize]]) -> Self {
+        let mut board = Self::new();
+
+        for (y, row) in cells.iter().enumerate() {
+            for (x, cell) in row.iter().enumerate() {
+                board.set(x, y, *cell != 0);
+            }
+        }
+
+        board
+    }
+
+    /// Creates a board with `pattern` placed at `anchor`.
+    pub fn from_pattern(pattern: &Pattern, anchor: Anchor) -> Result<Self, PatternError> {
+        Board::from_pattern(pattern, WIDTH, HEIGHT, anchor).map(|board| Self::from_board(&board))
+    }
+
+    /// Packs `board`, which must be `WIDTH` by `HEIGHT`.
+    pub fn from_board(board: &Board) -> Self {
+        assert_eq!(
+            (board.width(), board.height()),
+            (WIDTH, HEIGHT),
+            "a board packs into a board of the same size"
+        );
+
+        let mut packed = Self::new().with_topology(board.topology());
+
+        for &(x, y) in board.alive_cells() {
+            packed.set(x, y, true);
+        }
+
+        packed
+    }
+
+    pub fn rand(rng: &mut impl Rng, p: f64) -> Self {
+        let mut board = Self::new();
+
+        for y in 0..HEIGHT {
+            for x in 0..WIDTH {
+                if rng.gen_bool(p) {
+                    board.set(x, y, true);
+                }
+            }
+        }
+
+        board
+    }
+
+    pub fn with_topology(mut self, topology: Topology) -> Self {
+        self.topology = topology;
+        self
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> bool {
+        self.words[y * Self::WORDS + x / 64] & (1 << (x % 64)) != 0
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, is_alive: bool) {
+        assert!(
+            x < WIDTH && y < HEIGHT,
+            "({x}, {y}) is outside of the board"
+        );
+
+        let word = &mut self.words[y * Self::WORDS + x / 64];
+
+        if is_alive {
+            *word |= 1 << (x % 64);
+        } else {
+            *word &= !(1 << (x % 64));
+        }
+    }
+
+    /// Sets the state of every cell in `edits`, ignoring cells outside of
+    /// the board.
+    pub fn set_cells(&mut self, edits: &[CellEdit]) {
+        for edit in edits {
+            if let (Ok(x), Ok(y)) = (usize::try_from(edit.x), usize::try_from(edit.y)) {
+                if x < WIDTH && y < HEIGHT {
+                    self.set(x, y, edit.alive);
+                }
+            }
+        }
+    }
+
+    pub fn population(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    pub fn alive_cells(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.words
+            .chunks(Self::WORDS.max(1))
+            .enumerate()
+            .flat_map(|(y, row)| {
+                row.iter().enumerate().flat_map(move |(i, &word)| {
+                    SetBits(word).map(move |bit| (i * 64 + bit as usize, y))
+                })
+            })
+    }
+
+    pub fn tick(&self, rule: &Rule) -> Self {
+        let mut board = Self::new().with_topology(self.topology);
+
+        if Self::WORDS == 0 {
+            return board;
+        }
+
+        let birth: Vec<u8> = rule.birth_counts().collect();
+        let survival: Vec<u8> = rule.survival_counts().collect();
+        let empty_row = vec![0; Self::WORDS];
+
+        for y in 0..HEIGHT {
+            let above = self.row_at(y as isize - 1).unwrap_or(&empty_row);
+            let row = self.row(y);
+            let below = self.row_at(y as isize + 1).unwrap_or(&empty_row);
+
+            for i in 0..Self::WORDS {
+                let (above_west, above_east) = self.shifted(above, i);
+                let (west, east) = self.shifted(row, i);
+                let (below_west, below_east) = self.shifted(below, i);
+
+                let count = NeighbourCount::add([
+                    above_west, above[i], above_east, west, east, below_west, below[i], below_east,
+                ]);
+
+                let alive = row[i];
+                let next = (alive & count.matches(&survival)) | (!alive & count.matches(&birth));
+
+                board.words[y * Self::WORDS + i] = next & Self::word_mask(i);
+            }
+        }
+
+        board
+    }
+
+    fn row(&self, y: usize) -> &[u64] {
+        &self.words[y * Self::WORDS..(y + 1) * Self::WORDS]
+    }
+
+    /// Returns the row at `y` taking the topology into account, or `None` if
+    /// it is off the board.
+    fn row_at(&self, y: isize) -> Option<&[u64]> {
+        match self.topology {
+            Topology::Bounded if y < 0 || y >= HEIGHT as isize => None,
+            Topology::Bounded => Some(self.row(y as usize)),
+            Topology::Torus => Some(self.row(y.rem_euclid(HEIGHT as isize) as usize)),
+        }
+    }
+
+    /// Returns word `i` of `row` shifted so every bit lines up with its west
+    /// and east neighbour respectively.
+    fn shifted(&self, row: &[u64], i: usize) -> (u64, u64) {
+        let last = Self::WORDS - 1;
+        let last_bit = (WIDTH - 1) % 64;
+
+        let west_carry = match (i, self.topology) {
+            (0, Topology::Bounded) => 0,
+            (0, Topology::Torus) => (row[last] >> last_bit) & 1,
+            _ => row[i - 1] >> 63,
+        };
+
+        let east_carry = match (i == last, self.topology) {
+            (true, Topology::Bounded) => 0,
+            (true, Topology::Torus) => (row[0] & 1) << last_bit,
+            _ => row[i + 1] << 63,
+        };
+
+        ((row[i] << 1) | west_carry, (row[i] >> 1) | east_carry)
+    }
+
+    /// The bits of word `i` which are on the board.
+    fn word_mask(i: usize) -> u64 {
+        match WIDTH - i * 64 {
+            bits @ 0..64 => (1 << bits) - 1,
+            _ => u64::MAX,
+        }
+    }
+}
+
+impl<const WIDTH: usize, const HEIGHT: usize> Default for PackedBoard<WIDTH, HEIGHT> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const WIDTH: usize, const HEIGHT: usize> GameOfLifeBoard for PackedBoard<WIDTH, HEIGHT> {
+    fn alive_cells(&self) -> impl Iterator<Item = (isize, isize)> + '_ {
+        self.alive_cells().map(|(x, y)| (x as isize, y as isize))
+    }
+
+    fn tick(&self, rule: &Rule) -> Self {
+        self.tick(rule)
+    }
+
+    fn size(&self) -> Option<(usize, usize)> {
+        Some((WIDTH, HEIGHT))
+    }
+
+    fn is_alive(&self, x: isize, y: isize) -> bool {
+        match (usize::try_from(x), usize::try_from(y)) {
+            (Ok(x), Ok(y)) if x < WIDTH && y < HEIGHT => self.get(x, y),
+            _ => false,
+        }
+    }
+
+    fn set_cells(&mut self, edits: &[CellEdit]) {
+        self.set_cells(edits)
+    }
+
+    fn population(&self) -> usize {
+        self.population()
+    }
+}
+
+impl<const WIDTH: usize, const HEIGHT: usize> fmt::Debug for PackedBoard<WIDTH, HEIGHT> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for y in 0..HEIGHT {
+            f.write_char('\n')?;
+
+            for x in 0..WIDTH {
+                f.write_char(if self.get(x, y) { 'O' } else { '.' })?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The neighbour counts of 64 cells as four bit planes, bit `i` of `bits[k]`
+/// is bit `k` of the count for cell `i`.
+struct NeighbourCount {
+    bits: [u64; 4],
+}
+
+impl NeighbourCount {
+    /// Adds up eight neighbour words with a tree of full and half adders.
+    fn add(neighbours: [u64; 8]) -> Self {
+        let [a, b, c, d, e, f, g, h] = neighbours;
+
+        let (ones_abc, twos_abc) = full_add(a, b, c);
+        let (ones_def, twos_def) = full_add(d, e, f);
+        let (ones_gh, twos_gh) = half_add(g, h);
+
+        let (ones, twos_ones) = full_add(ones_abc, ones_def, ones_gh);
+        let (twos, fours_abc) = full_add(twos_abc, twos_def, twos_gh);
+        let (twos, fours_twos) = half_add(twos, twos_ones);
+        let (fours, eights) = half_add(fours_abc, fours_twos);
+
+        Self {
+            bits: [ones, twos, fours, eights],
+        }
+    }
+
+    /// Returns the cells whose count is one of `counts`.
+    fn matches(&self, counts: &[u8]) -> u64 {
+        counts.iter().fold(0, |matches, &count| {
+            matches
+                | self
+                    .bits
+                    .iter()
+                    .enumerate()
+                    .fold(u64::MAX, |equal, (k, &bit)| {
+                        equal & if count & (1 << k) != 0 { bit } else { !bit }
+                    })
+        })
+    }
+}
+
+fn full_add(a: u64, b: u64, c: u64) -> (u64, u64) {
+    let partial = a ^ b;
+    (partial ^ c, (a & b) | (partial & c))
+}
+
+fn half_add(a: u64, b: u64) -> (u64, u64) {
+    (a ^ b, a & b)
+}
+
+/// Iterates over the positions of the set bits in a word.
+struct SetBits(u64);
+
+impl Iterator for SetBits {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.0 == 0 {
+            return None;
+        }
+
+        let bit = self.0.trailing_zeros();
+        self.0 &= self.0 - 1;
+        Some(bit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use rand::{rngs::StdRng, thread_rng, SeedableRng};
+
+    use super::*;
+
+    fn random_pair<const W: usize, const H: usize>(
+        rng: &mut impl Rng,
+        p: f64,
+        topology: Topology,
+    ) -> (Board, PackedBoard<W, H>) {
+        let mut cells = vec![vec![0; W]; H];
+
+        for row in cells.iter_mut() {
+            for cell in row.iter_mut() {
+                *cell = rng.gen_bool(p) as usize;
+            }
+        }
+
+        let cells: Vec<&[usize]> = cells.iter().map(|row| row.as_slice()).collect();
+        let board = Board::from_cells(&cells).with_topology(topology);
+        let packed = PackedBoard::from_cells(&cells).with_topology(topology);
+
+        (board, packed)
+    }
+
+    fn assert_same<const W: usize, const H: usize>(board: &Board, packed: &PackedBoard<W, H>) {
+        let mut expected = board.alive_cells().clone();
+        let mut actual: Vec<_> = packed.alive_cells().collect();
+        expected.sort_unstable();
+        actual.sort_unstable();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_neighbour_count() {
+        for n in 0..=255u8 {
+            let neighbours = std::array::from_fn(|k| ((n >> k) & 1) as u64 * u64::MAX);
+            let count = NeighbourCount::add(neighbours);
+
+            for (k, bit) in count.bits.into_iter().enumerate() {
+                assert_eq!(bit != 0, (n.count_ones() >> k) & 1 != 0, "{n:08b}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_blinker() {
+        let board = PackedBoard::<3, 3>::from_cells(&[&[0, 1, 0], &[0, 1, 0], &[0, 1, 0]]);
+        let expected = PackedBoard::from_cells(&[&[0, 0, 0], &[1, 1, 1], &[0, 0, 0]]);
+
+        assert_eq!(board.tick(&Rule::CONWAY), expected);
+        assert_eq!(board.tick(&Rule::CONWAY).tick(&Rule::CONWAY), board);
+    }
+
+    #[test]
+    fn test_matches_board() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let rules: [Rule; 3] = [
+            Rule::CONWAY,
+            "B36/S23".parse().unwrap(),
+            "B2/S".parse().unwrap(),
+        ];
+
+        for topology in [Topology::Bounded, Topology::Torus] {
+            for rule in &rules {
+                // Sizes on either side of a word boundary.
+                let (mut board, mut packed) = random_pair::<130, 17>(&mut rng, 0.5, topology);
+                let (mut small_board, mut small_packed) =
+                    random_pair::<64, 5>(&mut rng, 0.5, topology);
+
+                for _ in 0..32 {
+                    board = board.tick(rule);
+                    packed = packed.tick(rule);
+                    assert_same(&board, &packed);
+
+                    small_board = small_board.tick(rule);
+                    small_packed = small_packed.tick(rule);
+                    assert_same(&small_board, &small_packed);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_from_board() {
+        let board = Board::rand(70, 3, &mut thread_rng(), 0.5);
+        let packed = PackedBoard::<70, 3>::from_board(&board);
+
+        assert_same(&board, &packed);
+        assert_eq!(packed.population(), board.alive_cells().len());
+    }
+
+    #[test]
+    fn test_set_cells() {
+        let mut board = PackedBoard::<70, 3>::new();
+
+        board.set_cells(&[
+            CellEdit {
+                x: 65,
+                y: 1,
+                alive: true,
+            },
+            CellEdit {
+                x: 0,
+                y: 2,
+                alive: true,
+            },
+            CellEdit {
+                x: 70,
+                y: 0,
+                alive: true,
+            },
+            CellEdit {
+                x: -1,
+                y: 0,
+                alive: true,
+            },
+            CellEdit {
+                x: 0,
+                y: 2,
+                alive: false,
+            },
+        ]);
+
+        assert_eq!(board.alive_cells().collect::<Vec<_>>(), &[(65, 1)]);
+    }
+
+    /// Run with `cargo test --release -- --ignored --nocapture bench`.
+    #[test]
+    #[ignore]
+    fn bench_dense_soup() {
+        const GENERATIONS: u32 = 100;
+
+        let mut board = Board::rand(1000, 1000, &mut thread_rng(), 0.5);
+        let mut packed = PackedBoard::<1000, 1000>::from_board(&board);
+
+        let before = Instant::now();
+        for _ in 0..GENERATIONS {
+            board = board.tick(&Rule::CONWAY);
+        }
+        let board_time = before.elapsed();
+
+        let before = Instant::now();
+        for _ in 0..GENERATIONS {
+            packed = packed.tick(&Rule::CONWAY);
+        }
+        let packed_time = before.elapsed();
+
+        assert_same(&board, &packed);
+
+        println!("Board: {:?} per generation", board_time / GENERATIONS);
+        println!(
+            "PackedBoard: {:?} per generation ({:.1}x faster)",
+            packed_time / GENERATIONS,
+            board_time.as_secs_f64() / packed_time.as_secs_f64()
+        );
+    }
+
+    #[test]
+    fn test_rand_is_seeded() {
+        let rand = |seed| PackedBoard::<100, 60>::rand(&mut StdRng::seed_from_u64(seed), 0.3);
+
+        assert_eq!(rand(7), rand(7));
+        assert_ne!(rand(7), rand(8));
+    }
+}
@@ -0,0 +1,196 @@
+//! A catalog of well known patterns built into the binary.
+
+use std::fmt;
+
+use super::{rle, Pattern};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Category {
+    StillLife,
+    Oscillator,
+    Spaceship,
+    Gun,
+    Methuselah,
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::StillLife => "still life",
+            Self::Oscillator => "oscillator",
+            Self::Spaceship => "spaceship",
+            Self::Gun => "gun",
+            Self::Methuselah => "methuselah",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Entry {
+    pub name: &'static str,
+    pub category: Category,
+    rle: &'static str,
+}
+
+impl Entry {
+    const fn new(name: &'static str, category: Category, rle: &'static str) -> Self {
+        Self {
+            name,
+            category,
+            rle,
+        }
+    }
+
+    pub fn pattern(&self) -> Pattern {
+        let mut pattern = rle::parse(self.rle).expect("library patterns are valid RLE");
+        pattern.name = Some(self.name.to_string());
+        pattern
+    }
+}
+
+/// Every pattern in the library, grouped by category.
+pub const ENTRIES: &[Entry] = &[
+    Entry::new("Block", Category::StillLife, "x = 2, y = 2\n2o$2o!"),
+    Entry::new(
+        "Beehive",
+        Category::StillLife,
+        "x = 4, y = 3\nb2o$o2bo$b2o!",
+    ),
+    Entry::new(
+        "Loaf",
+        Category::StillLife,
+        "x = 4, y = 4\nb2o$o2bo$bobo$2bo!",
+    ),
+    Entry::new("Boat", Category::StillLife, "x = 3, y = 3\n2o$obo$bo!"),
+    Entry::new("Tub", Category::StillLife, "x = 3, y = 3\nbo$obo$bo!"),
+    Entry::new("Blinker", Category::Oscillator, "x = 3, y = 1\n3o!"),
+    Entry::new("Toad", Category::Oscillator, "x = 4, y = 2\nb3o$3o!"),
+    Entry::new(
+        "Beacon",
+        Category::Oscillator,
+        "x = 4, y = 4\n2o$2o$2b2o$2b2o!",
+    ),
+    Entry::new(
+        "Pulsar",
+        Category::Oscillator,
+        "x = 13, y = 13\n\
+         2b3o3b3o2$o4bobo4bo$o4bobo4bo$o4bobo4bo$2b3o3b3o2$2b3o3b3o$\
+         o4bobo4bo$o4bobo4bo$o4bobo4bo2$2b3o3b3o!",
+    ),
+    Entry::new(
+        "Pentadecathlon",
+        Category::Oscillator,
+        "x = 10, y = 3\n2bo4bo$2ob4ob2o$2bo4bo!",
+    ),
+    Entry::new("Glider", Category::Spaceship, "x = 3, y = 3\nbo$2bo$3o!"),
+    Entry::new(
+        "Lightweight spaceship",
+        Category::Spaceship,
+        "x = 5, y = 4\nbo2bo$o$o3bo$4o!",
+    ),
+    Entry::new(
+        "Middleweight spaceship",
+        Category::Spaceship,
+        "x = 6, y = 5\n3bo$bo3bo$o$o4bo$5o!",
+    ),
+    Entry::new(
+        "Heavyweight spaceship",
+        Category::Spaceship,
+        "x = 7, y = 5\n3b2o$bo4bo$o$o5bo$6o!",
+    ),
+    Entry::new(
+        "Gosper glider gun",
+        Category::Gun,
+        "x = 36, y = 9\n\
+         24bo$22bobo$12b2o6b2o12b2o$11bo3bo4b2o12b2o$2o8bo5bo3b2o$\
+         2o8bo3bob2o4bobo$10bo5bo7bo$11bo3bo$12b2o!",
+    ),
+    Entry::new(
+        "Simkin glider gun",
+        Category::Gun,
+        "x = 33, y = 21\n\
+         2o5b2o$2o5b2o2$4b2o$4b2o5$22b2ob2o$21bo5bo$21bo6bo2b2o$\
+         21b3o3bo3b2o$26bo4$20b2o$20bo$21b3o$23bo!",
+    ),
+    Entry::new(
+        "R-pentomino",
+        Category::Methuselah,
+        "x = 3, y = 3\nb2o$2o$bo!",
+    ),
+    Entry::new(
+        "Diehard",
+        Category::Methuselah,
+        "x = 8, y = 3\n6bo$2o$bo3b3o!",
+    ),
+    Entry::new(
+        "Acorn",
+        Category::Methuselah,
+        "x = 7, y = 3\nbo$3bo$2o2b3o!",
+    ),
+    Entry::new(
+        "Pi-heptomino",
+        Category::Methuselah,
+        "x = 3, y = 3\n3o$obo$obo!",
+    ),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{game_context::GameOfLifeBoard, infinite_board::InfiniteBoard, rule::Rule};
+
+    fn board(name: &str) -> InfiniteBoard {
+        let entry = ENTRIES.iter().find(|entry| entry.name == name).unwrap();
+        InfiniteBoard::from_alive_cells(entry.pattern().alive_cells())
+    }
+
+    #[test]
+    fn test_entries_parse() {
+        for entry in ENTRIES {
+            let pattern = entry.pattern();
+
+            assert!(!pattern.cells.is_empty(), "{}", entry.name);
+            assert!(
+                pattern
+                    .cells
+                    .iter()
+                    .all(|&(x, y)| x < pattern.width && y < pattern.height),
+                "{}",
+                entry.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_still_lifes_are_still() {
+        for entry in ENTRIES {
+            if entry.category == Category::StillLife {
+                let board = board(entry.name);
+                assert_eq!(board.tick(&Rule::CONWAY), board, "{}", entry.name);
+            }
+        }
+    }
+
+    #[test]
+    fn test_guns_fire_gliders() {
+        for (name, period) in [("Gosper glider gun", 30), ("Simkin glider gun", 120)] {
+            let board = board(name);
+            let later = board.step(&Rule::CONWAY, period * 4);
+
+            // Each glider adds 5 cells to the population.
+            assert_eq!(
+                later.population(),
+                board.step(&Rule::CONWAY, period * 3).population() + 5,
+                "{name}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_diehard_dies() {
+        let board = board("Diehard");
+
+        assert_ne!(board.step(&Rule::CONWAY, 129).population(), 0);
+        assert_eq!(board.step(&Rule::CONWAY, 130).population(), 0);
+    }
+}
@@ -0,0 +1,188 @@
+//! The Life 1.06 format, a list of the coordinates of every live cell.
+//!
+//! ```text
+//! #Life 1.06
+//! 0 -1
+//! 1 0
+//! -1 1
+//! 0 1
+//! 1 1
+//! ```
+
+use std::fmt::Write;
+
+use super::{Pattern, PatternError};
+
+pub const HEADER: &str = "#Life 1.06";
+
+pub fn parse(input: &str) -> Result<Pattern, PatternError> {
+    let mut lines = input.lines().enumerate().map(|(i, line)| (i + 1, line));
+
+    match lines.next() {
+        Some((_, line)) if line.trim_end() == HEADER => (),
+        _ => {
+            return Err(PatternError::syntax(
+                1,
+                1,
+                format!("expected a `{HEADER}` header"),
+            ))
+        }
+    }
+
+    let mut cells = Vec::new();
+
+    for (number, line) in lines {
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let mut coordinates = line
+            .split_whitespace()
+            .map(|token| (token, token.as_ptr() as usize - line.as_ptr() as usize + 1));
+
+        let (Some(x), Some(y), None) = (coordinates.next(), coordinates.next(), coordinates.next())
+        else {
+            return Err(PatternError::syntax(
+                number,
+                1,
+                format!("expected two coordinates `x y`, found `{}`", line.trim()),
+            ));
+        };
+
+        cells.push((parse_coordinate(number, x)?, parse_coordinate(number, y)?));
+    }
+
+    let Some(&(first_x, first_y)) = cells.first() else {
+        return Ok(Pattern::default());
+    };
+
+    let (min_x, min_y, max_x, max_y) = cells.iter().fold(
+        (first_x, first_y, first_x, first_y),
+        |(min_x, min_y, max_x, max_y), &(x, y)| {
+            (min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y))
+        },
+    );
+
+    let (Some(width), Some(height)) = (
+        max_x.checked_sub(min_x).and_then(|w| w.checked_add(1)),
+        max_y.checked_sub(min_y).and_then(|h| h.checked_add(1)),
+    ) else {
+        return Err(PatternError::syntax(
+            1,
+            1,
+            "the live cells are too far apart to fit in a pattern",
+        ));
+    };
+
+    let mut cells: Vec<(usize, usize)> = cells
+        .into_iter()
+        .map(|(x, y)| ((x - min_x) as usize, (y - min_y) as usize))
+        .collect();
+    cells.sort_unstable_by_key(|&(x, y)| (y, x));
+    cells.dedup();
+
+    Ok(Pattern {
+        width: width as usize,
+        height: height as usize,
+        cells,
+        ..Pattern::default()
+    })
+}
+
+fn parse_coordinate(number: usize, (token, column): (&str, usize)) -> Result<isize, PatternError> {
+    token.parse().map_err(|_| {
+        let message = if token.bytes().all(|b| b.is_ascii_digit() || b == b'-') {
+            format!("coordinate `{token}` is out of range")
+        } else {
+            format!("`{token}` is not a valid coordinate")
+        };
+
+        PatternError::syntax(number, column, message)
+    })
+}
+
+pub fn write(pattern: &Pattern) -> String {
+    let mut out = format!("{HEADER}\n");
+
+    for &(x, y) in &pattern.cells {
+        let _ = writeln!(out, "{x} {y}");
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let pattern = parse("#Life 1.06\n0 -1\n1 0\n-1 1\n0 1\n1 1\n").unwrap();
+
+        assert_eq!((pattern.width, pattern.height), (3, 3));
+        assert_eq!(pattern.cells, &[(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse("0 0\n"),
+            Err(PatternError::syntax(1, 1, "expected a `#Life 1.06` header"))
+        );
+
+        assert_eq!(
+            parse("#Life 1.06\n0 0\n1\n"),
+            Err(PatternError::syntax(
+                3,
+                1,
+                "expected two coordinates `x y`, found `1`"
+            ))
+        );
+
+        assert_eq!(
+            parse("#Life 1.06\n0 0 0\n"),
+            Err(PatternError::syntax(
+                2,
+                1,
+                "expected two coordinates `x y`, found `0 0 0`"
+            ))
+        );
+
+        assert_eq!(
+            parse("#Life 1.06\n0  x1\n"),
+            Err(PatternError::syntax(2, 4, "`x1` is not a valid coordinate"))
+        );
+
+        assert_eq!(
+            parse("#Life 1.06\n99999999999999999999 0\n"),
+            Err(PatternError::syntax(
+                2,
+                1,
+                "coordinate `99999999999999999999` is out of range"
+            ))
+        );
+
+        assert_eq!(
+            parse(&format!("#Life 1.06\n{} 0\n{} 0\n", isize::MIN, isize::MAX)),
+            Err(PatternError::syntax(
+                1,
+                1,
+                "the live cells are too far apart to fit in a pattern"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let pattern = Pattern {
+            width: 3,
+            height: 3,
+            cells: vec![(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)],
+            ..Pattern::default()
+        };
+
+        let life = write(&pattern);
+        assert_eq!(life, "#Life 1.06\n1 0\n2 1\n0 2\n1 2\n2 2\n");
+        assert_eq!(parse(&life), Ok(pattern));
+    }
+}
@@ -0,0 +1,278 @@
+use std::{error::Error, fmt, fs, path::Path};
+
+use crate::{game_context::GameOfLifeBoard, rule::Rule};
+
+pub mod library;
+pub mod life106;
+pub mod plaintext;
+pub mod png;
+pub mod rle;
+
+/// A pattern file format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Rle,
+    Plaintext,
+    Life106,
+}
+
+impl Format {
+    /// Guesses the format of a pattern file from its contents.
+    pub fn detect(input: &str) -> Option<Self> {
+        let line = input.lines().map(str::trim).find(|line| !line.is_empty())?;
+
+        if line.starts_with(life106::HEADER) {
+            Some(Self::Life106)
+        } else if line.starts_with('#')
+            || line.starts_with('x') && line[1..].trim_start().starts_with('=')
+        {
+            Some(Self::Rle)
+        } else if line.starts_with('!') || line.chars().all(|c| matches!(c, '.' | 'O' | '*')) {
+            Some(Self::Plaintext)
+        } else {
+            None
+        }
+    }
+
+    /// Picks the format for a file name based on its extension.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+
+        match extension.as_str() {
+            "rle" => Some(Self::Rle),
+            "cells" => Some(Self::Plaintext),
+            "lif" | "life" => Some(Self::Life106),
+            _ => None,
+        }
+    }
+}
+
+/// Parses a pattern in any of the supported formats.
+pub fn parse(input: &str) -> Result<Pattern, PatternError> {
+    match Format::detect(input) {
+        Some(Format::Rle) => rle::parse(input),
+        Some(Format::Plaintext) => plaintext::parse(input),
+        Some(Format::Life106) => life106::parse(input),
+        None => Err(PatternError::syntax(
+            1,
+            1,
+            "unrecognised pattern format, expected RLE, plaintext or Life 1.06",
+        )),
+    }
+}
+
+pub fn write(pattern: &Pattern, format: Format) -> String {
+    match format {
+        Format::Rle => rle::write(pattern),
+        Format::Plaintext => plaintext::write(pattern),
+        Format::Life106 => life106::write(pattern),
+    }
+}
+
+/// Loads a pattern file, or a PNG image read with the colors of `png`.
+pub fn load(path: &Path, png: &png::PngOptions) -> Result<Pattern, String> {
+    let data = fs::read(path).map_err(|e| format!("could not read `{}`: {e}", path.display()))?;
+
+    if png::is_png(&data) {
+        return png::parse(&data, png).map_err(|e| format!("{}: {e}", path.display()));
+    }
+
+    let input =
+        String::from_utf8(data).map_err(|e| format!("could not read `{}`: {e}", path.display()))?;
+
+    parse(&input).map_err(|e| format!("{}: {e}", path.display()))
+}
+
+/// Saves a pattern, in the format matching the extension of `path`.
+pub fn save(path: &Path, pattern: &Pattern) -> Result<(), String> {
+    let format = Format::from_path(path).ok_or_else(|| {
+        format!(
+            "don't know which format to save `{}` in, use a `.rle`, `.cells` or `.lif` extension",
+            path.display()
+        )
+    })?;
+
+    fs::write(path, write(pattern, format))
+        .map_err(|e| format!("could not write `{}`: {e}", path.display()))
+}
+
+/// A pattern read from or written to a pattern file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Pattern {
+    pub width: usize,
+    pub height: usize,
+    /// The live cells, relative to the top left corner of the pattern.
+    pub cells: Vec<(usize, usize)>,
+    pub rule: Option<Rule>,
+    pub name: Option<String>,
+    pub comments: Vec<String>,
+}
+
+impl Pattern {
+    /// Creates a pattern from a board. Boards with a size keep it so the
+    /// pattern can be loaded back exactly where it was, unbounded boards are
+    /// cropped to their live cells.
+    pub fn from_board<B: GameOfLifeBoard>(board: &B) -> Self {
+        let cells: Vec<(isize, isize)> = board.alive_cells().collect();
+
+        let (min_x, min_y, width, height) = match board.size() {
+            Some((width, height)) => (0, 0, width, height),
+            None if cells.is_empty() => (0, 0, 0, 0),
+            None => {
+                let (min_x, min_y, max_x, max_y) = cells.iter().fold(
+                    (isize::MAX, isize::MAX, isize::MIN, isize::MIN),
+                    |(min_x, min_y, max_x, max_y), &(x, y)| {
+                        (min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y))
+                    },
+                );
+
+                (
+                    min_x,
+                    min_y,
+                    (max_x - min_x) as usize + 1,
+                    (max_y - min_y) as usize + 1,
+                )
+            }
+        };
+
+        let mut cells: Vec<(usize, usize)> = cells
+            .into_iter()
+            .map(|(x, y)| ((x - min_x) as usize, (y - min_y) as usize))
+            .collect();
+        cells.sort_unstable_by_key(|&(x, y)| (y, x));
+
+        Self {
+            width,
+            height,
+            cells,
+            ..Self::default()
+        }
+    }
+
+    pub fn alive_cells(&self) -> impl Iterator<Item = (isize, isize)> + '_ {
+        self.cells.iter().map(|&(x, y)| (x as isize, y as isize))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatternError {
+    /// The pattern file is malformed, `line` and `column` start at 1.
+    Syntax {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    /// The pattern is bigger than the board it is being loaded onto.
+    TooLarge {
+        width: usize,
+        height: usize,
+        board_width: usize,
+        board_height: usize,
+    },
+}
+
+impl PatternError {
+    fn syntax(line: usize, column: usize, message: impl Into<String>) -> Self {
+        Self::Syntax {
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for PatternError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Syntax {
+                line,
+                column,
+                message,
+            } => write!(f, "line {line}, column {column}: {message}"),
+            Self::TooLarge {
+                width,
+                height,
+                board_width,
+                board_height,
+            } => write!(
+                f,
+                "pattern is {width}x{height} but the board is only {board_width}x{board_height}"
+            ),
+        }
+    }
+}
+
+impl Error for PatternError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GLIDER: &[(usize, usize)] = &[(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(
+            Format::detect("x = 3, y = 3\nbo$2bo$3o!"),
+            Some(Format::Rle)
+        );
+        assert_eq!(Format::detect("\n#N Glider\nx=3,y=3\n"), Some(Format::Rle));
+        assert_eq!(
+            Format::detect("!Name: Glider\n.O\n"),
+            Some(Format::Plaintext)
+        );
+        assert_eq!(Format::detect(".O\n..O\nOOO\n"), Some(Format::Plaintext));
+        assert_eq!(Format::detect("#Life 1.06\n0 0\n"), Some(Format::Life106));
+        assert_eq!(Format::detect("hello"), None);
+        assert_eq!(Format::detect(""), None);
+    }
+
+    #[test]
+    fn test_parse_any_format() {
+        for input in [
+            "x = 3, y = 3\nbo$2bo$3o!",
+            ".O.\n..O\nOOO\n",
+            "#Life 1.06\n0 -1\n1 0\n-1 1\n0 1\n1 1\n",
+        ] {
+            assert_eq!(parse(input).unwrap().cells, GLIDER, "{input}");
+        }
+
+        assert_eq!(
+            parse("0 0"),
+            Err(PatternError::syntax(
+                1,
+                1,
+                "unrecognised pattern format, expected RLE, plaintext or Life 1.06"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_write_any_format() {
+        let glider = parse("x = 3, y = 3\nbo$2bo$3o!").unwrap();
+
+        for format in [Format::Rle, Format::Plaintext, Format::Life106] {
+            let written = write(&glider, format);
+
+            assert_eq!(Format::detect(&written), Some(format), "{written}");
+            assert_eq!(parse(&written).unwrap().cells, GLIDER, "{written}");
+        }
+    }
+
+    #[test]
+    fn test_from_path() {
+        assert_eq!(
+            Format::from_path(Path::new("a/glider.rle")),
+            Some(Format::Rle)
+        );
+        assert_eq!(
+            Format::from_path(Path::new("glider.CELLS")),
+            Some(Format::Plaintext)
+        );
+        assert_eq!(
+            Format::from_path(Path::new("glider.lif")),
+            Some(Format::Life106)
+        );
+        assert_eq!(Format::from_path(Path::new("glider")), None);
+    }
+}
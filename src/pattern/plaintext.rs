@@ -0,0 +1,135 @@
+//! The plaintext `.cells` format, a picture of the pattern made of `.` for
+//! dead cells and `O` for live ones.
+//!
+//! ```text
+//! !Name: Glider
+//! .O.
+//! ..O
+//! OOO
+//! ```
+//!
+//! Rows may leave out trailing dead cells, like most `.cells` files do.
+
+use std::fmt::Write;
+
+use super::{Pattern, PatternError};
+
+pub fn parse(input: &str) -> Result<Pattern, PatternError> {
+    let mut pattern = Pattern::default();
+    let mut y = 0;
+
+    for (number, line) in input.lines().enumerate().map(|(i, line)| (i + 1, line)) {
+        if let Some(comment) = line.strip_prefix('!') {
+            match comment.strip_prefix("Name:") {
+                Some(name) => pattern.name = Some(name.trim().to_string()),
+                None => pattern.comments.push(comment.trim().to_string()),
+            }
+
+            continue;
+        }
+
+        let row = line.trim_end();
+
+        for (x, c) in row.chars().enumerate() {
+            match c {
+                '.' => (),
+                'O' | '*' => pattern.cells.push((x, y)),
+                _ => {
+                    return Err(PatternError::syntax(
+                        number,
+                        x + 1,
+                        format!("unexpected `{c}`, expected `.` or `O`"),
+                    ))
+                }
+            }
+        }
+
+        // Trailing blank lines aren't part of the pattern.
+        if !row.is_empty() {
+            pattern.width = pattern.width.max(row.chars().count());
+            pattern.height = y + 1;
+        }
+
+        y += 1;
+    }
+
+    Ok(pattern)
+}
+
+pub fn write(pattern: &Pattern) -> String {
+    let mut out = String::new();
+
+    if let Some(name) = &pattern.name {
+        let _ = writeln!(out, "!Name: {name}");
+    }
+
+    for comment in &pattern.comments {
+        let _ = writeln!(out, "!{comment}");
+    }
+
+    let mut rows = vec![vec!['.'; pattern.width]; pattern.height];
+    for &(x, y) in &pattern.cells {
+        rows[y][x] = 'O';
+    }
+
+    for row in rows {
+        out.extend(row);
+        out.push('\n');
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let pattern = parse("!Name: Glider\n!A small spaceship\n.O\n..O\nOOO\n").unwrap();
+
+        assert_eq!(pattern.name.as_deref(), Some("Glider"));
+        assert_eq!(pattern.comments, &["A small spaceship"]);
+        assert_eq!((pattern.width, pattern.height), (3, 3));
+        assert_eq!(pattern.cells, &[(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]);
+    }
+
+    #[test]
+    fn test_parse_blank_rows() {
+        let pattern = parse("O\n\n...\nO.O\n\n").unwrap();
+
+        assert_eq!((pattern.width, pattern.height), (3, 4));
+        assert_eq!(pattern.cells, &[(0, 0), (0, 3), (2, 3)]);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse("!Name: Blinker\nOOO\nO0O\n"),
+            Err(PatternError::syntax(
+                3,
+                2,
+                "unexpected `0`, expected `.` or `O`"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let pattern = Pattern {
+            width: 4,
+            height: 3,
+            cells: vec![(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)],
+            name: Some("Glider".to_string()),
+            comments: vec!["www.conwaylife.com/wiki/Glider".to_string()],
+            ..Pattern::default()
+        };
+
+        let cells = write(&pattern);
+        assert_eq!(
+            cells,
+            "!Name: Glider\n!www.conwaylife.com/wiki/Glider\n.O..\n..O.\nOOO.\n"
+        );
+        assert_eq!(parse(&cells), Ok(pattern));
+    }
+}
@@ -0,0 +1,190 @@
+//! Patterns as PNG images, with every cell a square of pixels.
+//!
+//! Images are read back by sampling the middle of every square and deciding
+//! whether its brightness is closer to that of live or of dead cells, so
+//! black and white drawings from image editors can be loaded too.
+
+use std::{fs, path::Path, path::PathBuf};
+
+use super::Pattern;
+use crate::png::{self, Image};
+
+/// How patterns are drawn as images and where they are saved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PngOptions {
+    pub output: PathBuf,
+    /// The pixels across every cell.
+    pub cell_size: usize,
+    pub alive_color: [u8; 3],
+    pub dead_color: [u8; 3],
+}
+
+impl Default for PngOptions {
+    fn default() -> Self {
+        Self {
+            output: PathBuf::from("life.png"),
+            cell_size: 1,
+            alive_color: [255; 3],
+            dead_color: [0; 3],
+        }
+    }
+}
+
+/// Whether `data` starts like a PNG image.
+pub fn is_png(data: &[u8]) -> bool {
+    data.starts_with(b"\x89PNG")
+}
+
+pub fn write(pattern: &Pattern, options: &PngOptions) -> Result<Vec<u8>, String> {
+    let cell_size = options.cell_size;
+    // Empty patterns are drawn as a single dead cell.
+    let (columns, rows) = (pattern.width.max(1), pattern.height.max(1));
+
+    let dimension = |cells: usize| {
+        cells
+            .checked_mul(cell_size)
+            .and_then(|pixels| u32::try_from(pixels).ok())
+    };
+    let (Some(width), Some(height)) = (dimension(columns), dimension(rows)) else {
+        return Err(format!(
+            "a {columns}x{rows} pattern with {cell_size} pixels per cell is too large for a PNG"
+        ));
+    };
+
+    let stride = width as usize;
+    let mut indices = vec![0; stride * height as usize];
+    for &(x, y) in &pattern.cells {
+        for row in y * cell_size..(y + 1) * cell_size {
+            indices[row * stride + x * cell_size..][..cell_size].fill(1);
+        }
+    }
+
+    Ok(png::encode(
+        width,
+        height,
+        &[options.dead_color, options.alive_color],
+        &indices,
+    ))
+}
+
+pub fn parse(data: &[u8], options: &PngOptions) -> Result<Pattern, String> {
+    let image = png::decode(data).map_err(|e| e.to_string())?;
+    Ok(from_image(&image, options))
+}
+
+/// Saves `pattern` as a PNG image at `path`.
+pub fn save(path: &Path, pattern: &Pattern, options: &PngOptions) -> Result<(), String> {
+    fs::write(path, write(pattern, options)?)
+        .map_err(|e| format!("could not write `{}`: {e}", path.display()))
+}
+
+fn from_image(image: &Image, options: &PngOptions) -> Pattern {
+    let cell_size = options.cell_size;
+    let alive = brightness(options.alive_color);
+    let dead = brightness(options.dead_color);
+
+    let mut pattern = Pattern {
+        width: image.width / cell_size,
+        height: image.height / cell_size,
+        ..Pattern::default()
+    };
+
+    for y in 0..pattern.height {
+        for x in 0..pattern.width {
+            let [red, green, blue, alpha] =
+                image.pixel(x * cell_size + cell_size / 2, y * cell_size + cell_size / 2);
+
+            // Transparent pixels show the dead cells behind them.
+            let blend = |channel: u8, behind: u8| {
+                ((u32::from(channel) * u32::from(alpha)
+                    + u32::from(behind) * (255 - u32::from(alpha)))
+                    / 255) as u8
+            };
+            let [dead_red, dead_green, dead_blue] = options.dead_color;
+            let pixel = brightness([
+                blend(red, dead_red),
+                blend(green, dead_green),
+                blend(blue, dead_blue),
+            ]);
+
+            if pixel.abs_diff(alive) < pixel.abs_diff(dead) {
+                pattern.cells.push((x, y));
+            }
+        }
+    }
+
+    pattern
+}
+
+/// The perceived brightness of a color, from 0 to 255.
+fn brightness([red, green, blue]: [u8; 3]) -> u32 {
+    (u32::from(red) * 299 + u32::from(green) * 587 + u32::from(blue) * 114) / 1000
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn glider() -> Pattern {
+        Pattern {
+            width: 3,
+            height: 3,
+            cells: vec![(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)],
+            ..Pattern::default()
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for options in [
+            PngOptions::default(),
+            PngOptions {
+                cell_size: 4,
+                alive_color: [0; 3],
+                dead_color: [255, 255, 200],
+                ..PngOptions::default()
+            },
+        ] {
+            let data = write(&glider(), &options).unwrap();
+            assert!(is_png(&data));
+
+            let image = png::decode(&data).unwrap();
+            assert_eq!(image.width, 3 * options.cell_size);
+
+            assert_eq!(parse(&data, &options).unwrap(), glider());
+        }
+    }
+
+    #[test]
+    fn test_threshold() {
+        // Light and dark grays, and transparent white.
+        let image = Image {
+            width: 4,
+            height: 1,
+            pixels: vec![
+                [200, 200, 200, 255],
+                [60, 60, 60, 255],
+                [255, 0, 0, 255],
+                [255, 255, 255, 0],
+            ],
+        };
+
+        let pattern = from_image(&image, &PngOptions::default());
+        assert_eq!(pattern.cells, [(0, 0)]);
+
+        let inverted = PngOptions {
+            alive_color: [0; 3],
+            dead_color: [255; 3],
+            ..PngOptions::default()
+        };
+        assert_eq!(from_image(&image, &inverted).cells, [(1, 0), (2, 0)]);
+    }
+
+    #[test]
+    fn test_unsupported() {
+        assert_eq!(
+            parse(b"x = 3, y = 3", &PngOptions::default()),
+            Err("not a PNG image".to_string())
+        );
+    }
+}
@@ -0,0 +1,443 @@
+//! The Run Length Encoded format, the most common format for sharing
+//! patterns.
+//!
+//! ```text
+//! #N Glider
+//! x = 3, y = 3, rule = B3/S23
+//! bob$2bo$3o!
+//! ```
+
+use std::fmt::Write;
+
+use super::{Pattern, PatternError};
+
+/// Lines written by [`write`] are kept at most this long.
+const MAX_LINE_LENGTH: usize = 70;
+
+pub fn parse(input: &str) -> Result<Pattern, PatternError> {
+    let mut pattern = Pattern::default();
+    let mut lines = input.lines().enumerate().map(|(i, line)| (i + 1, line));
+    let mut has_header = false;
+
+    for (number, line) in lines.by_ref() {
+        let trimmed = line.trim();
+
+        if trimmed.is_empty() {
+            continue;
+        }
+
+        if let Some(comment) = trimmed.strip_prefix('#') {
+            parse_comment(comment, &mut pattern);
+            continue;
+        }
+
+        parse_header(number, line, &mut pattern)?;
+        has_header = true;
+        break;
+    }
+
+    if !has_header {
+        return Err(PatternError::syntax(
+            input.lines().count().max(1),
+            1,
+            "missing `x = ..., y = ...` header",
+        ));
+    }
+
+    let (mut x, mut y) = (0usize, 0usize);
+    // The run count being read, with the line and column it started at.
+    let mut run: Option<(usize, usize, usize)> = None;
+
+    'lines: for (number, line) in lines {
+        if line.trim_start().starts_with('#') {
+            continue;
+        }
+
+        for (column, c) in line.chars().enumerate().map(|(i, c)| (i + 1, c)) {
+            if let Some(digit) = c.to_digit(10) {
+                let (count, start_line, start_column) = run.unwrap_or((0, number, column));
+                let count = count
+                    .checked_mul(10)
+                    .and_then(|count| count.checked_add(digit as usize))
+                    .ok_or_else(|| {
+                        PatternError::syntax(number, column, "run count is too large")
+                    })?;
+
+                run = Some((count, start_line, start_column));
+                continue;
+            }
+
+            if c.is_whitespace() {
+                continue;
+            }
+
+            let count = run.take().map_or(1, |(count, ..)| count);
+            let too_large = || PatternError::syntax(number, column, "run count is too large");
+
+            match c {
+                'b' | '.' => x = x.checked_add(count).ok_or_else(too_large)?,
+                'o' | 'A' => {
+                    let end = x.checked_add(count).ok_or_else(too_large)?;
+                    if end > pattern.width || y >= pattern.height {
+                        return Err(PatternError::syntax(
+                            number,
+                            column,
+                            format!(
+                                "live cell is outside of the {}x{} size given in the header",
+                                pattern.width, pattern.height
+                            ),
+                        ));
+                    }
+
+                    pattern.cells.extend((x..end).map(|x| (x, y)));
+                    x = end;
+                }
+                '$' => {
+                    y = y.checked_add(count).ok_or_else(too_large)?;
+                    x = 0;
+                }
+                '!' => break 'lines,
+                _ => {
+                    return Err(PatternError::syntax(
+                        number,
+                        column,
+                        format!("unexpected `{c}`, expected `b`, `o`, `$` or `!`"),
+                    ))
+                }
+            }
+        }
+    }
+
+    if let Some((_, line, column)) = run {
+        return Err(PatternError::syntax(
+            line,
+            column,
+            "run count is missing a tag",
+        ));
+    }
+
+    Ok(pattern)
+}
+
+fn parse_comment(comment: &str, pattern: &mut Pattern) {
+    let mut chars = comment.chars();
+
+    match chars.next() {
+        Some('N') => pattern.name = Some(chars.as_str().trim().to_string()),
+        Some('C' | 'c') => pattern.comments.push(chars.as_str().trim().to_string()),
+        _ => (),
+    }
+}
+
+fn parse_header(number: usize, line: &str, pattern: &mut Pattern) -> Result<(), PatternError> {
+    let (mut width, mut height) = (None, None);
+    let mut offset = 0;
+
+    for field in line.split(',') {
+        let field_offset = offset;
+        offset += field.len() + 1;
+
+        let column = field_offset + leading_whitespace(field) + 1;
+
+        let Some((key, value)) = field.split_once('=') else {
+            return Err(PatternError::syntax(
+                number,
+                column,
+                format!("expected `key = value` in header, found `{}`", field.trim()),
+            ));
+        };
+
+        let value_column = field_offset + key.len() + 1 + leading_whitespace(value) + 1;
+        let (key, value) = (key.trim(), value.trim());
+
+        let parse_size = |value: &str| {
+            value.parse::<usize>().map_err(|_| {
+                PatternError::syntax(
+                    number,
+                    value_column,
+                    format!("`{value}` is not a valid size for `{key}`"),
+                )
+            })
+        };
+
+        match key {
+            "x" => width = Some(parse_size(value)?),
+            "y" => height = Some(parse_size(value)?),
+            "rule" => {
+                pattern.rule = Some(value.parse().map_err(|e| {
+                    PatternError::syntax(number, value_column, format!("invalid rule: {e}"))
+                })?)
+            }
+            _ => {
+                return Err(PatternError::syntax(
+                    number,
+                    column,
+                    format!("unknown header field `{key}`"),
+                ))
+            }
+        }
+    }
+
+    match (width, height) {
+        (Some(width), Some(height)) => {
+            pattern.width = width;
+            pattern.height = height;
+            Ok(())
+        }
+        _ => Err(PatternError::syntax(
+            number,
+            1,
+            "header must contain both `x` and `y`",
+        )),
+    }
+}
+
+fn leading_whitespace(s: &str) -> usize {
+    s.len() - s.trim_start().len()
+}
+
+pub fn write(pattern: &Pattern) -> String {
+    let mut out = String::new();
+
+    if let Some(name) = &pattern.name {
+        let _ = writeln!(out, "#N {name}");
+    }
+
+    for comment in &pattern.comments {
+        let _ = writeln!(out, "#C {comment}");
+    }
+
+    let _ = write!(out, "x = {}, y = {}", pattern.width, pattern.height);
+    if let Some(rule) = &pattern.rule {
+        let _ = write!(out, ", rule = {rule}");
+    }
+    out.push('\n');
+
+    let mut rows = vec![Vec::new(); pattern.height];
+    for &(x, y) in &pattern.cells {
+        rows[y].push(x);
+    }
+
+    let mut tokens = Vec::new();
+    let mut current_row = 0;
+
+    for (y, row) in rows.iter_mut().enumerate() {
+        if row.is_empty() {
+            continue;
+        }
+
+        if y > current_row {
+            tokens.push(token(y - current_row, '$'));
+            current_row = y;
+        }
+
+        row.sort_unstable();
+        row.dedup();
+
+        let mut x = 0;
+        for run in row.chunk_by(|a, b| a + 1 == *b) {
+            if run[0] > x {
+                tokens.push(token(run[0] - x, 'b'));
+            }
+
+            tokens.push(token(run.len(), 'o'));
+            x = run[0] + run.len();
+        }
+    }
+
+    tokens.push("!".to_string());
+
+    let mut line_length = 0;
+    for token in tokens {
+        if line_length + token.len() > MAX_LINE_LENGTH {
+            out.push('\n');
+            line_length = 0;
+        }
+
+        line_length += token.len();
+        out.push_str(&token);
+    }
+    out.push('\n');
+
+    out
+}
+
+fn token(count: usize, tag: char) -> String {
+    if count == 1 {
+        tag.to_string()
+    } else {
+        format!("{count}{tag}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        board::{Anchor, Board},
+        rule::Rule,
+    };
+
+    #[test]
+    fn test_parse_glider() {
+        let pattern = parse(
+            "#N Glider\n#C The smallest spaceship.\nx = 3, y = 3, rule = B3/S23\nbob$2bo$3o!\n",
+        )
+        .unwrap();
+
+        assert_eq!(pattern.name.as_deref(), Some("Glider"));
+        assert_eq!(pattern.comments, &["The smallest spaceship."]);
+        assert_eq!((pattern.width, pattern.height), (3, 3));
+        assert_eq!(pattern.rule, Some(Rule::CONWAY));
+        assert_eq!(pattern.cells, &[(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]);
+    }
+
+    #[test]
+    fn test_parse_multiline() {
+        let pattern = parse("x=5,y=4\n2o\n3$\n2b3o\n!ignored").unwrap();
+
+        assert_eq!(pattern.rule, None);
+        assert_eq!(pattern.cells, &[(0, 0), (1, 0), (2, 3), (3, 3), (4, 3)]);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse("#C nothing else\n"),
+            Err(PatternError::syntax(
+                1,
+                1,
+                "missing `x = ..., y = ...` header"
+            ))
+        );
+
+        assert_eq!(
+            parse("x = 3, y = three\n!"),
+            Err(PatternError::syntax(
+                1,
+                12,
+                "`three` is not a valid size for `y`"
+            ))
+        );
+
+        assert_eq!(
+            parse("x = 3, y = 3, rule = B3/S2x\n!"),
+            Err(PatternError::syntax(
+                1,
+                22,
+                "invalid rule: unexpected character `x` in rule"
+            ))
+        );
+
+        assert_eq!(
+            parse("x = 3, z = 3\n!"),
+            Err(PatternError::syntax(1, 8, "unknown header field `z`"))
+        );
+
+        assert_eq!(
+            parse("x = 2, y = 2\nbo$\nobq!"),
+            Err(PatternError::syntax(
+                3,
+                3,
+                "unexpected `q`, expected `b`, `o`, `$` or `!`"
+            ))
+        );
+
+        assert_eq!(
+            parse("x = 2, y = 2\nb2o!"),
+            Err(PatternError::syntax(
+                2,
+                3,
+                "live cell is outside of the 2x2 size given in the header"
+            ))
+        );
+
+        assert_eq!(
+            parse("x = 2, y = 2\no$12"),
+            Err(PatternError::syntax(2, 3, "run count is missing a tag"))
+        );
+
+        assert_eq!(
+            parse("x = 2, y = 2\n18446744073709551615b2o!"),
+            Err(PatternError::syntax(2, 23, "run count is too large"))
+        );
+
+        assert_eq!(
+            parse("x = 2, y = 2\no$18446744073709551615$$o!"),
+            Err(PatternError::syntax(2, 23, "run count is too large"))
+        );
+    }
+
+    #[test]
+    fn test_write() {
+        let pattern = Pattern {
+            width: 3,
+            height: 3,
+            cells: vec![(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)],
+            rule: Some(Rule::CONWAY),
+            name: Some("Glider".to_string()),
+            comments: vec!["The smallest spaceship.".to_string()],
+        };
+
+        assert_eq!(
+            write(&pattern),
+            "#N Glider\n#C The smallest spaceship.\nx = 3, y = 3, rule = B3/S23\nbo$2bo$3o!\n"
+        );
+
+        let pattern = Pattern {
+            width: 100,
+            height: 3,
+            cells: (0..100).step_by(2).map(|x| (x, 2)).collect(),
+            ..Pattern::default()
+        };
+
+        let rle = write(&pattern);
+        assert!(rle.starts_with("x = 100, y = 3\n2$ob"));
+        assert!(rle.lines().all(|line| line.len() <= MAX_LINE_LENGTH));
+        assert_eq!(parse(&rle), Ok(pattern));
+    }
+
+    fn assert_round_trips((width, height): (usize, usize), cells: &[&[usize]]) {
+        let board = Board::from_cells(cells).resized(width, height, Anchor::TopLeft);
+        let rle = write(&Pattern::from_board(&board));
+        let pattern = parse(&rle).unwrap();
+
+        assert_eq!(
+            Board::from_pattern(&pattern, width, height, Anchor::TopLeft),
+            Ok(board)
+        );
+    }
+
+    #[test]
+    fn test_round_trip_blinker() {
+        assert_round_trips((3, 3), &[&[0, 1, 0], &[0, 1, 0], &[0, 1, 0]]);
+        assert_round_trips((3, 3), &[&[0, 0, 0], &[1, 1, 1], &[0, 0, 0]]);
+    }
+
+    #[test]
+    fn test_round_trip_glider() {
+        assert_round_trips((4, 4), &[&[1, 0, 0], &[0, 1, 1], &[1, 1, 0]]);
+        assert_round_trips((4, 4), &[&[0, 1, 0], &[0, 0, 1], &[1, 1, 1]]);
+        assert_round_trips((4, 4), &[&[0, 0, 0], &[1, 0, 1], &[0, 1, 1], &[0, 1, 0]]);
+        assert_round_trips((4, 4), &[&[0, 0, 0], &[0, 0, 1], &[1, 0, 1], &[0, 1, 1]]);
+        assert_round_trips(
+            (4, 4),
+            &[&[0, 0, 0, 0], &[0, 1, 0, 0], &[0, 0, 1, 1], &[0, 1, 1, 0]],
+        );
+    }
+
+    #[test]
+    fn test_pattern_too_large() {
+        let pattern = parse("x = 5, y = 1\n5o!").unwrap();
+
+        assert_eq!(
+            Board::from_pattern(&pattern, 4, 4, Anchor::TopLeft),
+            Err(PatternError::TooLarge {
+                width: 5,
+                height: 1,
+                board_width: 4,
+                board_height: 4
+            })
+        );
+    }
+}
@@ -0,0 +1,257 @@
+use std::collections::HashMap;
+
+use crate::game_context::GameOfLifeBoard;
+
+/// At most this many states, holding at most this many live cells between
+/// them, are remembered, after which they are all forgotten so long lived
+/// patterns can't use up all of the memory.
+const MAX_STATES: usize = 1 << 20;
+const MAX_STORED_CELLS: usize = 8_000_000;
+
+/// Boards with more live cells than this aren't looked at, as comparing them
+/// would slow down every update.
+const MAX_POPULATION: usize = 100_000;
+
+/// How a pattern repeats once it has stabilized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Periodicity {
+    /// The generations between two occurrences of the same state, which is
+    /// 1 for still lifes and dead boards.
+    pub period: u64,
+    /// How far the pattern moves every period, which is only non-zero for
+    /// spaceships and other moving patterns.
+    pub displacement: (isize, isize),
+    /// The first generation of the repeating cycle.
+    pub stabilized_at: u64,
+}
+
+/// A state of the board seen before.
+struct State {
+    generation: u64,
+    /// The top left corner of the bounding box of the live cells.
+    position: (isize, isize),
+    /// The live cells relative to `position`, sorted.
+    cells: Vec<(isize, isize)>,
+}
+
+/// Detects when the board returns to a state it has been in before, anywhere
+/// on the board.
+///
+/// The period found is exact when every generation is observed, and a
+/// multiple of it otherwise.
+#[derive(Default)]
+pub struct PeriodDetector {
+    /// The states seen with every fingerprint, which different states can
+    /// share.
+    seen: HashMap<u64, Vec<State>>,
+    stored_cells: usize,
+    periodicity: Option<Periodicity>,
+}
+
+impl PeriodDetector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether nothing has been observed since this was created or reset.
+    pub fn is_empty(&self) -> bool {
+        self.seen.is_empty()
+    }
+
+    pub fn periodicity(&self) -> Option<Periodicity> {
+        self.periodicity
+    }
+
+    /// Forgets every state, for when the board is changed by anything other
+    /// than advancing it.
+    pub fn reset(&mut self) {
+        self.seen.clear();
+        self.stored_cells = 0;
+        self.periodicity = None;
+    }
+
+    /// Records `board` at `generation`, returning how it repeats if it has
+    /// been seen before. Once a repeat is found the board isn't looked at
+    /// again until [`PeriodDetector::reset`], and boards with more than
+    /// [`MAX_POPULATION`] live cells aren't looked at at all.
+    pub fn observe<B: GameOfLifeBoard>(
+        &mut self,
+        generation: u64,
+        board: &B,
+    ) -> Option<Periodicity> {
+        if self.periodicity.is_some() {
+            return self.periodicity;
+        }
+
+        if board.population() > MAX_POPULATION {
+            return None;
+        }
+
+        let (cells, (x, y)) = normalize(board);
+        let fingerprint = fingerprint(&cells);
+
+        let first = self
+            .seen
+            .get(&fingerprint)
+            .and_then(|states| states.iter().find(|state| state.cells == cells));
+
+        match first {
+            Some(first) if first.generation < generation => {
+                let (first_x, first_y) = first.position;
+                self.periodicity = Some(Periodicity {
+                    period: generation - first.generation,
+                    displacement: (x - first_x, y - first_y),
+                    stabilized_at: first.generation,
+                });
+            }
+            Some(_) => (),
+            None => {
+                if self.seen.len() >= MAX_STATES
+                    || self.stored_cells + cells.len() > MAX_STORED_CELLS
+                {
+                    self.seen.clear();
+                    self.stored_cells = 0;
+                }
+
+                self.stored_cells += cells.len();
+                self.seen.entry(fingerprint).or_default().push(State {
+                    generation,
+                    position: (x, y),
+                    cells,
+                });
+            }
+        }
+
+        self.periodicity
+    }
+}
+
+/// Returns the live cells of `board` relative to the top left corner of
+/// their bounding box, so the same pattern anywhere on the board has the same
+/// cells, sorted, along with that corner.
+fn normalize<B: GameOfLifeBoard>(board: &B) -> (Vec<(isize, isize)>, (isize, isize)) {
+    let mut cells: Vec<(isize, isize)> = board.alive_cells().collect();
+    let (min_x, min_y) = cells
+        .iter()
+        .fold((isize::MAX, isize::MAX), |(min_x, min_y), &(x, y)| {
+            (min_x.min(x), min_y.min(y))
+        });
+
+    for (x, y) in &mut cells {
+        (*x, *y) = (*x - min_x, *y - min_y);
+    }
+    cells.sort_unstable();
+
+    (cells, (min_x, min_y))
+}
+
+/// Hashes normalized cells, for finding states which may have been seen
+/// before.
+fn fingerprint(cells: &[(isize, isize)]) -> u64 {
+    cells.iter().fold(0u64, |hash, &(x, y)| {
+        mix(hash ^ mix((x as u64) << 32 ^ y as u64))
+    })
+}
+
+/// The SplitMix64 finalizer, spreading every bit of `value` over the result.
+fn mix(mut value: u64) -> u64 {
+    value = (value ^ (value >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    value = (value ^ (value >> 27)).wrapping_mul(0x94d049bb133111eb);
+    value ^ (value >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{infinite_board::InfiniteBoard, rule::Rule};
+
+    /// Runs `board` until it repeats, for at most `generations` generations.
+    fn detect(mut board: InfiniteBoard, generations: u64) -> Option<Periodicity> {
+        let mut detector = PeriodDetector::new();
+
+        for generation in 0..=generations {
+            if let Some(periodicity) = detector.observe(generation, &board) {
+                return Some(periodicity);
+            }
+            board = board.tick(&Rule::CONWAY);
+        }
+
+        None
+    }
+
+    #[test]
+    fn test_oscillator() {
+        let blinker = InfiniteBoard::from_cells(&[&[1, 1, 1]]);
+
+        assert_eq!(
+            detect(blinker, 10),
+            Some(Periodicity {
+                period: 2,
+                displacement: (0, 0),
+                stabilized_at: 0,
+            })
+        );
+    }
+
+    #[test]
+    fn test_spaceship() {
+        let glider = InfiniteBoard::from_cells(&[&[0, 1, 0], &[0, 0, 1], &[1, 1, 1]]);
+
+        assert_eq!(
+            detect(glider, 10),
+            Some(Periodicity {
+                period: 4,
+                displacement: (1, 1),
+                stabilized_at: 0,
+            })
+        );
+    }
+
+    #[test]
+    fn test_fingerprint_collision() {
+        let blinker = InfiniteBoard::from_cells(&[&[1, 1, 1]]);
+        let (cells, _) = normalize(&blinker);
+
+        // A different state which happens to share the blinker's fingerprint.
+        let mut detector = PeriodDetector::new();
+        detector.seen.insert(
+            fingerprint(&cells),
+            vec![State {
+                generation: 0,
+                position: (0, 0),
+                cells: vec![(0, 0)],
+            }],
+        );
+
+        assert_eq!(detector.observe(1, &blinker), None);
+        assert_eq!(detector.observe(2, &blinker.tick(&Rule::CONWAY)), None);
+        assert_eq!(
+            detector
+                .observe(3, &blinker)
+                .map(|periodicity| periodicity.period),
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn test_stabilization() {
+        // An L tromino becomes a block after one generation.
+        let tromino = InfiniteBoard::from_cells(&[&[1, 1], &[0, 1]]);
+        assert_eq!(
+            detect(tromino, 10),
+            Some(Periodicity {
+                period: 1,
+                displacement: (0, 0),
+                stabilized_at: 1,
+            })
+        );
+
+        let diehard = InfiniteBoard::from_cells(&[
+            &[0, 0, 0, 0, 0, 0, 1, 0],
+            &[1, 1, 0, 0, 0, 0, 0, 0],
+            &[0, 1, 0, 0, 0, 1, 1, 1],
+        ]);
+        assert_eq!(detect(diehard.clone(), 129), None);
+        assert_eq!(detect(diehard, 200).unwrap().stabilized_at, 130);
+    }
+}
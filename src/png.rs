@@ -0,0 +1,490 @@
+//! Writing paletted PNG images and reading back the common kinds of PNG.
+//!
+//! Every color type and bit depth can be read, but interlaced images can't.
+
+use std::fmt;
+
+use crate::zlib;
+
+const SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// The most pixels read from an image, so a corrupt or hostile size can't
+/// use up all of the memory.
+const MAX_PIXELS: u64 = 1 << 28;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PngError {
+    /// The file doesn't start like a PNG.
+    NotPng,
+    /// The file is a valid PNG, but uses a feature that can't be read.
+    Unsupported(String),
+    /// The file is damaged.
+    Corrupt(String),
+}
+
+impl fmt::Display for PngError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotPng => f.write_str("not a PNG image"),
+            Self::Unsupported(feature) => write!(f, "unsupported PNG: {feature}"),
+            Self::Corrupt(reason) => write!(f, "corrupt PNG: {reason}"),
+        }
+    }
+}
+
+fn corrupt<T>(reason: impl Into<String>) -> Result<T, PngError> {
+    Err(PngError::Corrupt(reason.into()))
+}
+
+/// A decoded image, with its pixels as RGBA row by row from the top left.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<[u8; 4]>,
+}
+
+impl Image {
+    pub fn pixel(&self, x: usize, y: usize) -> [u8; 4] {
+        self.pixels[y * self.width + x]
+    }
+}
+
+/// Encodes a `width` by `height` image of indices into `palette`, which has
+/// between 1 and 256 colors.
+pub fn encode(width: u32, height: u32, palette: &[[u8; 3]], indices: &[u8]) -> Vec<u8> {
+    assert!(
+        (1..=256).contains(&palette.len()),
+        "a PNG palette has 1 to 256 colors"
+    );
+    assert_eq!(indices.len(), width as usize * height as usize);
+
+    let mut png = SIGNATURE.to_vec();
+
+    let mut header = Vec::with_capacity(13);
+    header.extend(width.to_be_bytes());
+    header.extend(height.to_be_bytes());
+    // 8 bit palette indices, compressed, filtered and not interlaced.
+    header.extend([8, 3, 0, 0, 0]);
+    write_chunk(&mut png, b"IHDR", &header);
+
+    write_chunk(&mut png, b"PLTE", palette.as_flattened());
+
+    // Every row starts with the filter used for it, which is always none.
+    let mut rows = Vec::with_capacity(indices.len() + height as usize);
+    if width > 0 {
+        for row in indices.chunks(width as usize) {
+            rows.push(0);
+            rows.extend(row);
+        }
+    }
+    write_chunk(&mut png, b"IDAT", &zlib::compress(&rows));
+
+    write_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+fn write_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend((data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend(kind);
+    png.extend(data);
+    let crc = crc32(&png[start..]);
+    png.extend(crc.to_be_bytes());
+}
+
+/// The CRC-32 checksum of every PNG chunk.
+fn crc32(data: &[u8]) -> u32 {
+    const TABLE: [u32; 256] = {
+        let mut table = [0; 256];
+        let mut i = 0;
+        while i < 256 {
+            let mut crc = i as u32;
+            let mut bit = 0;
+            while bit < 8 {
+                crc = if crc & 1 != 0 {
+                    0xedb8_8320 ^ (crc >> 1)
+                } else {
+                    crc >> 1
+                };
+                bit += 1;
+            }
+            table[i] = crc;
+            i += 1;
+        }
+        table
+    };
+
+    !data.iter().fold(!0, |crc, &byte| {
+        TABLE[((crc ^ u32::from(byte)) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
+/// The layout of the pixels, from the `IHDR` chunk.
+struct Header {
+    width: usize,
+    height: usize,
+    bit_depth: u8,
+    color_type: u8,
+}
+
+impl Header {
+    fn parse(data: &[u8]) -> Result<Self, PngError> {
+        let &[w0, w1, w2, w3, h0, h1, h2, h3, bit_depth, color_type, compression, filter, interlace] =
+            data
+        else {
+            return corrupt("the header has the wrong length");
+        };
+
+        let width = u32::from_be_bytes([w0, w1, w2, w3]);
+        let height = u32::from_be_bytes([h0, h1, h2, h3]);
+        if width == 0 || height == 0 {
+            return corrupt("the image is empty");
+        }
+
+        let depths: &[u8] = match color_type {
+            0 => &[1, 2, 4, 8, 16],
+            3 => &[1, 2, 4, 8],
+            2 | 4 | 6 => &[8, 16],
+            _ => return corrupt(format!("unknown color type {color_type}")),
+        };
+        if !depths.contains(&bit_depth) {
+            return corrupt(format!(
+                "a bit depth of {bit_depth} isn't allowed with color type {color_type}"
+            ));
+        }
+
+        if compression != 0 || filter != 0 {
+            return corrupt("unknown compression or filter method");
+        }
+        if interlace != 0 {
+            return Err(PngError::Unsupported(
+                "interlaced images can't be read".to_string(),
+            ));
+        }
+        if u64::from(width) * u64::from(height) > MAX_PIXELS {
+            return Err(PngError::Unsupported(format!(
+                "a {width}x{height} image is too large"
+            )));
+        }
+
+        Ok(Self {
+            width: width as usize,
+            height: height as usize,
+            bit_depth,
+            color_type,
+        })
+    }
+
+    fn channels(&self) -> usize {
+        match self.color_type {
+            2 => 3,
+            4 => 2,
+            6 => 4,
+            _ => 1,
+        }
+    }
+
+    /// The bytes in a row of pixels, without its filter byte.
+    fn stride(&self) -> usize {
+        (self.width * self.channels() * usize::from(self.bit_depth)).div_ceil(8)
+    }
+
+    /// The distance in bytes between the same channel of neighboring
+    /// pixels, as used by the filters.
+    fn pixel_bytes(&self) -> usize {
+        (self.channels() * usize::from(self.bit_depth)).div_ceil(8)
+    }
+}
+
+/// Decodes a PNG image.
+pub fn decode(png: &[u8]) -> Result<Image, PngError> {
+    let mut rest = png.strip_prefix(SIGNATURE).ok_or(PngError::NotPng)?;
+    let mut header = None;
+    let mut palette = Vec::new();
+    let mut alphas = Vec::new();
+    let mut compressed = Vec::new();
+
+    loop {
+        if rest.len() < 12 {
+            return corrupt("the file ends before the last chunk");
+        }
+        let length = u32::from_be_bytes([rest[0], rest[1], rest[2], rest[3]]) as usize;
+        let kind = &rest[4..8];
+        let Some(data) = rest.get(8..8 + length) else {
+            return corrupt("the file ends in the middle of a chunk");
+        };
+        let Some(crc) = rest.get(8 + length..12 + length) else {
+            return corrupt("the file ends in the middle of a chunk");
+        };
+        if crc32(&rest[4..8 + length]).to_be_bytes() != crc {
+            return corrupt(format!(
+                "the checksum of the `{}` chunk doesn't match",
+                String::from_utf8_lossy(kind)
+            ));
+        }
+        rest = &rest[12 + length..];
+
+        match kind {
+            b"IHDR" => header = Some(Header::parse(data)?),
+            _ if header.is_none() => return corrupt("the first chunk isn't the header"),
+            b"PLTE" => palette = data.chunks_exact(3).map(|c| [c[0], c[1], c[2]]).collect(),
+            b"tRNS" => alphas = data.to_vec(),
+            b"IDAT" => compressed.extend(data),
+            b"IEND" => break,
+            // Chunks starting with a lower case letter can be skipped.
+            _ if kind[0].is_ascii_lowercase() => (),
+            _ => {
+                return Err(PngError::Unsupported(format!(
+                    "the `{}` chunk isn't known",
+                    String::from_utf8_lossy(kind)
+                )))
+            }
+        }
+    }
+
+    let header = header.expect("the loop only ends after the header");
+    if header.color_type == 3 && palette.is_empty() {
+        return corrupt("the palette is missing");
+    }
+
+    // Anything beyond the filtered rows would be thrown away.
+    let expected = (header.stride() + 1) * header.height;
+    let data =
+        zlib::decompress(&compressed, expected).map_err(|e| PngError::Corrupt(e.to_string()))?;
+    let rows = unfilter(&header, &data)?;
+
+    let samples = |row: &[u8]| -> Vec<u16> {
+        let depth = usize::from(header.bit_depth);
+        let count = header.width * header.channels();
+
+        (0..count)
+            .map(|i| match depth {
+                16 => u16::from_be_bytes([row[i * 2], row[i * 2 + 1]]),
+                8 => u16::from(row[i]),
+                _ => {
+                    let bit = i * depth;
+                    let shift = 8 - depth - bit % 8;
+                    u16::from(row[bit / 8] >> shift) & ((1 << depth) - 1)
+                }
+            })
+            .collect()
+    };
+    // Scales a sample to 8 bits.
+    let scale = |sample: u16| match header.bit_depth {
+        16 => (sample >> 8) as u8,
+        depth => (u32::from(sample) * 255 / ((1 << depth) - 1)) as u8,
+    };
+
+    let mut pixels = Vec::with_capacity(header.width * header.height);
+    for row in rows.chunks_exact(header.stride()) {
+        let samples = samples(row);
+
+        for pixel in samples.chunks_exact(header.channels()) {
+            pixels.push(match (header.color_type, pixel) {
+                (0, &[gray]) => [scale(gray), scale(gray), scale(gray), 255],
+                (2, &[red, green, blue]) => [scale(red), scale(green), scale(blue), 255],
+                (3, &[index]) => {
+                    let index = usize::from(index);
+                    let Some(&[red, green, blue]) = palette.get(index) else {
+                        return corrupt(format!("palette index {index} is out of range"));
+                    };
+                    [red, green, blue, alphas.get(index).copied().unwrap_or(255)]
+                }
+                (4, &[gray, alpha]) => [scale(gray), scale(gray), scale(gray), scale(alpha)],
+                (_, &[red, green, blue, alpha]) => {
+                    [scale(red), scale(green), scale(blue), scale(alpha)]
+                }
+                _ => unreachable!("every color type has its own number of channels"),
+            });
+        }
+    }
+
+    Ok(Image {
+        width: header.width,
+        height: header.height,
+        pixels,
+    })
+}
+
+/// Undoes the filter of every row, returning the rows without their filter
+/// bytes.
+fn unfilter(header: &Header, data: &[u8]) -> Result<Vec<u8>, PngError> {
+    let stride = header.stride();
+    let distance = header.pixel_bytes();
+
+    if data.len() < (stride + 1) * header.height {
+        return corrupt("the image data is too short");
+    }
+
+    let mut rows = vec![0; stride * header.height];
+    for (y, filtered) in data
+        .chunks_exact(stride + 1)
+        .take(header.height)
+        .enumerate()
+    {
+        let (done, rest) = rows.split_at_mut(y * stride);
+        let above = done.get(done.len().wrapping_sub(stride)..).unwrap_or(&[]);
+        let row = &mut rest[..stride];
+
+        for x in 0..stride {
+            let left = if x >= distance { row[x - distance] } else { 0 };
+            let up = above.get(x).copied().unwrap_or(0);
+            let up_left = if x >= distance {
+                above.get(x - distance).copied().unwrap_or(0)
+            } else {
+                0
+            };
+
+            let predicted = match filtered[0] {
+                0 => 0,
+                1 => left,
+                2 => up,
+                3 => ((u16::from(left) + u16::from(up)) / 2) as u8,
+                4 => paeth(left, up, up_left),
+                filter => return corrupt(format!("unknown filter type {filter}")),
+            };
+            row[x] = filtered[x + 1].wrapping_add(predicted);
+        }
+    }
+
+    Ok(rows)
+}
+
+/// Picks whichever of the left, upper and upper left bytes is closest to
+/// `left + up - up_left`.
+fn paeth(left: u8, up: u8, up_left: u8) -> u8 {
+    let estimate = i16::from(left) + i16::from(up) - i16::from(up_left);
+    let distance = |byte: u8| (estimate - i16::from(byte)).abs();
+
+    if distance(left) <= distance(up) && distance(left) <= distance(up_left) {
+        left
+    } else if distance(up) <= distance(up_left) {
+        up
+    } else {
+        up_left
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(crc32(b"IEND"), 0xae42_6082);
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let palette = [[0, 0, 0], [255, 128, 0], [1, 2, 3]];
+        let indices: Vec<u8> = (0..35).map(|i| (i * i % 7 % 3) as u8).collect();
+
+        let image = decode(&encode(7, 5, &palette, &indices)).unwrap();
+        assert_eq!((image.width, image.height), (7, 5));
+        assert_eq!(
+            image.pixels,
+            indices
+                .iter()
+                .map(|&index| {
+                    let [red, green, blue] = palette[usize::from(index)];
+                    [red, green, blue, 255]
+                })
+                .collect::<Vec<_>>()
+        );
+    }
+
+    /// Builds a PNG out of the given header fields and unfiltered rows.
+    fn png(width: u32, height: u32, bit_depth: u8, color_type: u8, rows: &[u8]) -> Vec<u8> {
+        let mut png = SIGNATURE.to_vec();
+        let mut header = Vec::new();
+        header.extend(width.to_be_bytes());
+        header.extend(height.to_be_bytes());
+        header.extend([bit_depth, color_type, 0, 0, 0]);
+        write_chunk(&mut png, b"IHDR", &header);
+        write_chunk(&mut png, b"IDAT", &zlib::compress(rows));
+        write_chunk(&mut png, b"IEND", &[]);
+        png
+    }
+
+    #[test]
+    fn test_color_types() {
+        // 1 bit grayscale, with a row of 10 pixels taking 2 bytes.
+        let image = decode(&png(10, 1, 1, 0, &[0, 0b1010_0000, 0b0100_0000])).unwrap();
+        let grays: Vec<u8> = image.pixels.iter().map(|pixel| pixel[0]).collect();
+        assert_eq!(grays, [255, 0, 255, 0, 0, 0, 0, 0, 0, 255]);
+
+        // 16 bit gray and alpha.
+        let image = decode(&png(1, 1, 16, 4, &[0, 0x80, 0x01, 0xff, 0xff])).unwrap();
+        assert_eq!(image.pixels, [[0x80, 0x80, 0x80, 0xff]]);
+
+        // 8 bit RGBA, with the second row using the up filter.
+        let image = decode(&png(1, 2, 8, 6, &[0, 10, 20, 30, 40, 2, 1, 1, 1, 1])).unwrap();
+        assert_eq!(image.pixels, [[10, 20, 30, 40], [11, 21, 31, 41]]);
+    }
+
+    #[test]
+    fn test_filters() {
+        // Two rows of two RGB pixels, with the sub, average and Paeth
+        // filters.
+        let rows = [
+            1, 10, 20, 30, 5, 5, 5, //
+            3, 0, 0, 0, 0, 0, 0, //
+        ];
+        let image = decode(&png(2, 2, 8, 2, &rows)).unwrap();
+        assert_eq!(
+            image.pixels,
+            [
+                [10, 20, 30, 255],
+                [15, 25, 35, 255],
+                [5, 10, 15, 255],
+                [10, 17, 25, 255],
+            ]
+        );
+
+        let rows = [0, 1, 2, 3, 4, 5, 6, 4, 1, 1, 1, 1, 1, 1];
+        let image = decode(&png(2, 2, 8, 2, &rows)).unwrap();
+        assert_eq!(image.pixels[2..], [[2, 3, 4, 255], [5, 6, 7, 255]]);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(decode(b"GIF89a"), Err(PngError::NotPng));
+
+        let mut interlaced = png(1, 1, 8, 0, &[0, 0]);
+        interlaced[28] = 1;
+        let crc = crc32(&interlaced[12..29]);
+        interlaced[29..33].copy_from_slice(&crc.to_be_bytes());
+        assert_eq!(
+            decode(&interlaced),
+            Err(PngError::Unsupported(
+                "interlaced images can't be read".to_string()
+            ))
+        );
+
+        let mut damaged = encode(2, 2, &[[0; 3]], &[0; 4]);
+        let last = damaged.len() - 20;
+        damaged[last] ^= 1;
+        assert!(matches!(decode(&damaged), Err(PngError::Corrupt(_))));
+
+        assert_eq!(
+            decode(&png(1, 1, 3, 2, &[0, 0])),
+            Err(PngError::Corrupt(
+                "a bit depth of 3 isn't allowed with color type 2".to_string()
+            ))
+        );
+        assert!(matches!(
+            decode(&encode(3, 1, &[[0; 3]], &[0, 1, 0])),
+            Err(PngError::Corrupt(_))
+        ));
+
+        // A 1x1 image whose data decompresses to far more than its one row.
+        assert_eq!(
+            decode(&png(1, 1, 8, 0, &[0; 1_000_000])),
+            Err(PngError::Corrupt(
+                "the compressed data decompresses to too much data".to_string()
+            ))
+        );
+    }
+}
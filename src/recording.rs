@@ -0,0 +1,327 @@
+//! Recording generations of a board into an animated GIF.
+
+use std::{
+    fs::File,
+    io::BufWriter,
+    path::{Path, PathBuf},
+};
+
+use crate::{cli::Options, game_context::GameOfLifeBoard, gif, rule::Rule};
+
+/// A rectangle of cells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Region {
+    pub x: isize,
+    pub y: isize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Region {
+    /// The smallest region covering every live cell of `board`, if any.
+    pub fn around<B: GameOfLifeBoard>(board: &B) -> Option<Self> {
+        let ((min_x, min_y), (max_x, max_y)) = board.bounding_box()?;
+
+        Some(Self {
+            x: min_x,
+            y: min_y,
+            width: (max_x - min_x + 1) as usize,
+            height: (max_y - min_y + 1) as usize,
+        })
+    }
+
+    /// The smallest region covering both `self` and `other`.
+    pub fn union(self, other: Self) -> Self {
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        let right = (self.x + self.width as isize).max(other.x + other.width as isize);
+        let bottom = (self.y + self.height as isize).max(other.y + other.height as isize);
+
+        Self {
+            x,
+            y,
+            width: (right - x) as usize,
+            height: (bottom - y) as usize,
+        }
+    }
+}
+
+/// How recordings look and where they go.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GifOptions {
+    pub output: PathBuf,
+    /// The number of updates to record without opening a window, or `None`
+    /// to record from the viewer.
+    pub frames: Option<u64>,
+    /// The pixels across every cell.
+    pub cell_size: usize,
+    /// `None` records the whole board, or everything the pattern reaches on
+    /// unbounded boards, or what's visible in the viewer.
+    pub region: Option<Region>,
+    /// How long every frame is shown for, in hundredths of a second.
+    pub frame_delay: u16,
+    pub alive_color: [u8; 3],
+    pub dead_color: [u8; 3],
+}
+
+impl Default for GifOptions {
+    fn default() -> Self {
+        Self {
+            output: PathBuf::from("life.gif"),
+            frames: None,
+            cell_size: 4,
+            region: None,
+            frame_delay: 10,
+            alive_color: [255; 3],
+            dead_color: [0; 3],
+        }
+    }
+}
+
+/// Writes generations of a board to a GIF file as they are recorded.
+pub struct Recorder {
+    encoder: gif::Encoder<BufWriter<File>>,
+    output: PathBuf,
+    region: Region,
+    cell_size: usize,
+    frame_delay: u16,
+    /// The generation recorded last, so it isn't recorded twice.
+    last_generation: Option<u64>,
+    frames: u64,
+}
+
+impl Recorder {
+    /// Starts recording `region` to `options.output`.
+    pub fn create(options: &GifOptions, region: Region) -> Result<Self, String> {
+        let dimension = |cells: usize| {
+            cells
+                .checked_mul(options.cell_size)
+                .and_then(|pixels| u16::try_from(pixels).ok())
+                .filter(|&pixels| pixels > 0)
+        };
+        let (Some(width), Some(height)) = (dimension(region.width), dimension(region.height))
+        else {
+            return Err(format!(
+                "a {}x{} region with {} pixels per cell doesn't fit in a GIF, which is at most \
+                 65535 pixels across",
+                region.width, region.height, options.cell_size
+            ));
+        };
+
+        let file = File::create(&options.output).map_err(|e| write_error(&options.output, e))?;
+        let encoder = gif::Encoder::new(
+            BufWriter::new(file),
+            width,
+            height,
+            &[options.dead_color, options.alive_color],
+        )
+        .map_err(|e| write_error(&options.output, e))?;
+
+        Ok(Self {
+            encoder,
+            output: options.output.clone(),
+            region,
+            cell_size: options.cell_size,
+            frame_delay: options.frame_delay,
+            last_generation: None,
+            frames: 0,
+        })
+    }
+
+    pub fn output(&self) -> &Path {
+        &self.output
+    }
+
+    /// Adds `board` as the next frame, unless `generation` was just
+    /// recorded.
+    pub fn record<B: GameOfLifeBoard>(&mut self, generation: u64, board: &B) -> Result<(), String> {
+        if self.last_generation == Some(generation) {
+            return Ok(());
+        }
+
+        let pixels = rasterize(board, self.region, self.cell_size);
+        self.encoder
+            .write_frame(&pixels, self.frame_delay)
+            .map_err(|e| write_error(&self.output, e))?;
+
+        self.last_generation = Some(generation);
+        self.frames += 1;
+        Ok(())
+    }
+
+    /// Finishes the file, returning the number of frames recorded.
+    pub fn finish(self) -> Result<u64, String> {
+        self.encoder
+            .finish()
+            .map_err(|e| write_error(&self.output, e))?;
+        Ok(self.frames)
+    }
+}
+
+fn write_error(path: &Path, e: std::io::Error) -> String {
+    format!("could not write `{}`: {e}", path.display())
+}
+
+/// The palette indices of the pixels of `region` of `board`, with every cell
+/// `cell_size` pixels across.
+fn rasterize<B: GameOfLifeBoard>(board: &B, region: Region, cell_size: usize) -> Vec<u8> {
+    let width = region.width * cell_size;
+    let mut pixels = vec![0; width * region.height * cell_size];
+
+    for (x, y) in board.alive_cells() {
+        let (column, row) = (x - region.x, y - region.y);
+        if !(0..region.width as isize).contains(&column)
+            || !(0..region.height as isize).contains(&row)
+        {
+            continue;
+        }
+
+        let left = column as usize * cell_size;
+        for pixel_row in row as usize * cell_size..(row as usize + 1) * cell_size {
+            pixels[pixel_row * width + left..][..cell_size].fill(1);
+        }
+    }
+
+    pixels
+}
+
+/// Records `options.gif.frames` updates of `board` without opening a
+/// window, advancing it by `2^options.step_log2` generations every update.
+pub fn run<B: GameOfLifeBoard>(board: B, rule: Rule, options: &Options) -> Result<(), String> {
+    let gif = &options.gif;
+    let frames = gif.frames.unwrap_or(1).max(1);
+    let advance = |board: &B| board.step_pow2(&rule, options.step_log2);
+
+    let region = match (gif.region, board.size()) {
+        (Some(region), _) => region,
+        (None, Some((width, height))) => Region {
+            x: 0,
+            y: 0,
+            width,
+            height,
+        },
+        // Unbounded boards are run through once first to find out how far
+        // the pattern reaches.
+        (None, None) => {
+            let mut region = Region::around(&board);
+            let mut later = board.clone();
+            for _ in 1..frames {
+                later = advance(&later);
+                region = match (region, Region::around(&later)) {
+                    (Some(region), Some(other)) => Some(region.union(other)),
+                    (region, other) => region.or(other),
+                };
+            }
+
+            region.unwrap_or(Region {
+                x: 0,
+                y: 0,
+                width: 1,
+                height: 1,
+            })
+        }
+    };
+
+    let mut recorder = Recorder::create(gif, region)?;
+    let mut board = board;
+    for frame in 0..frames {
+        if frame > 0 {
+            board = advance(&board);
+        }
+        recorder.record(frame << options.step_log2, &board)?;
+    }
+
+    let frames = recorder.finish()?;
+    println!(
+        "Recorded {frames} frames of {}x{} cells to `{}`",
+        region.width,
+        region.height,
+        gif.output.display()
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{hashlife::HashLifeBoard, infinite_board::InfiniteBoard};
+
+    #[test]
+    fn test_region() {
+        let glider = InfiniteBoard::from_cells(&[&[0, 1, 0], &[0, 0, 1], &[1, 1, 1]]);
+        let region = Region::around(&glider).unwrap();
+        assert_eq!(
+            region,
+            Region {
+                x: 0,
+                y: 0,
+                width: 3,
+                height: 3,
+            }
+        );
+        assert_eq!(Region::around(&InfiniteBoard::new()), None);
+        assert_eq!(
+            Region::around(&HashLifeBoard::from_alive_cells(
+                glider.alive_cells().clone()
+            )),
+            Some(region)
+        );
+
+        let moved = Region::around(&glider.step(&Rule::CONWAY, 4)).unwrap();
+        assert_eq!(
+            region.union(moved),
+            Region {
+                x: 0,
+                y: 0,
+                width: 4,
+                height: 4,
+            }
+        );
+    }
+
+    #[test]
+    fn test_create_too_large() {
+        let options = GifOptions {
+            cell_size: usize::MAX,
+            ..GifOptions::default()
+        };
+        let region = Region {
+            x: 0,
+            y: 0,
+            width: 2,
+            height: 2,
+        };
+
+        assert_eq!(
+            Recorder::create(&options, region).err(),
+            Some(format!(
+                "a 2x2 region with {} pixels per cell doesn't fit in a GIF, which is at most \
+                     65535 pixels across",
+                usize::MAX
+            ))
+        );
+    }
+
+    #[test]
+    fn test_rasterize() {
+        let board = InfiniteBoard::from_cells(&[&[1, 0], &[0, 1]]);
+        let region = Region {
+            x: 1,
+            y: 0,
+            width: 2,
+            height: 2,
+        };
+
+        #[rustfmt::skip]
+        assert_eq!(
+            rasterize(&board, region, 2),
+            [
+                0, 0, 0, 0,
+                0, 0, 0, 0,
+                1, 1, 0, 0,
+                1, 1, 0, 0,
+            ]
+        );
+    }
+}
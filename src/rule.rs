@@ -0,0 +1,262 @@
+use std::{error::Error, fmt, str::FromStr};
+
+/// An outer-totalistic rule, which decides the next state of a cell from
+/// its current state and the number of live neighbours around it.
+///
+/// Rules are usually written as rulestrings such as `B3/S23` (Conway's
+/// Game of Life) or in the older survival/birth notation `23/3`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rule {
+    birth: u16,
+    survival: u16,
+}
+
+impl Rule {
+    /// Conway's Game of Life, `B3/S23`.
+    pub const CONWAY: Self = Self::from_masks(1 << 3, (1 << 2) | (1 << 3));
+
+    const fn from_masks(birth: u16, survival: u16) -> Self {
+        Self { birth, survival }
+    }
+
+    /// Creates a rule from the neighbour counts a dead cell is born on and
+    /// the neighbour counts a live cell survives on.
+    pub fn new(birth: &[u8], survival: &[u8]) -> Result<Self, ParseRuleError> {
+        let birth = to_mask(birth.iter().copied())?;
+        let survival = to_mask(survival.iter().copied())?;
+
+        if birth & 1 != 0 {
+            return Err(ParseRuleError::UnsupportedBirthOnZero);
+        }
+
+        Ok(Self::from_masks(birth, survival))
+    }
+
+    /// Returns `true` if a dead cell with `neighbour_count` live neighbours
+    /// becomes alive.
+    #[inline]
+    pub fn is_born(&self, neighbour_count: i8) -> bool {
+        self.birth & (1 << neighbour_count) != 0
+    }
+
+    /// Returns `true` if a live cell with `neighbour_count` live neighbours
+    /// stays alive.
+    #[inline]
+    pub fn survives(&self, neighbour_count: i8) -> bool {
+        self.survival & (1 << neighbour_count) != 0
+    }
+
+    pub fn birth_counts(&self) -> impl Iterator<Item = u8> + '_ {
+        (0..=8).filter(|n| self.is_born(*n as i8))
+    }
+
+    pub fn survival_counts(&self) -> impl Iterator<Item = u8> + '_ {
+        (0..=8).filter(|n| self.survives(*n as i8))
+    }
+}
+
+impl Default for Rule {
+    fn default() -> Self {
+        Self::CONWAY
+    }
+}
+
+impl FromStr for Rule {
+    type Err = ParseRuleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+
+        if s.is_empty() {
+            return Err(ParseRuleError::Empty);
+        }
+
+        let mut parts = s.split('/');
+        let (Some(first), Some(second), None) = (parts.next(), parts.next(), parts.next()) else {
+            return Err(ParseRuleError::MissingSeparator);
+        };
+
+        let prefix = |part: &str| part.chars().next().map(|c| c.to_ascii_uppercase());
+
+        let (birth, survival) = match (prefix(first), prefix(second)) {
+            (Some('B'), Some('S')) => (&first[1..], &second[1..]),
+            (Some('S'), Some('B')) => (&second[1..], &first[1..]),
+            // Survival/birth notation, e.g. `23/3`, has no prefixes at all.
+            (a, b)
+                if a.is_none_or(|c| c.is_ascii_digit()) && b.is_none_or(|c| c.is_ascii_digit()) =>
+            {
+                (second, first)
+            }
+            _ => return Err(ParseRuleError::InvalidNotation),
+        };
+
+        Self::new(&parse_counts(birth)?, &parse_counts(survival)?)
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("B")?;
+        for n in self.birth_counts() {
+            write!(f, "{n}")?;
+        }
+
+        f.write_str("/S")?;
+        for n in self.survival_counts() {
+            write!(f, "{n}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Rule({self})")
+    }
+}
+
+fn parse_counts(part: &str) -> Result<Vec<u8>, ParseRuleError> {
+    part.chars()
+        .map(|c| match c.to_digit(10) {
+            Some(n) => Ok(n as u8),
+            None => Err(ParseRuleError::InvalidCharacter(c)),
+        })
+        .collect()
+}
+
+fn to_mask(counts: impl Iterator<Item = u8>) -> Result<u16, ParseRuleError> {
+    let mut mask = 0;
+
+    for n in counts {
+        if n > 8 {
+            return Err(ParseRuleError::InvalidNeighbourCount(n));
+        }
+
+        if mask & (1 << n) != 0 {
+            return Err(ParseRuleError::DuplicateNeighbourCount(n));
+        }
+
+        mask |= 1 << n;
+    }
+
+    Ok(mask)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseRuleError {
+    Empty,
+    MissingSeparator,
+    InvalidNotation,
+    InvalidCharacter(char),
+    InvalidNeighbourCount(u8),
+    DuplicateNeighbourCount(u8),
+    UnsupportedBirthOnZero,
+}
+
+impl fmt::Display for ParseRuleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "rule is empty"),
+            Self::MissingSeparator => {
+                write!(f, "rule must have exactly one `/`, e.g. `B3/S23` or `23/3`")
+            }
+            Self::InvalidNotation => write!(
+                f,
+                "rule must either be in `B.../S...` or `.../...` notation, not a mix of both"
+            ),
+            Self::InvalidCharacter(c) => write!(f, "unexpected character `{c}` in rule"),
+            Self::InvalidNeighbourCount(n) => {
+                write!(f, "neighbour count {n} is out of range, must be 0 to 8")
+            }
+            Self::DuplicateNeighbourCount(n) => {
+                write!(f, "neighbour count {n} is listed more than once")
+            }
+            Self::UnsupportedBirthOnZero => {
+                write!(f, "rules with `B0` are not supported")
+            }
+        }
+    }
+}
+
+impl Error for ParseRuleError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_conway() {
+        assert_eq!("B3/S23".parse(), Ok(Rule::CONWAY));
+        assert_eq!("b3/s23".parse(), Ok(Rule::CONWAY));
+        assert_eq!("S23/B3".parse(), Ok(Rule::CONWAY));
+        assert_eq!("23/3".parse(), Ok(Rule::CONWAY));
+        assert_eq!(" B3/S23\n".parse(), Ok(Rule::CONWAY));
+        assert_eq!(Rule::default(), Rule::CONWAY);
+    }
+
+    #[test]
+    fn test_parse_known_rules() {
+        let highlife: Rule = "B36/S23".parse().unwrap();
+        assert_eq!(highlife, "23/36".parse().unwrap());
+        assert_eq!(highlife.birth_counts().collect::<Vec<_>>(), &[3, 6]);
+        assert_eq!(highlife.survival_counts().collect::<Vec<_>>(), &[2, 3]);
+
+        let seeds: Rule = "B2/S".parse().unwrap();
+        assert_eq!(seeds, "/2".parse().unwrap());
+        assert_eq!(seeds.birth_counts().collect::<Vec<_>>(), &[2]);
+        assert_eq!(seeds.survival_counts().count(), 0);
+
+        let day_and_night: Rule = "B3678/S34678".parse().unwrap();
+        assert_eq!(day_and_night, "34678/3678".parse().unwrap());
+        assert!(day_and_night.is_born(8));
+        assert!(!day_and_night.survives(5));
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Rule::CONWAY.to_string(), "B3/S23");
+        assert_eq!(
+            "34678/3678".parse::<Rule>().unwrap().to_string(),
+            "B3678/S34678"
+        );
+        assert_eq!("B2/S".parse::<Rule>().unwrap().to_string(), "B2/S");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!("".parse::<Rule>(), Err(ParseRuleError::Empty));
+        assert_eq!(
+            "B3S23".parse::<Rule>(),
+            Err(ParseRuleError::MissingSeparator)
+        );
+        assert_eq!(
+            "B3/S2/3".parse::<Rule>(),
+            Err(ParseRuleError::MissingSeparator)
+        );
+        assert_eq!(
+            "B3/23".parse::<Rule>(),
+            Err(ParseRuleError::InvalidNotation)
+        );
+        assert_eq!(
+            "B3/B23".parse::<Rule>(),
+            Err(ParseRuleError::InvalidNotation)
+        );
+        assert_eq!(
+            "B3/S2x".parse::<Rule>(),
+            Err(ParseRuleError::InvalidCharacter('x'))
+        );
+        assert_eq!(
+            "B39/S23".parse::<Rule>(),
+            Err(ParseRuleError::InvalidNeighbourCount(9))
+        );
+        assert_eq!(
+            "B33/S23".parse::<Rule>(),
+            Err(ParseRuleError::DuplicateNeighbourCount(3))
+        );
+        assert_eq!(
+            "B03/S23".parse::<Rule>(),
+            Err(ParseRuleError::UnsupportedBirthOnZero)
+        );
+    }
+}
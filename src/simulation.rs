@@ -0,0 +1,576 @@
+use std::{
+    sync::{
+        mpsc::{Receiver, RecvTimeoutError},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    census::Census,
+    game_context::{CellEdit, GameOfLifeBoard},
+    history::History,
+    infinite_board::InfiniteBoard,
+    period::{PeriodDetector, Periodicity},
+    rule::Rule,
+    timeline::Timeline,
+};
+
+/// Messages sent from the viewer to the simulation thread.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Control {
+    Edit(Vec<CellEdit>),
+    /// Ends the edit in progress, so it can be undone in one go.
+    EndEdit,
+    SetPaused(bool),
+    /// Advances the board by this many generations and pauses.
+    Step(u64),
+    SetSpeed(Speed),
+    /// Undoes the last edit or generation and pauses.
+    Undo,
+    /// Redoes the last undone edit or generation and pauses.
+    Redo,
+    /// Jumps to a generation on the timeline and pauses.
+    Seek(u64),
+    /// Prints a census of the objects on the board.
+    Census,
+}
+
+/// The state of the simulation shown by the viewer.
+#[derive(Clone)]
+pub struct Snapshot<B> {
+    pub board: B,
+    pub generation: u64,
+    pub population: usize,
+    /// The cells born and died in the last update, or `None` if the update
+    /// was too big to count them.
+    pub births: Option<usize>,
+    pub deaths: Option<usize>,
+    /// The measured rate the board is being advanced at.
+    pub generations_per_second: f64,
+    /// How the board repeats, once it has been simulated into a state it
+    /// was in before.
+    pub periodicity: Option<Periodicity>,
+    /// The range of generations which can be sought to.
+    pub earliest_generation: u64,
+    pub latest_generation: u64,
+}
+
+/// How fast the simulation runs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Speed {
+    /// `None` runs updates back to back as fast as possible.
+    pub generations_per_second: Option<f64>,
+    /// Every update advances the board by `2^step_log2` generations.
+    pub step_log2: u32,
+}
+
+impl Speed {
+    /// The speeds stepped through by [`Speed::faster`] and [`Speed::slower`]
+    /// before going as fast as possible.
+    const STEPS: [f64; 8] = [1.0, 2.0, 5.0, 10.0, 20.0, 30.0, 60.0, 120.0];
+
+    /// The next speed up, which once there's no limit on updates per second
+    /// starts doubling the generations per update, up to
+    /// `2^max_step_log2`.
+    pub fn faster(self, max_step_log2: u32) -> Self {
+        match self.generations_per_second {
+            Some(current) => Self {
+                generations_per_second: Self::STEPS.into_iter().find(|&gps| gps > current),
+                ..self
+            },
+            None => Self {
+                step_log2: (self.step_log2 + 1).min(max_step_log2),
+                ..self
+            },
+        }
+    }
+
+    /// The inverse of [`Speed::faster`], down to one generation per second.
+    pub fn slower(self) -> Self {
+        match self.generations_per_second {
+            None if self.step_log2 > 0 => Self {
+                step_log2: self.step_log2 - 1,
+                ..self
+            },
+            None => Self {
+                generations_per_second: Self::STEPS.last().copied(),
+                ..self
+            },
+            Some(current) => Self {
+                generations_per_second: Some(
+                    Self::STEPS
+                        .into_iter()
+                        .rev()
+                        .find(|&gps| gps < current)
+                        .unwrap_or(current),
+                ),
+                ..self
+            },
+        }
+    }
+
+    /// The time between the start of two updates.
+    pub fn interval(self) -> Duration {
+        self.generations_per_second
+            .map_or(Duration::ZERO, |gps| Duration::from_secs_f64(1.0 / gps))
+    }
+}
+
+/// The state owned by the simulation thread.
+pub struct Simulation<B> {
+    board: B,
+    rule: Rule,
+    speed: Speed,
+    paused: bool,
+    generation: u64,
+    history: History,
+    timeline: Timeline<B>,
+    /// Set when the board was edited since the timeline was last told
+    /// about it.
+    edited: bool,
+    births: Option<usize>,
+    deaths: Option<usize>,
+    /// The size of the last update, when it was too big to be kept in the
+    /// history and is undone and redone by seeking on the timeline instead.
+    unrecorded_step: Option<u64>,
+    /// The generations advanced since `rate_measured`, which are turned into
+    /// `generations_per_second` a couple of times a second.
+    generations_since_measured: u64,
+    rate_measured: Instant,
+    generations_per_second: f64,
+    period_detector: PeriodDetector,
+}
+
+impl<B: GameOfLifeBoard> Simulation<B> {
+    pub fn new(board: B, rule: Rule, speed: Speed, paused: bool) -> Self {
+        Self {
+            timeline: Timeline::new(&board),
+            board,
+            rule,
+            speed,
+            paused,
+            generation: 0,
+            history: History::new(),
+            edited: false,
+            births: Some(0),
+            deaths: Some(0),
+            unrecorded_step: None,
+            generations_since_measured: 0,
+            rate_measured: Instant::now(),
+            generations_per_second: 0.0,
+            period_detector: PeriodDetector::new(),
+        }
+    }
+
+    pub fn snapshot(&self) -> Snapshot<B> {
+        Snapshot {
+            // The viewer reads the board while the next update is simulated.
+            board: self.board.detached(),
+            generation: self.generation,
+            population: self.board.population(),
+            births: self.births,
+            deaths: self.deaths,
+            generations_per_second: self.generations_per_second,
+            periodicity: self.period_detector.periodicity(),
+            earliest_generation: self.timeline.earliest_generation(),
+            latest_generation: if self.edited {
+                self.generation
+            } else {
+                self.timeline.latest_generation()
+            },
+        }
+    }
+
+    /// Simulates the board until `controls` is disconnected, copying every
+    /// new state of the board into `shared`.
+    pub fn run(mut self, controls: Receiver<Control>, shared: Arc<Mutex<Snapshot<B>>>) {
+        let mut last_update = Instant::now();
+
+        loop {
+            // Wait for a message until the next update is due, or forever if
+            // paused.
+            let control = if self.paused {
+                // Nothing is being generated while paused.
+                if self.generations_per_second != 0.0 {
+                    self.measure_rate(true);
+                    *shared.lock().unwrap() = self.snapshot();
+                }
+
+                controls.recv().map_err(|_| RecvTimeoutError::Disconnected)
+            } else {
+                let next_update = last_update + self.speed.interval();
+                controls.recv_timeout(next_update.saturating_duration_since(Instant::now()))
+            };
+
+            match control {
+                Ok(control) => {
+                    if self.handle(control) {
+                        *shared.lock().unwrap() = self.snapshot();
+                    }
+                    continue;
+                }
+                Err(RecvTimeoutError::Disconnected) => return,
+                Err(RecvTimeoutError::Timeout) => (),
+            }
+
+            last_update = Instant::now();
+            self.advance(1 << self.speed.step_log2);
+            *shared.lock().unwrap() = self.snapshot();
+        }
+    }
+
+    /// Applies `control`, returning `true` if the board changed.
+    fn handle(&mut self, control: Control) -> bool {
+        match control {
+            Control::Edit(edits) => {
+                self.history.edit(&mut self.board, &edits);
+                self.edited = true;
+                self.period_detector.reset();
+                true
+            }
+            Control::EndEdit => {
+                self.history.end_edit(&self.board);
+                self.update_timeline();
+                false
+            }
+            Control::SetPaused(paused) => {
+                if self.paused && !paused {
+                    self.measure_rate(true);
+                }
+                self.paused = paused;
+                false
+            }
+            Control::Step(generations) => {
+                self.paused = true;
+                self.advance(generations);
+                true
+            }
+            Control::SetSpeed(speed) => {
+                self.speed = speed;
+                false
+            }
+            Control::Undo => {
+                self.paused = true;
+                (self.births, self.deaths) = (Some(0), Some(0));
+                self.period_detector.reset();
+                let generations = match self.history.undo(&mut self.board) {
+                    None => self.seek_by_unrecorded_step(false),
+                    generations => generations,
+                };
+                self.generation -= generations.unwrap_or(0);
+                self.edited |= generations == Some(0);
+                generations.is_some()
+            }
+            Control::Redo => {
+                self.paused = true;
+                (self.births, self.deaths) = (Some(0), Some(0));
+                self.period_detector.reset();
+                let generations = match self.history.redo(&mut self.board) {
+                    None => self.seek_by_unrecorded_step(true),
+                    generations => generations,
+                };
+                self.generation += generations.unwrap_or(0);
+                self.edited |= generations == Some(0);
+                generations.is_some()
+            }
+            Control::Seek(generation) => {
+                self.paused = true;
+                (self.births, self.deaths) = (Some(0), Some(0));
+                self.period_detector.reset();
+                self.update_timeline();
+                (self.generation, self.board) = self.timeline.seek(generation, &self.rule);
+                self.history.clear();
+                true
+            }
+            Control::Census => {
+                // Objects can take a long time to settle, so the census is
+                // taken on a copy of the board without holding up the
+                // simulation.
+                let board = InfiniteBoard::from_alive_cells(self.board.alive_cells());
+                let (generation, rule) = (self.generation, self.rule);
+                thread::spawn(move || {
+                    let census = Census::take(&board, &rule);
+                    println!("Census of generation {generation}: {census}");
+                });
+                false
+            }
+        }
+    }
+
+    /// Tells the timeline about edits made to the board since it was last
+    /// updated, which become the new end of the timeline.
+    fn update_timeline(&mut self) {
+        if self.edited {
+            self.timeline.rewrite(self.generation, &self.board);
+            self.edited = false;
+        }
+    }
+
+    /// Moves the board along the timeline by the size of the last update
+    /// which wasn't kept in the history, returning how many generations it
+    /// moved by. The generation is left for the caller to update.
+    fn seek_by_unrecorded_step(&mut self, forwards: bool) -> Option<u64> {
+        let step = self.unrecorded_step?;
+        let target = if forwards {
+            self.generation
+                .checked_add(step)
+                .filter(|&target| target <= self.timeline.latest_generation())?
+        } else {
+            self.generation
+                .checked_sub(step)
+                .filter(|&target| target >= self.timeline.earliest_generation())?
+        };
+
+        self.update_timeline();
+        (_, self.board) = self.timeline.seek(target, &self.rule);
+        self.history.clear();
+
+        Some(step)
+    }
+
+    /// Advances the board by `generations` generations, recording the
+    /// change in the history. Updates are cut down to the largest the board
+    /// can make in one go, so the simulation keeps responding.
+    ///
+    /// Working out which cells changed means looking at every one of them,
+    /// so big steps and boards which step without doing that aren't kept
+    /// in the history, and are undone by seeking on the timeline instead.
+    /// For the same reason, only single generation steps are checked for
+    /// repeats.
+    fn advance(&mut self, generations: u64) {
+        let generations = generations
+            .min(1 << self.board.max_step_log2())
+            .min(u64::MAX - self.generation);
+        if generations == 0 {
+            return;
+        }
+
+        self.update_timeline();
+
+        let observe = generations == 1;
+        if observe && self.period_detector.is_empty() {
+            self.period_detector.observe(self.generation, &self.board);
+        }
+
+        let next = self.board.step(&self.rule, generations);
+
+        if generations == 1 && !self.board.steps_without_visiting_cells() {
+            let (births, deaths) = self.history.record_step(&self.board, &next, generations);
+            (self.births, self.deaths) = (Some(births), Some(deaths));
+            self.unrecorded_step = None;
+        } else {
+            self.history.clear();
+            (self.births, self.deaths) = (None, None);
+            self.unrecorded_step = Some(generations);
+        }
+        self.timeline
+            .record(self.generation, self.generation + generations, &next);
+        self.board = next;
+        self.generation += generations;
+        if observe {
+            self.period_detector.observe(self.generation, &self.board);
+        }
+
+        self.generations_since_measured += generations;
+        self.measure_rate(false);
+    }
+
+    /// Updates `generations_per_second` if enough time has passed since it
+    /// was last measured, or straight away if `reset`.
+    fn measure_rate(&mut self, reset: bool) {
+        let elapsed = self.rate_measured.elapsed();
+
+        if reset || elapsed >= Duration::from_millis(500) {
+            self.generations_per_second = if reset {
+                0.0
+            } else {
+                self.generations_since_measured as f64 / elapsed.as_secs_f64()
+            };
+            self.generations_since_measured = 0;
+            self.rate_measured = Instant::now();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::mpsc, thread};
+
+    use super::*;
+    use crate::{
+        game_context::MAX_TICKED_STEP_LOG2,
+        hashlife::{HashLifeBoard, MAX_STEP_LOG2},
+        infinite_board::InfiniteBoard,
+    };
+
+    fn speed(generations_per_second: Option<f64>, step_log2: u32) -> Speed {
+        Speed {
+            generations_per_second,
+            step_log2,
+        }
+    }
+
+    #[test]
+    fn test_faster_and_slower() {
+        let mut current = speed(Some(1.0), 0);
+        let mut speeds = vec![current];
+
+        for _ in 0..10 {
+            current = current.faster(MAX_STEP_LOG2);
+            speeds.push(current);
+        }
+
+        assert_eq!(speeds[1], speed(Some(2.0), 0));
+        assert_eq!(speeds[7], speed(Some(120.0), 0));
+        assert_eq!(speeds[8], speed(None, 0));
+        assert_eq!(speeds[10], speed(None, 2));
+
+        for expected in speeds.into_iter().rev() {
+            assert_eq!(current, expected);
+            current = current.slower();
+        }
+
+        assert_eq!(current, speed(Some(1.0), 0));
+        assert_eq!(speed(Some(0.5), 0).slower(), speed(Some(0.5), 0));
+        assert_eq!(
+            speed(Some(45.0), 3).faster(MAX_STEP_LOG2),
+            speed(Some(60.0), 3)
+        );
+        assert_eq!(
+            speed(None, MAX_STEP_LOG2).faster(MAX_STEP_LOG2),
+            speed(None, MAX_STEP_LOG2)
+        );
+        assert_eq!(speed(None, 4).faster(4), speed(None, 4));
+    }
+
+    /// Runs a simulation of a blinker through `controls`, returning the last
+    /// snapshot it shared.
+    fn simulate(controls: Vec<Control>) -> Snapshot<InfiniteBoard> {
+        let blinker = InfiniteBoard::from_alive_cells([(0, 0), (1, 0), (2, 0)]);
+        simulate_board(blinker, controls)
+    }
+
+    fn simulate_board<B: GameOfLifeBoard + Send + 'static>(
+        board: B,
+        controls: Vec<Control>,
+    ) -> Snapshot<B> {
+        let simulation = Simulation::new(board, Rule::CONWAY, speed(None, 0), true);
+        let shared = Arc::new(Mutex::new(simulation.snapshot()));
+        let (sender, receiver) = mpsc::channel();
+
+        let handle = thread::spawn({
+            let shared = shared.clone();
+            move || simulation.run(receiver, shared)
+        });
+
+        for control in controls {
+            sender.send(control).unwrap();
+        }
+        drop(sender);
+        handle.join().unwrap();
+
+        let snapshot = shared.lock().unwrap().clone();
+        snapshot
+    }
+
+    const EDIT: CellEdit = CellEdit {
+        x: 5,
+        y: 5,
+        alive: true,
+    };
+
+    #[test]
+    fn test_controls() {
+        let snapshot = simulate(vec![
+            Control::Step(3),
+            Control::Edit(vec![EDIT]),
+            Control::Undo,
+            Control::Undo,
+            Control::Redo,
+            Control::Edit(vec![EDIT]),
+        ]);
+
+        // The lone cell was added after stepping, so it hasn't died yet.
+        let expected = InfiniteBoard::from_alive_cells([(1, -1), (1, 0), (1, 1), (5, 5)]);
+        assert_eq!(snapshot.board, expected);
+        assert_eq!(snapshot.generation, 3);
+        assert_eq!(snapshot.latest_generation, 3);
+    }
+
+    #[test]
+    fn test_undo_big_steps() {
+        let vertical = InfiniteBoard::from_alive_cells([(1, -1), (1, 0), (1, 1)]);
+
+        let snapshot = simulate(vec![Control::Step(1)]);
+        assert_eq!((snapshot.births, snapshot.deaths), (Some(2), Some(2)));
+
+        // Big steps aren't diffed, so they are undone on the timeline.
+        let snapshot = simulate(vec![Control::Step(1), Control::Step(4)]);
+        assert_eq!((snapshot.births, snapshot.deaths), (None, None));
+
+        let snapshot = simulate(vec![Control::Step(1), Control::Step(4), Control::Undo]);
+        assert_eq!(snapshot.board, vertical);
+        assert_eq!(snapshot.generation, 1);
+
+        let snapshot = simulate(vec![
+            Control::Step(1),
+            Control::Step(4),
+            Control::Undo,
+            Control::Undo,
+            Control::Redo,
+            Control::Redo,
+        ]);
+        assert_eq!(snapshot.board, vertical);
+        assert_eq!(snapshot.generation, 5);
+        assert_eq!(snapshot.latest_generation, 5);
+    }
+
+    #[test]
+    fn test_huge_steps() {
+        let snapshot = simulate(vec![Control::Step(u64::MAX)]);
+        assert_eq!(snapshot.generation, 1 << MAX_TICKED_STEP_LOG2);
+
+        let blinker = HashLifeBoard::from_alive_cells([(0, 0), (1, 0), (2, 0)]);
+        let snapshot = simulate_board(blinker.clone(), vec![Control::Step(u64::MAX)]);
+        assert_eq!(snapshot.generation, 1 << MAX_STEP_LOG2);
+        assert_eq!(snapshot.board, blinker);
+    }
+
+    #[test]
+    fn test_seek() {
+        let vertical = [(1, -1), (1, 0), (1, 1)];
+
+        let snapshot = simulate(vec![
+            Control::Step(3),
+            Control::Edit(vec![EDIT]),
+            Control::EndEdit,
+            Control::Step(2),
+            Control::Seek(3),
+        ]);
+
+        let mut expected = vertical.to_vec();
+        expected.push((5, 5));
+        assert_eq!(snapshot.board, InfiniteBoard::from_alive_cells(expected));
+        assert_eq!(snapshot.generation, 3);
+        assert_eq!(snapshot.latest_generation, 5);
+
+        // Going back to before the edit and editing again throws away the
+        // rest of the timeline.
+        let snapshot = simulate(vec![
+            Control::Step(3),
+            Control::Edit(vec![EDIT]),
+            Control::Step(2),
+            Control::Seek(1),
+            Control::Edit(vec![CellEdit {
+                alive: false,
+                ..EDIT
+            }]),
+        ]);
+
+        assert_eq!(snapshot.board, InfiniteBoard::from_alive_cells(vertical));
+        assert_eq!(snapshot.generation, 1);
+        assert_eq!(snapshot.latest_generation, 1);
+    }
+}
@@ -0,0 +1,457 @@
+//! A front end drawing the board in the terminal with ANSI escape codes, so
+//! the simulation can be watched without a window, for example over SSH.
+
+use std::{
+    fmt::Write as _,
+    io::{self, Read, Write},
+    process::{Command, Stdio},
+    sync::mpsc::{self, RecvTimeoutError},
+    thread,
+    time::{Duration, Instant},
+};
+
+use colored::Colorize;
+
+use crate::{
+    cli::Options,
+    game_context::{GameOfLifeBoard, ADVANCE_GENERATIONS},
+    rule::Rule,
+    simulation::Control,
+};
+
+/// How long to wait for input between frames.
+const FRAME_INTERVAL: Duration = Duration::from_millis(33);
+
+/// How often the size of the terminal is looked up again.
+const RESIZE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Every dot covers at most `2^MAX_SCALE_LOG2` cells across.
+const MAX_SCALE_LOG2: u32 = 6;
+
+/// The number of dots the arrow keys move the view by.
+const PAN_DOTS: isize = 4;
+
+/// The size assumed when the terminal can't be asked for it.
+const DEFAULT_SIZE: (usize, usize) = (80, 24);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Key {
+    Char(char),
+    Up,
+    Down,
+    Left,
+    Right,
+    Escape,
+    /// Ctrl+C, which arrives as a key since raw mode turns off signals.
+    Interrupt,
+}
+
+/// The characters cells are drawn with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Glyphs {
+    /// `▀`, `▄` and `█`, with two dots to a character.
+    HalfBlocks,
+    /// Braille patterns, with eight dots to a character.
+    Braille,
+}
+
+impl Glyphs {
+    /// The number of dots across and down every character.
+    fn dots(self) -> (usize, usize) {
+        match self {
+            Self::HalfBlocks => (1, 2),
+            Self::Braille => (2, 4),
+        }
+    }
+
+    /// The character showing the dots of one character cell, where
+    /// `is_set(x, y)` tells whether the dot at `(x, y)` is lit.
+    fn glyph(self, is_set: impl Fn(usize, usize) -> bool) -> char {
+        match self {
+            Self::HalfBlocks => match (is_set(0, 0), is_set(0, 1)) {
+                (false, false) => ' ',
+                (true, false) => '▀',
+                (false, true) => '▄',
+                (true, true) => '█',
+            },
+            Self::Braille => {
+                // The bit of every dot, which isn't in reading order since
+                // the bottom row was added to braille later.
+                const BITS: [[u32; 2]; 4] =
+                    [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+                let bits = (0..4)
+                    .flat_map(|y| (0..2).map(move |x| (x, y)))
+                    .filter(|&(x, y)| is_set(x, y))
+                    .fold(0, |bits, (x, y)| bits | BITS[y][x]);
+
+                char::from_u32(0x2800 + bits).unwrap_or(' ')
+            }
+        }
+    }
+}
+
+/// Puts the terminal in raw mode on the alternate screen until dropped.
+struct RawMode {
+    saved: String,
+}
+
+impl RawMode {
+    fn enter() -> Result<Self, String> {
+        let saved = stty(&["-g"])
+            .map_err(|_| "the terminal front end needs stdin to be a terminal".to_string())?;
+        stty(&["raw", "-echo"])?;
+
+        print!("\x1b[?1049h\x1b[?25l");
+        let _ = io::stdout().flush();
+
+        Ok(Self {
+            saved: saved.trim().to_string(),
+        })
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        print!("\x1b[?25h\x1b[?1049l");
+        let _ = io::stdout().flush();
+        let _ = stty(&[&self.saved]);
+    }
+}
+
+/// Runs `stty` on the terminal, returning what it printed.
+fn stty(args: &[&str]) -> Result<String, String> {
+    let output = Command::new("stty")
+        .args(args)
+        .stdin(Stdio::inherit())
+        .output()
+        .map_err(|e| format!("could not run `stty`: {e}"))?;
+
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// The number of columns and rows of the terminal.
+fn terminal_size() -> (usize, usize) {
+    let size = stty(&["size"]).ok().and_then(|size| {
+        let (rows, columns) = size.trim().split_once(' ')?;
+        Some((columns.parse().ok()?, rows.parse().ok()?))
+    });
+
+    match size {
+        Some((columns, rows)) if columns > 0 && rows > 0 => (columns, rows),
+        _ => DEFAULT_SIZE,
+    }
+}
+
+/// Splits the bytes read from the terminal into keys, ignoring escape
+/// sequences for keys without a binding.
+fn parse_keys(bytes: &[u8]) -> Vec<Key> {
+    let mut keys = Vec::new();
+    let mut rest = bytes;
+
+    while let Some((&byte, tail)) = rest.split_first() {
+        rest = tail;
+
+        match byte {
+            0x1b => match rest {
+                [b'[' | b'O', code, tail @ ..] => {
+                    rest = tail;
+                    keys.extend(match code {
+                        b'A' => Some(Key::Up),
+                        b'B' => Some(Key::Down),
+                        b'C' => Some(Key::Right),
+                        b'D' => Some(Key::Left),
+                        _ => None,
+                    });
+
+                    // Skips the rest of longer sequences, like `ESC [ 5 ~`.
+                    if !code.is_ascii_alphabetic() && *code != b'~' {
+                        let end = rest
+                            .iter()
+                            .position(|byte| byte.is_ascii_alphabetic() || *byte == b'~')
+                            .map_or(rest.len(), |end| end + 1);
+                        rest = &rest[end..];
+                    }
+                }
+                _ => keys.push(Key::Escape),
+            },
+            3 => keys.push(Key::Interrupt),
+            byte if byte.is_ascii() && !byte.is_ascii_control() => {
+                keys.push(Key::Char(char::from(byte)))
+            }
+            _ => (),
+        }
+    }
+
+    keys
+}
+
+/// What part of the board is shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct View {
+    /// The cell in the middle of the terminal.
+    center: (isize, isize),
+    /// Every dot covers `2^scale_log2` by `2^scale_log2` cells.
+    scale_log2: u32,
+}
+
+impl View {
+    /// The cell at the top left corner of a grid of `width` by `height`
+    /// dots.
+    fn top_left(&self, width: usize, height: usize) -> (isize, isize) {
+        let (x, y) = self.center;
+        (
+            x - ((width as isize) << self.scale_log2) / 2,
+            y - ((height as isize) << self.scale_log2) / 2,
+        )
+    }
+
+    fn pan(&mut self, dx: isize, dy: isize) {
+        let (x, y) = self.center;
+        self.center = (
+            x + ((dx * PAN_DOTS) << self.scale_log2),
+            y + ((dy * PAN_DOTS) << self.scale_log2),
+        );
+    }
+}
+
+/// Lights every dot of a `width` by `height` grid covering a live cell.
+fn rasterize<B: GameOfLifeBoard>(board: &B, view: &View, width: usize, height: usize) -> Vec<bool> {
+    let mut dots = vec![false; width * height];
+    let (left, top) = view.top_left(width, height);
+
+    for (x, y) in board.alive_cells() {
+        let dot_x = (x - left) >> view.scale_log2;
+        let dot_y = (y - top) >> view.scale_log2;
+
+        if (0..width as isize).contains(&dot_x) && (0..height as isize).contains(&dot_y) {
+            dots[dot_y as usize * width + dot_x as usize] = true;
+        }
+    }
+
+    dots
+}
+
+/// Draws `dots`, a grid of `columns` by `rows` characters worth of dots, as
+/// lines of `glyphs`.
+fn render(dots: &[bool], columns: usize, rows: usize, glyphs: Glyphs) -> Vec<String> {
+    let (dots_across, dots_down) = glyphs.dots();
+    let width = columns * dots_across;
+
+    (0..rows)
+        .map(|row| {
+            (0..columns)
+                .map(|column| {
+                    glyphs.glyph(|x, y| {
+                        dots[(row * dots_down + y) * width + column * dots_across + x]
+                    })
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Simulates `board` in the terminal until `q` is pressed.
+pub fn run<B: GameOfLifeBoard + Send + 'static>(
+    board: B,
+    rule: Rule,
+    options: &Options,
+) -> Result<(), String> {
+    let center = match board.size() {
+        Some((width, height)) => (width as isize / 2, height as isize / 2),
+        None => (0, 0),
+    };
+    let max_step_log2 = board.max_step_log2();
+    let (controls, snapshot) = crate::spawn_simulation(board, rule, options);
+    let send = |control| {
+        // The simulation thread only stops when the program exits.
+        let _ = controls.send(control);
+    };
+
+    let raw_mode = RawMode::enter()?;
+
+    // The thread is left blocked on stdin when quitting, which is fine since
+    // the program exits straight after.
+    let (keys, key_receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let mut buffer = [0; 64];
+        while let Ok(read @ 1..) = io::stdin().read(&mut buffer) {
+            for key in parse_keys(&buffer[..read]) {
+                if keys.send(key).is_err() {
+                    return;
+                }
+            }
+        }
+    });
+
+    let mut view = View {
+        center,
+        scale_log2: 0,
+    };
+    let mut glyphs = Glyphs::HalfBlocks;
+    let mut status_visible = true;
+    let mut paused = options.paused;
+    let mut speed = options.speed();
+    let mut size = terminal_size();
+    let mut size_checked = Instant::now();
+
+    'main: loop {
+        let mut key = match key_receiver.recv_timeout(FRAME_INTERVAL) {
+            Ok(key) => Some(key),
+            Err(RecvTimeoutError::Timeout) => None,
+            Err(RecvTimeoutError::Disconnected) => break,
+        };
+
+        while let Some(pressed) = key {
+            match pressed {
+                Key::Char('q') | Key::Escape | Key::Interrupt => break 'main,
+                Key::Up | Key::Char('w') => view.pan(0, -1),
+                Key::Down | Key::Char('s') => view.pan(0, 1),
+                Key::Left | Key::Char('a') => view.pan(-1, 0),
+                Key::Right | Key::Char('d') => view.pan(1, 0),
+                Key::Char('i') => view.scale_log2 = view.scale_log2.saturating_sub(1),
+                Key::Char('o') => view.scale_log2 = (view.scale_log2 + 1).min(MAX_SCALE_LOG2),
+                Key::Char('b') => {
+                    glyphs = match glyphs {
+                        Glyphs::HalfBlocks => Glyphs::Braille,
+                        Glyphs::Braille => Glyphs::HalfBlocks,
+                    }
+                }
+                Key::Char('h') => status_visible = !status_visible,
+                Key::Char(' ') => {
+                    paused = !paused;
+                    send(Control::SetPaused(paused));
+                }
+                Key::Char('.') => {
+                    paused = true;
+                    send(Control::Step(1));
+                }
+                Key::Char('n') => {
+                    paused = true;
+                    send(Control::Step(ADVANCE_GENERATIONS));
+                }
+                Key::Char('+' | '=') => {
+                    speed = speed.faster(max_step_log2);
+                    send(Control::SetSpeed(speed));
+                }
+                Key::Char('-') => {
+                    speed = speed.slower();
+                    send(Control::SetSpeed(speed));
+                }
+                Key::Char(',') => {
+                    paused = true;
+                    send(Control::Undo);
+                }
+                _ => (),
+            }
+
+            key = key_receiver.try_recv().ok();
+        }
+
+        if size_checked.elapsed() >= RESIZE_INTERVAL {
+            size = terminal_size();
+            size_checked = Instant::now();
+        }
+
+        let (columns, rows) = size;
+        let board_rows = if status_visible { rows - 1 } else { rows };
+        let (dots_across, dots_down) = glyphs.dots();
+
+        let shown = snapshot.lock().unwrap();
+        let dots = rasterize(
+            &shown.board,
+            &view,
+            columns * dots_across,
+            board_rows * dots_down,
+        );
+        let status = format!(
+            " Generation {}{}  Population {}  {:.1} gen/s  Camera {}, {}  Zoom 1:{} ",
+            shown.generation,
+            if paused { " (paused)" } else { "" },
+            shown.population,
+            shown.generations_per_second,
+            view.center.0,
+            view.center.1,
+            1u64 << view.scale_log2,
+        );
+        drop(shown);
+
+        // Everything is drawn over the last frame in one write to avoid
+        // flickering.
+        let mut frame = String::from("\x1b[H");
+        for line in render(&dots, columns, board_rows, glyphs) {
+            let _ = write!(frame, "{line}\x1b[K\r\n");
+        }
+        if status_visible {
+            let status: String = status.chars().take(columns).collect();
+            let _ = write!(frame, "{}\x1b[K", status.black().on_white());
+        }
+        frame += "\x1b[J";
+
+        let mut stdout = io::stdout().lock();
+        let _ = stdout.write_all(frame.as_bytes());
+        let _ = stdout.flush();
+    }
+
+    drop(raw_mode);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infinite_board::InfiniteBoard;
+
+    #[test]
+    fn test_parse_keys() {
+        assert_eq!(
+            parse_keys(b"q \x1b[A\x1b[D\x03"),
+            [
+                Key::Char('q'),
+                Key::Char(' '),
+                Key::Up,
+                Key::Left,
+                Key::Interrupt
+            ]
+        );
+        assert_eq!(parse_keys(b"\x1b"), [Key::Escape]);
+        assert_eq!(parse_keys(b"\x1bOB"), [Key::Down]);
+        assert_eq!(parse_keys(b"\x1b[5~x"), [Key::Char('x')]);
+        assert_eq!(parse_keys("é\r".as_bytes()), Vec::new());
+    }
+
+    #[test]
+    fn test_glyphs() {
+        assert_eq!(Glyphs::HalfBlocks.glyph(|_, y| y == 0), '▀');
+        assert_eq!(Glyphs::HalfBlocks.glyph(|_, _| true), '█');
+        assert_eq!(Glyphs::Braille.glyph(|_, _| false), '⠀');
+        assert_eq!(Glyphs::Braille.glyph(|_, _| true), '⣿');
+        assert_eq!(Glyphs::Braille.glyph(|x, y| (x, y) == (1, 3)), '⢀');
+        assert_eq!(Glyphs::Braille.glyph(|x, _| x == 0), '⡇');
+    }
+
+    #[test]
+    fn test_rasterize() {
+        let glider = InfiniteBoard::from_cells(&[&[0, 1, 0], &[0, 0, 1], &[1, 1, 1]]);
+        let view = View {
+            center: (2, 2),
+            scale_log2: 0,
+        };
+
+        let dots = rasterize(&glider, &view, 4, 4);
+        assert_eq!(render(&dots, 4, 2, Glyphs::HalfBlocks), [" ▀▄ ", "▀▀▀ "]);
+        assert_eq!(render(&dots, 2, 1, Glyphs::Braille), ["⠬⠆"]);
+
+        // Zoomed out, a dot is lit by any of the cells it covers.
+        let view = View {
+            scale_log2: 1,
+            ..view
+        };
+        let dots = rasterize(&glider, &view, 4, 4);
+        assert_eq!(render(&dots, 4, 2, Glyphs::HalfBlocks), [" ▄▄ ", " ▀▀ "]);
+    }
+}
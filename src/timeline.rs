@@ -0,0 +1,277 @@
+use std::collections::BTreeMap;
+
+use crate::{game_context::GameOfLifeBoard, rule::Rule};
+
+/// Keyframes are initially stored every this many generations.
+const INITIAL_INTERVAL: u64 = 64;
+
+/// Once there are more keyframes than this, or they hold more live cells
+/// than [`MAX_STORED_CELLS`], every other keyframe is thrown away.
+const MAX_KEYFRAMES: usize = 64;
+const MAX_STORED_CELLS: usize = 8_000_000;
+
+struct Keyframe<B> {
+    board: B,
+    population: usize,
+    /// Pinned keyframes are where the board was edited, so they can't be
+    /// regenerated from an earlier keyframe and are kept when thinning out.
+    pinned: bool,
+}
+
+/// Copies of the board at regular generations, which any generation between
+/// them can be regenerated from.
+pub struct Timeline<B> {
+    keyframes: BTreeMap<u64, Keyframe<B>>,
+    interval: u64,
+    latest_generation: u64,
+    stored_cells: usize,
+}
+
+impl<B: GameOfLifeBoard> Timeline<B> {
+    /// Creates a timeline starting with `board` at generation 0.
+    pub fn new(board: &B) -> Self {
+        let mut timeline = Self {
+            keyframes: BTreeMap::new(),
+            interval: INITIAL_INTERVAL,
+            latest_generation: 0,
+            stored_cells: 0,
+        };
+
+        timeline.insert(0, board, true);
+        timeline
+    }
+
+    /// The earliest generation which can still be sought to.
+    pub fn earliest_generation(&self) -> u64 {
+        self.keyframes.keys().next().copied().unwrap_or(0)
+    }
+
+    /// The furthest generation the board has been simulated to since it was
+    /// last edited.
+    pub fn latest_generation(&self) -> u64 {
+        self.latest_generation
+    }
+
+    /// Records the board having been advanced from generation `from` to
+    /// `board` at generation `to`.
+    pub fn record(&mut self, from: u64, to: u64, board: &B) {
+        if to / self.interval > from / self.interval && !self.keyframes.contains_key(&to) {
+            self.insert(to, board, false);
+        }
+
+        self.latest_generation = self.latest_generation.max(to);
+    }
+
+    /// Records `board` having been edited at `generation`, which throws away
+    /// everything after it.
+    pub fn rewrite(&mut self, generation: u64, board: &B) {
+        for keyframe in self.keyframes.split_off(&generation).into_values() {
+            self.stored_cells -= keyframe.population;
+        }
+
+        self.insert(generation, board, true);
+        self.latest_generation = generation;
+    }
+
+    /// Regenerates the board at `generation`, which is clamped to
+    /// [`Timeline::earliest_generation`]. Generations past the latest one
+    /// are simulated and recorded, up to the largest update the board can
+    /// make past it.
+    pub fn seek(&mut self, generation: u64, rule: &Rule) -> (u64, B) {
+        let max_step = 1
+            << self
+                .keyframes
+                .values()
+                .next()
+                .expect("there's always a keyframe at the earliest generation")
+                .board
+                .max_step_log2();
+
+        let generation = generation
+            .min(self.latest_generation.saturating_add(max_step))
+            .max(self.earliest_generation());
+        let (&start, keyframe) = self
+            .keyframes
+            .range(..=generation)
+            .next_back()
+            .expect("there's always a keyframe at the earliest generation");
+
+        let mut board = keyframe.board.clone();
+        let mut current = start;
+
+        while current < generation {
+            let next = (current / self.interval + 1).saturating_mul(self.interval);
+            let next = next.min(generation).min(current + max_step);
+
+            board = board.step(rule, next - current);
+            self.record(current, next, &board);
+            current = next;
+        }
+
+        (generation, board)
+    }
+
+    fn insert(&mut self, generation: u64, board: &B, pinned: bool) {
+        let population = board.population();
+        let keyframe = Keyframe {
+            board: board.clone(),
+            population,
+            pinned,
+        };
+
+        self.stored_cells += population;
+        if let Some(old) = self.keyframes.insert(generation, keyframe) {
+            self.stored_cells -= old.population;
+        }
+
+        self.thin_out();
+    }
+
+    fn thin_out(&mut self) {
+        while self.keyframes.len() > MAX_KEYFRAMES
+            || self.stored_cells > MAX_STORED_CELLS && self.keyframes.len() > 1
+        {
+            let interval = self.interval.saturating_mul(2);
+            let removed: Vec<u64> = self
+                .keyframes
+                .iter()
+                .filter(|(generation, keyframe)| !keyframe.pinned && *generation % interval != 0)
+                .map(|(&generation, _)| generation)
+                .collect();
+
+            // Edits can't be thinned out, so if there are too many of them
+            // the oldest keyframe is forgotten instead.
+            if removed.is_empty() {
+                let (_, oldest) = self.keyframes.pop_first().unwrap();
+                self.stored_cells -= oldest.population;
+                continue;
+            }
+
+            self.interval = interval;
+            for generation in removed {
+                let keyframe = self.keyframes.remove(&generation).unwrap();
+                self.stored_cells -= keyframe.population;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        board::{Anchor, Board, Topology},
+        game_context::{CellEdit, MAX_TICKED_STEP_LOG2},
+        hashlife::{HashLifeBoard, MAX_STEP_LOG2},
+    };
+
+    const GLIDER: &[&[usize]] = &[&[0, 1, 0], &[0, 0, 1], &[1, 1, 1]];
+
+    /// Runs a glider on a torus for `generations` generations, returning
+    /// every state it went through.
+    fn run(timeline: &mut Timeline<Board>, generations: u64) -> Vec<Board> {
+        let mut board = timeline.seek(0, &Rule::CONWAY).1;
+        let mut states = vec![board.clone()];
+
+        for generation in 0..generations {
+            board = board.tick(&Rule::CONWAY);
+            timeline.record(generation, generation + 1, &board);
+            states.push(board.clone());
+        }
+
+        states
+    }
+
+    fn glider() -> Board {
+        Board::from_cells(GLIDER)
+            .resized(16, 16, Anchor::TopLeft)
+            .with_topology(Topology::Torus)
+    }
+
+    #[test]
+    fn test_seek() {
+        let mut timeline = Timeline::new(&glider());
+        let states = run(&mut timeline, 300);
+
+        assert_eq!(timeline.latest_generation(), 300);
+
+        for generation in [0, 1, 63, 64, 65, 200, 300, 150, 7] {
+            let (sought, board) = timeline.seek(generation, &Rule::CONWAY);
+            assert_eq!(sought, generation);
+            assert_eq!(
+                board, states[generation as usize],
+                "generation {generation}"
+            );
+        }
+
+        // Seeking past the end simulates the generations in between.
+        let (_, board) = timeline.seek(1000, &Rule::CONWAY);
+        assert_eq!(board, states[1000 % 64]);
+        assert_eq!(timeline.latest_generation(), 1000);
+        assert!(timeline.keyframes.contains_key(&960));
+    }
+
+    #[test]
+    fn test_seek_far_ahead() {
+        let mut timeline = Timeline::new(&glider());
+        let states = run(&mut timeline, 300);
+
+        // Only as far as the board can go in one update is simulated.
+        let (sought, board) = timeline.seek(u64::MAX, &Rule::CONWAY);
+        assert_eq!(sought, 300 + (1 << MAX_TICKED_STEP_LOG2));
+        assert_eq!(board, states[sought as usize % 64]);
+
+        let blinker = HashLifeBoard::from_alive_cells([(0, 0), (1, 0), (2, 0)]);
+        let mut timeline = Timeline::new(&blinker);
+        assert_eq!(
+            timeline.seek(9_999_999_999_999_999_999, &Rule::CONWAY),
+            (1 << MAX_STEP_LOG2, blinker)
+        );
+    }
+
+    #[test]
+    fn test_rewrite_after_edit() {
+        let mut timeline = Timeline::new(&glider());
+        let states = run(&mut timeline, 200);
+
+        let (_, mut board) = timeline.seek(100, &Rule::CONWAY);
+        board.set_cells(&[CellEdit {
+            x: 10,
+            y: 10,
+            alive: true,
+        }]);
+        timeline.rewrite(100, &board);
+
+        assert_eq!(timeline.latest_generation(), 100);
+        assert_eq!(timeline.seek(99, &Rule::CONWAY).1, states[99]);
+        assert_eq!(timeline.seek(100, &Rule::CONWAY).1, board);
+        assert_eq!(
+            timeline.seek(101, &Rule::CONWAY).1,
+            board.tick(&Rule::CONWAY)
+        );
+    }
+
+    #[test]
+    fn test_thin_out() {
+        let mut timeline = Timeline::new(&glider());
+        let states = run(&mut timeline, 10_000);
+
+        assert!(timeline.keyframes.len() <= MAX_KEYFRAMES);
+        assert!(timeline.interval > INITIAL_INTERVAL);
+        assert_eq!(timeline.earliest_generation(), 0);
+        assert_eq!(timeline.seek(9_999, &Rule::CONWAY).1, states[9_999]);
+
+        // Edits can't be thinned out, so once there are too many of them the
+        // oldest ones are forgotten.
+        for generation in 0..MAX_KEYFRAMES as u64 * 2 {
+            timeline.rewrite(generation, &states[generation as usize]);
+        }
+
+        assert!(timeline.keyframes.len() <= MAX_KEYFRAMES);
+        assert!(timeline.earliest_generation() > 0);
+        assert_eq!(
+            timeline.seek(0, &Rule::CONWAY).0,
+            timeline.earliest_generation()
+        );
+    }
+}
@@ -0,0 +1,566 @@
+//! The zlib format and the deflate compression inside it, as used by PNG.
+//!
+//! Compression finds repeats with hash chains and writes them with the fixed
+//! Huffman codes, which is simple and works well for pictures of cells.
+//! Decompression handles every kind of deflate block.
+
+use std::fmt;
+
+/// How far back repeats can be found.
+const WINDOW_SIZE: usize = 1 << 15;
+
+/// The shortest and longest repeats deflate can express.
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+
+/// The most earlier positions looked at for every repeat.
+const MAX_CHAIN: usize = 64;
+
+const HASH_BITS: u32 = 15;
+
+const END_OF_BLOCK: u16 = 256;
+
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+/// The order the lengths of the code length code are listed in.
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ZlibError(&'static str);
+
+impl fmt::Display for ZlibError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+const TRUNCATED: ZlibError = ZlibError("the compressed data is truncated");
+const TOO_LARGE: ZlibError = ZlibError("the compressed data decompresses to too much data");
+
+/// Compresses `data` into a zlib stream.
+pub fn compress(data: &[u8]) -> Vec<u8> {
+    let mut writer = BitWriter::default();
+
+    // Deflate with a 32K window and no preset dictionary.
+    writer.bytes.extend([0x78, 0x01]);
+
+    // A single final block using the fixed codes.
+    writer.write(0b011, 3);
+
+    let mut matcher = Matcher::new(data.len());
+    let mut position = 0;
+
+    while position < data.len() {
+        let (length, distance) = matcher.longest_match(data, position);
+
+        if length >= MIN_MATCH {
+            write_repeat(&mut writer, length, distance);
+            for position in position..position + length {
+                matcher.insert(data, position);
+            }
+            position += length;
+        } else {
+            write_symbol(&mut writer, u16::from(data[position]));
+            matcher.insert(data, position);
+            position += 1;
+        }
+    }
+
+    write_symbol(&mut writer, END_OF_BLOCK);
+
+    let mut bytes = writer.finish();
+    bytes.extend(adler32(data).to_be_bytes());
+    bytes
+}
+
+/// Decompresses a zlib stream, failing as soon as it decompresses to more
+/// than `limit` bytes.
+pub fn decompress(data: &[u8], limit: usize) -> Result<Vec<u8>, ZlibError> {
+    let [method, flags, ..] = *data else {
+        return Err(TRUNCATED);
+    };
+
+    if method & 0x0f != 8 || (u16::from(method) << 8 | u16::from(flags)) % 31 != 0 {
+        return Err(ZlibError("the compressed data has an invalid header"));
+    }
+    if flags & 0x20 != 0 {
+        return Err(ZlibError("preset dictionaries are not supported"));
+    }
+
+    let mut reader = BitReader {
+        data: &data[2..],
+        position: 0,
+    };
+    let output = inflate(&mut reader, limit)?;
+
+    let checksum_at = 2 + reader.position.div_ceil(8);
+    let checksum = data.get(checksum_at..checksum_at + 4).ok_or(TRUNCATED)?;
+    if checksum != adler32(&output).to_be_bytes() {
+        return Err(ZlibError(
+            "the checksum of the compressed data doesn't match",
+        ));
+    }
+
+    Ok(output)
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (a, b) = data.iter().fold((1u32, 0u32), |(a, b), &byte| {
+        let a = (a + u32::from(byte)) % 65521;
+        (a, (b + a) % 65521)
+    });
+
+    b << 16 | a
+}
+
+/// Finds earlier occurrences of the bytes at a position, by chaining
+/// together the positions starting with the same three bytes.
+struct Matcher {
+    /// The latest position with every hash.
+    head: Vec<usize>,
+    /// The position before every position with the same hash.
+    previous: Vec<usize>,
+}
+
+impl Matcher {
+    fn new(length: usize) -> Self {
+        Self {
+            head: vec![usize::MAX; 1 << HASH_BITS],
+            previous: vec![usize::MAX; length],
+        }
+    }
+
+    fn hash(data: &[u8], position: usize) -> Option<usize> {
+        let bytes = data.get(position..position + MIN_MATCH)?;
+        let bytes = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], 0]);
+        Some((bytes.wrapping_mul(0x9e37_79b1) >> (32 - HASH_BITS)) as usize)
+    }
+
+    fn insert(&mut self, data: &[u8], position: usize) {
+        if let Some(hash) = Self::hash(data, position) {
+            self.previous[position] = self.head[hash];
+            self.head[hash] = position;
+        }
+    }
+
+    /// The length and distance of the longest repeat of the bytes at
+    /// `position`, which is shorter than [`MIN_MATCH`] if there is none.
+    fn longest_match(&self, data: &[u8], position: usize) -> (usize, usize) {
+        let Some(hash) = Self::hash(data, position) else {
+            return (0, 0);
+        };
+        let max_length = MAX_MATCH.min(data.len() - position);
+        let mut best = (0, 0);
+        let mut candidate = self.head[hash];
+
+        for _ in 0..MAX_CHAIN {
+            if candidate == usize::MAX || position - candidate > WINDOW_SIZE {
+                break;
+            }
+
+            let length = data[candidate..]
+                .iter()
+                .zip(&data[position..position + max_length])
+                .take_while(|(a, b)| a == b)
+                .count();
+            if length > best.0 {
+                best = (length, position - candidate);
+                if length == max_length {
+                    break;
+                }
+            }
+
+            candidate = self.previous[candidate];
+        }
+
+        best
+    }
+}
+
+/// Writes a literal byte or the end of block with the fixed literal code.
+fn write_symbol(writer: &mut BitWriter, symbol: u16) {
+    let (code, length) = match symbol {
+        0..=143 => (0x30 + symbol, 8),
+        144..=255 => (0x190 + symbol - 144, 9),
+        256..=279 => (symbol - 256, 7),
+        _ => (0xc0 + symbol - 280, 8),
+    };
+
+    writer.write_huffman(code, length);
+}
+
+fn write_repeat(writer: &mut BitWriter, length: usize, distance: usize) {
+    let index = LENGTH_BASE.partition_point(|&base| usize::from(base) <= length) - 1;
+    write_symbol(writer, 257 + index as u16);
+    writer.write(
+        (length - usize::from(LENGTH_BASE[index])) as u32,
+        LENGTH_EXTRA[index],
+    );
+
+    let index = DISTANCE_BASE.partition_point(|&base| usize::from(base) <= distance) - 1;
+    writer.write_huffman(index as u16, 5);
+    writer.write(
+        (distance - usize::from(DISTANCE_BASE[index])) as u32,
+        DISTANCE_EXTRA[index],
+    );
+}
+
+/// Packs bits into bytes starting from the lowest bits, as deflate does.
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    bits: u32,
+    bit_count: u8,
+}
+
+impl BitWriter {
+    fn write(&mut self, value: u32, count: u8) {
+        self.bits |= value << self.bit_count;
+        self.bit_count += count;
+
+        while self.bit_count >= 8 {
+            self.bytes.push(self.bits as u8);
+            self.bits >>= 8;
+            self.bit_count -= 8;
+        }
+    }
+
+    /// Huffman codes are written starting from their highest bit.
+    fn write_huffman(&mut self, code: u16, length: u8) {
+        let reversed = code.reverse_bits() >> (16 - length);
+        self.write(u32::from(reversed), length);
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.bit_count > 0 {
+            self.bytes.push(self.bits as u8);
+        }
+        self.bytes
+    }
+}
+
+struct BitReader<'a> {
+    data: &'a [u8],
+    /// The number of bits read so far.
+    position: usize,
+}
+
+impl BitReader<'_> {
+    fn bits(&mut self, count: u8) -> Result<u32, ZlibError> {
+        let mut value = 0;
+
+        for bit in 0..count {
+            let byte = self.data.get(self.position / 8).ok_or(TRUNCATED)?;
+            value |= u32::from(byte >> (self.position % 8) & 1) << bit;
+            self.position += 1;
+        }
+
+        Ok(value)
+    }
+
+    /// Skips to the start of the next byte.
+    fn align(&mut self) {
+        self.position = self.position.div_ceil(8) * 8;
+    }
+}
+
+/// A canonical Huffman code, decoded a bit at a time.
+struct Huffman {
+    /// The number of codes of every length.
+    counts: [u16; 16],
+    /// The symbols ordered by their codes.
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    /// Builds the code giving every symbol the code length at its index,
+    /// where 0 means the symbol isn't used.
+    fn new(lengths: &[u8]) -> Result<Self, ZlibError> {
+        let mut counts = [0; 16];
+        for &length in lengths {
+            counts[usize::from(length)] += 1;
+        }
+        counts[0] = 0;
+
+        // Over-subscribed codes can't be decoded.
+        let mut left = 1i32;
+        for &count in &counts[1..] {
+            left = left * 2 - i32::from(count);
+            if left < 0 {
+                return Err(ZlibError("the compressed data has an invalid Huffman code"));
+            }
+        }
+
+        let mut symbols: Vec<u16> = (0..lengths.len() as u16)
+            .filter(|&symbol| lengths[usize::from(symbol)] != 0)
+            .collect();
+        symbols.sort_by_key(|&symbol| lengths[usize::from(symbol)]);
+
+        Ok(Self { counts, symbols })
+    }
+
+    fn decode(&self, reader: &mut BitReader) -> Result<u16, ZlibError> {
+        // The first code of every length and the index of its symbol.
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+
+        for &count in &self.counts[1..] {
+            code |= reader.bits(1)? as i32;
+            let count = i32::from(count);
+
+            if code - first < count {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+
+        Err(ZlibError("the compressed data has an invalid Huffman code"))
+    }
+}
+
+/// Decompresses deflate blocks until the final one.
+fn inflate(reader: &mut BitReader, limit: usize) -> Result<Vec<u8>, ZlibError> {
+    let mut output = Vec::new();
+
+    loop {
+        let last = reader.bits(1)? == 1;
+
+        match reader.bits(2)? {
+            0 => {
+                reader.align();
+                let length = reader.bits(16)?;
+                let complement = reader.bits(16)?;
+                if length != !complement & 0xffff {
+                    return Err(ZlibError("the compressed data has an invalid stored block"));
+                }
+
+                let start = reader.position / 8;
+                let bytes = reader
+                    .data
+                    .get(start..start + length as usize)
+                    .ok_or(TRUNCATED)?;
+                if output.len() + bytes.len() > limit {
+                    return Err(TOO_LARGE);
+                }
+                output.extend(bytes);
+                reader.position += length as usize * 8;
+            }
+            1 => {
+                let mut lengths = [8; 288];
+                lengths[144..256].fill(9);
+                lengths[256..280].fill(7);
+                inflate_block(
+                    reader,
+                    &mut output,
+                    limit,
+                    &Huffman::new(&lengths)?,
+                    &Huffman::new(&[5; 30])?,
+                )?;
+            }
+            2 => {
+                let (literals, distances) = read_dynamic_codes(reader)?;
+                inflate_block(reader, &mut output, limit, &literals, &distances)?;
+            }
+            _ => return Err(ZlibError("the compressed data has an invalid block type")),
+        }
+
+        if last {
+            return Ok(output);
+        }
+    }
+}
+
+/// Reads the literal and distance codes at the start of a dynamic block.
+fn read_dynamic_codes(reader: &mut BitReader) -> Result<(Huffman, Huffman), ZlibError> {
+    let literal_count = reader.bits(5)? as usize + 257;
+    let distance_count = reader.bits(5)? as usize + 1;
+    let code_length_count = reader.bits(4)? as usize + 4;
+
+    let mut code_lengths = [0; 19];
+    for &symbol in &CODE_LENGTH_ORDER[..code_length_count] {
+        code_lengths[symbol] = reader.bits(3)? as u8;
+    }
+    let code_lengths = Huffman::new(&code_lengths)?;
+
+    let mut lengths = Vec::with_capacity(literal_count + distance_count);
+    while lengths.len() < literal_count + distance_count {
+        let (length, repeat) = match code_lengths.decode(reader)? {
+            length @ 0..=15 => (length as u8, 1),
+            16 => {
+                let previous = *lengths
+                    .last()
+                    .ok_or(ZlibError("the compressed data repeats a missing length"))?;
+                (previous, 3 + reader.bits(2)?)
+            }
+            17 => (0, 3 + reader.bits(3)?),
+            _ => (0, 11 + reader.bits(7)?),
+        };
+
+        lengths.extend((0..repeat).map(|_| length));
+    }
+
+    if lengths.len() > literal_count + distance_count {
+        return Err(ZlibError("the compressed data has too many code lengths"));
+    }
+
+    let (literals, distances) = lengths.split_at(literal_count);
+    Ok((Huffman::new(literals)?, Huffman::new(distances)?))
+}
+
+/// Decompresses the symbols of a block up to its end, keeping `output` at
+/// most `limit` bytes long.
+fn inflate_block(
+    reader: &mut BitReader,
+    output: &mut Vec<u8>,
+    limit: usize,
+    literals: &Huffman,
+    distances: &Huffman,
+) -> Result<(), ZlibError> {
+    loop {
+        let symbol = literals.decode(reader)?;
+
+        match symbol {
+            0..=255 if output.len() >= limit => return Err(TOO_LARGE),
+            0..=255 => output.push(symbol as u8),
+            END_OF_BLOCK => return Ok(()),
+            _ => {
+                let index = usize::from(symbol - 257);
+                let (Some(&base), Some(&extra)) = (LENGTH_BASE.get(index), LENGTH_EXTRA.get(index))
+                else {
+                    return Err(ZlibError("the compressed data has an invalid length"));
+                };
+                let length = usize::from(base) + reader.bits(extra)? as usize;
+
+                let index = usize::from(distances.decode(reader)?);
+                let (Some(&base), Some(&extra)) =
+                    (DISTANCE_BASE.get(index), DISTANCE_EXTRA.get(index))
+                else {
+                    return Err(ZlibError("the compressed data has an invalid distance"));
+                };
+                let distance = usize::from(base) + reader.bits(extra)? as usize;
+
+                let start = output
+                    .len()
+                    .checked_sub(distance)
+                    .ok_or(ZlibError("the compressed data refers back too far"))?;
+                if output.len() + length > limit {
+                    return Err(TOO_LARGE);
+                }
+
+                // Repeats can overlap the bytes they produce.
+                for i in start..start + length {
+                    output.push(output[i]);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let mut state = 7u32;
+        let noise: Vec<u8> = (0..100_000)
+            .map(|i| {
+                state = state.wrapping_mul(1664525).wrapping_add(1013904223);
+                if i % 300 < 200 {
+                    (i / 300) as u8
+                } else {
+                    (state >> 24) as u8
+                }
+            })
+            .collect();
+
+        for data in [&[][..], b"a", b"abcabcabcabcabcabd", &[0; 100_000], &noise] {
+            let compressed = compress(data);
+            assert_eq!(decompress(&compressed, data.len()).unwrap(), data);
+        }
+
+        assert!(compress(&[0; 100_000]).len() < 1000);
+    }
+
+    #[test]
+    fn test_decompress_other_encoders() {
+        // Python's `zlib.compress(b"hello hello hello hello world", 9)`, which
+        // uses a fixed block.
+        assert_eq!(
+            decompress(
+                &[
+                    0x78, 0xda, 0xcb, 0x48, 0xcd, 0xc9, 0xc9, 0x57, 0xc8, 0xc0, 0x20, 0xcb, 0xf3,
+                    0x8b, 0x72, 0x52, 0x00, 0xa3, 0x8a, 0x0a, 0xf9,
+                ],
+                100
+            )
+            .unwrap(),
+            b"hello hello hello hello world"
+        );
+
+        // A plaintext pattern compressed by Python into a dynamic block.
+        assert_eq!(
+            decompress(
+                &[
+                    0x78, 0xda, 0x45, 0x8a, 0xc9, 0x09, 0x00, 0x30, 0x0c, 0xc3, 0xfe, 0x1a, 0xc6,
+                    0xab, 0x68, 0xff, 0x6d, 0x9a, 0xa6, 0x09, 0x95, 0xc1, 0x07, 0x38, 0xa6, 0x20,
+                    0xb6, 0x5e, 0x2f, 0x0c, 0x76, 0x8c, 0x29, 0xd7, 0x9c, 0x95, 0x85, 0xdf, 0x91,
+                    0x7d, 0x93, 0x03, 0xf6, 0xf1, 0x10, 0xd5,
+                ],
+                100
+            )
+            .unwrap(),
+            b".O....\n.O.O.O.....\n....O.\nO...O....O...OO\n..OOO..O...O.........\n\
+              .O........\nO\n....O...\n."
+        );
+
+        // A stored block, as written when compression is turned off.
+        let data: Vec<u8> = (0..=255).chain(0..=255).collect();
+        let mut stored = vec![0x78, 0x01, 0x01, 0x00, 0x02, 0xff, 0xfd];
+        stored.extend(&data);
+        stored.extend(adler32(&data).to_be_bytes());
+        assert_eq!(decompress(&stored, data.len()).unwrap(), data);
+    }
+
+    #[test]
+    fn test_decompress_errors() {
+        let compressed = compress(b"hello world");
+
+        assert_eq!(decompress(&compressed[..1], 100), Err(TRUNCATED));
+        assert_eq!(decompress(&compressed[..8], 100), Err(TRUNCATED));
+        assert_eq!(
+            decompress(&[0x78, 0x02], 100),
+            Err(ZlibError("the compressed data has an invalid header"))
+        );
+
+        let mut corrupted = compressed.clone();
+        *corrupted.last_mut().unwrap() ^= 1;
+        assert_eq!(
+            decompress(&corrupted, 100),
+            Err(ZlibError(
+                "the checksum of the compressed data doesn't match"
+            ))
+        );
+
+        assert_eq!(decompress(&compressed, 10), Err(TOO_LARGE));
+        assert_eq!(decompress(&compress(&[0; 100_000]), 99_999), Err(TOO_LARGE));
+    }
+}